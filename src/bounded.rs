@@ -1,9 +1,15 @@
 use bevy::math::{
     bounding::{Aabb2d, Bounded2d, BoundingCircle, BoundingVolume},
-    primitives::{Primitive2d, Rectangle},
-    Isometry2d,
+    primitives::{
+        BoxedPolygon, BoxedPolyline2d, ConvexPolygon, Polygon, Polyline2d, Primitive2d, Rectangle,
+        RegularPolygon, Rhombus, Segment2d, Triangle2d,
+    },
+    Dir2, Isometry2d, Rot2, Vec2,
 };
+use bevy::reflect::Reflect;
 
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point;
 
 impl Primitive2d for Point {}
@@ -23,23 +29,557 @@ impl Bounded2d for Point {
 }
 
 /// Rectangle with rounded corners.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundedRectangle {
+    /// Outer bounds of the shape, corners included.
     pub rect: Rectangle,
+    /// Radius of the corners.
     pub radius: f32,
 }
 
+impl RoundedRectangle {
+    /// Creates a new rectangle with rounded corners from full `width` and `height`,
+    /// corners included, and `radius` of the corners.
+    #[inline]
+    pub fn new(width: f32, height: f32, radius: f32) -> Self {
+        Self {
+            rect: Rectangle::new(width, height),
+            radius,
+        }
+    }
+
+    /// Half size of the rectangle, corners of which are centers of the rounded corners.
+    #[inline]
+    pub fn inner_half_size(&self) -> Vec2 {
+        (self.rect.half_size - Vec2::splat(self.radius)).max(Vec2::ZERO)
+    }
+}
+
 impl Primitive2d for RoundedRectangle {}
 
 impl Bounded<Aabb2d> for RoundedRectangle {
     fn bounding(&self) -> Aabb2d {
         self.rect.bounding()
     }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> Aabb2d {
+        Rectangle {
+            half_size: self.inner_half_size(),
+        }
+        .aabb_2d(isometry)
+        .grow(Vec2::splat(self.radius))
+    }
+}
+
+impl Bounded<BoundingCircle> for RoundedRectangle {
+    fn bounding(&self) -> BoundingCircle {
+        BoundingCircle::new(Vec2::ZERO, self.inner_half_size().length() + self.radius)
+    }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> BoundingCircle {
+        let mut bounding: BoundingCircle = self.bounding();
+        bounding.translate_by(isometry.translation);
+        bounding
+    }
+}
+
+/// Convex polygon, vertices of which are stored in counterclockwise order.
+/// Unlike [`Polygon`], it is guaranteed to be convex, which is validated on creation.
+/// Unlike [`ConvexPolygon`], number of vertices is not known at compile time.
+///
+/// Reflected as an opaque value, so the vertices can't be changed without validating them.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect(opaque, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<Vec2>", into = "Vec<Vec2>")
+)]
+pub struct ConvexShape {
+    vertices: Vec<Vec2>,
+    normals: Vec<Dir2>,
+}
+
+/// Error returned when [`ConvexShape`] can't be created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvexShapeError {
+    /// Polygon has less than 3 distinct vertices.
+    TooFewVertices,
+    /// All the vertices lie on one line.
+    Degenerate,
+    /// Polygon is concave or self-intersecting.
+    NotConvex,
+}
+
+impl std::fmt::Display for ConvexShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConvexShapeError::TooFewVertices => {
+                write!(f, "convex polygon must have at least 3 distinct vertices")
+            }
+            ConvexShapeError::Degenerate => {
+                write!(f, "all vertices of convex polygon lie on one line")
+            }
+            ConvexShapeError::NotConvex => {
+                write!(f, "polygon is concave or self-intersecting")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvexShapeError {}
+
+impl Primitive2d for ConvexShape {}
+
+impl ConvexShape {
+    /// Creates a new convex polygon.
+    /// Vertices can be given in either clockwise or counterclockwise order.
+    /// Duplicated vertices and vertices lying on the edges are removed.
+    pub fn new(points: impl IntoIterator<Item = Vec2>) -> Result<Self, ConvexShapeError> {
+        let mut vertices: Vec<Vec2> = Vec::new();
+        for vertex in points {
+            if vertices.last() != Some(&vertex) {
+                vertices.push(vertex);
+            }
+        }
+        while vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        if vertices.len() < 3 {
+            return Err(ConvexShapeError::TooFewVertices);
+        }
+
+        let signed_area: f32 = (0..vertices.len())
+            .map(|i| vertices[i].perp_dot(vertices[(i + 1) % vertices.len()]))
+            .sum();
+        if signed_area == 0. {
+            return Err(ConvexShapeError::Degenerate);
+        }
+        if signed_area < 0. {
+            vertices.reverse();
+        }
+
+        // Remove vertices lying on the edges
+        let mut i = 0;
+        while i < vertices.len() && vertices.len() >= 3 {
+            let len = vertices.len();
+            let previous = vertices[(i + len - 1) % len];
+            let next = vertices[(i + 1) % len];
+            let turn = (vertices[i] - previous).perp_dot(next - vertices[i]);
+            if turn == 0. {
+                vertices.remove(i);
+            } else if turn < 0. {
+                return Err(ConvexShapeError::NotConvex);
+            } else {
+                i += 1;
+            }
+        }
+
+        let edges = (0..vertices.len())
+            .map(|i| vertices[(i + 1) % vertices.len()] - vertices[i])
+            .collect::<Vec<_>>();
+
+        // Every turn is to the left, but self-intersecting polygon turns more than once around
+        let total_turn: f32 = (0..edges.len())
+            .map(|i| {
+                let next = edges[(i + 1) % edges.len()];
+                edges[i].perp_dot(next).atan2(edges[i].dot(next))
+            })
+            .sum();
+        if total_turn > std::f32::consts::TAU + 0.01 {
+            return Err(ConvexShapeError::NotConvex);
+        }
+
+        let normals = edges
+            .iter()
+            .map(|edge| Dir2::new(Vec2::new(edge.y, -edge.x)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ConvexShapeError::Degenerate)?;
+
+        Ok(Self { vertices, normals })
+    }
+
+    /// Vertices of the polygon in counterclockwise order.
+    #[inline]
+    pub fn vertices(&self) -> &[Vec2] {
+        &self.vertices
+    }
+
+    /// Outward normals of the edges. Normal at index `i` belongs to the edge
+    /// from vertex `i` to vertex `i + 1`.
+    #[inline]
+    pub fn normals(&self) -> &[Dir2] {
+        &self.normals
+    }
+}
+
+impl TryFrom<Vec<Vec2>> for ConvexShape {
+    type Error = ConvexShapeError;
+
+    fn try_from(vertices: Vec<Vec2>) -> Result<Self, Self::Error> {
+        Self::new(vertices)
+    }
+}
+
+impl From<ConvexShape> for Vec<Vec2> {
+    fn from(shape: ConvexShape) -> Self {
+        shape.vertices
+    }
+}
+
+/// Fails for a rectangle with zero width or height.
+impl TryFrom<Rectangle> for ConvexShape {
+    type Error = ConvexShapeError;
+
+    fn try_from(rect: Rectangle) -> Result<Self, Self::Error> {
+        let Rectangle { half_size } = rect;
+        Self::new([
+            Vec2::new(-half_size.x, -half_size.y),
+            Vec2::new(half_size.x, -half_size.y),
+            Vec2::new(half_size.x, half_size.y),
+            Vec2::new(-half_size.x, half_size.y),
+        ])
+    }
+}
+
+impl TryFrom<Triangle2d> for ConvexShape {
+    type Error = ConvexShapeError;
+
+    fn try_from(triangle: Triangle2d) -> Result<Self, Self::Error> {
+        Self::new(triangle.vertices)
+    }
+}
+
+impl TryFrom<Rhombus> for ConvexShape {
+    type Error = ConvexShapeError;
+
+    fn try_from(rhombus: Rhombus) -> Result<Self, Self::Error> {
+        let Rhombus { half_diagonals } = rhombus;
+        Self::new([
+            Vec2::new(half_diagonals.x, 0.),
+            Vec2::new(0., half_diagonals.y),
+            Vec2::new(-half_diagonals.x, 0.),
+            Vec2::new(0., -half_diagonals.y),
+        ])
+    }
+}
+
+impl TryFrom<RegularPolygon> for ConvexShape {
+    type Error = ConvexShapeError;
+
+    fn try_from(polygon: RegularPolygon) -> Result<Self, Self::Error> {
+        Self::new(polygon.vertices(0.))
+    }
+}
+
+impl<const N: usize> TryFrom<ConvexPolygon<N>> for ConvexShape {
+    type Error = ConvexShapeError;
+
+    fn try_from(polygon: ConvexPolygon<N>) -> Result<Self, Self::Error> {
+        Self::new(*polygon.vertices())
+    }
+}
+
+impl<const N: usize> TryFrom<Polygon<N>> for ConvexShape {
+    type Error = ConvexShapeError;
+
+    fn try_from(polygon: Polygon<N>) -> Result<Self, Self::Error> {
+        Self::new(polygon.vertices)
+    }
+}
+
+impl TryFrom<BoxedPolygon> for ConvexShape {
+    type Error = ConvexShapeError;
+
+    fn try_from(polygon: BoxedPolygon) -> Result<Self, Self::Error> {
+        Self::new(polygon.vertices.iter().copied())
+    }
+}
+
+impl Bounded<Aabb2d> for ConvexShape {
+    fn bounding(&self) -> Aabb2d {
+        self.bounding_with_isometry(Isometry2d::IDENTITY)
+    }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> Aabb2d {
+        Aabb2d::from_point_cloud(isometry, &self.vertices)
+    }
+}
+
+impl Bounded<BoundingCircle> for ConvexShape {
+    fn bounding(&self) -> BoundingCircle {
+        self.bounding_with_isometry(Isometry2d::IDENTITY)
+    }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> BoundingCircle {
+        BoundingCircle::from_point_cloud(isometry, &self.vertices)
+    }
+}
+
+/// Chain of connected segments, usually used to describe level geometry.
+///
+/// Front side of every segment is to the left of it's direction,
+/// so chain going from left to right faces up.
+/// Collisions with the ends of the segments that are hidden behind the neighbouring segments
+/// are ignored, so bodies sliding across the joints between segments don't catch on them.
+///
+/// Reflected as an opaque value, so the vertices can't be changed without validating them.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect(opaque, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "EdgeChainData", into = "EdgeChainData")
+)]
+pub struct EdgeChain {
+    vertices: Vec<Vec2>,
+    closed: bool,
+    one_sided: bool,
+    ghost_start: Option<Vec2>,
+    ghost_end: Option<Vec2>,
+}
+
+/// Error returned when [`EdgeChain`] can't be created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeChainError {
+    /// Chain has less than 2 vertices.
+    TooFewVertices,
+}
+
+impl std::fmt::Display for EdgeChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdgeChainError::TooFewVertices => {
+                write!(f, "EdgeChain must contain at least two vertices")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EdgeChainError {}
+
+impl EdgeChain {
+    /// Creates an open chain going through all of the `vertices`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are less than two vertices.
+    pub fn new(vertices: impl IntoIterator<Item = Vec2>) -> Self {
+        Self::try_new(vertices).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Creates an open chain going through all of the `vertices`,
+    /// fails if there are less than two of them.
+    pub fn try_new(vertices: impl IntoIterator<Item = Vec2>) -> Result<Self, EdgeChainError> {
+        let vertices: Vec<Vec2> = vertices.into_iter().collect();
+        if vertices.len() < 2 {
+            return Err(EdgeChainError::TooFewVertices);
+        }
+        Ok(Self {
+            vertices,
+            closed: false,
+            one_sided: false,
+            ghost_start: None,
+            ghost_end: None,
+        })
+    }
+
+    /// Creates a chain going through all of the `vertices`, last of which is connected to the first one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are less than two vertices.
+    pub fn closed(vertices: impl IntoIterator<Item = Vec2>) -> Self {
+        Self {
+            closed: true,
+            ..Self::new(vertices)
+        }
+    }
+
+    /// Makes segments collide only with the bodies that move into them from the front side.
+    #[inline]
+    pub fn with_one_sided(mut self, one_sided: bool) -> Self {
+        self.one_sided = one_sided;
+        self
+    }
+
+    /// Sets ghost vertices, which are the vertices preceding the first vertex
+    /// and following the last vertex of an open chain.
+    /// They don't produce collisions, but hide the ends of the chain the same way neighbouring segments do.
+    /// Useful when the level geometry is split into several chains.
+    #[inline]
+    pub fn with_ghost_vertices(mut self, start: Option<Vec2>, end: Option<Vec2>) -> Self {
+        self.ghost_start = start;
+        self.ghost_end = end;
+        self
+    }
+
+    #[inline]
+    pub fn vertices(&self) -> &[Vec2] {
+        &self.vertices
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    #[inline]
+    pub fn is_one_sided(&self) -> bool {
+        self.one_sided
+    }
+
+    /// Number of segments in the chain.
+    pub fn segments_count(&self) -> usize {
+        match self.vertices.len() {
+            0 | 1 => 0,
+            2 => 1,
+            len if self.closed => len,
+            len => len - 1,
+        }
+    }
+
+    /// Start and end of the segment with index `i`.
+    pub fn segment(&self, i: usize) -> (Vec2, Vec2) {
+        let len = self.vertices.len();
+        (self.vertices[i], self.vertices[(i + 1) % len])
+    }
+
+    /// Vertex preceding the start of the segment with index `i`,
+    /// either from previous segment or a ghost vertex.
+    pub fn previous_vertex(&self, i: usize) -> Option<Vec2> {
+        let len = self.vertices.len();
+        match i {
+            0 if self.closed && len > 2 => Some(self.vertices[len - 1]),
+            0 => self.ghost_start,
+            i => Some(self.vertices[i - 1]),
+        }
+    }
+
+    /// Same chain with all of the vertices rotated around the origin.
+    pub(crate) fn rotated(&self, rotation: Rot2) -> Self {
+        Self {
+            vertices: self
+                .vertices
+                .iter()
+                .map(|&vertex| rotation * vertex)
+                .collect(),
+            ghost_start: self.ghost_start.map(|vertex| rotation * vertex),
+            ghost_end: self.ghost_end.map(|vertex| rotation * vertex),
+            ..*self
+        }
+    }
+
+    /// Vertex following the end of the segment with index `i`,
+    /// either from next segment or a ghost vertex.
+    pub fn next_vertex(&self, i: usize) -> Option<Vec2> {
+        let len = self.vertices.len();
+        if i + 2 < len || (self.closed && len > 2) {
+            Some(self.vertices[(i + 2) % len])
+        } else {
+            self.ghost_end
+        }
+    }
+}
+
+/// Serialized form of the [`EdgeChain`], validated when deserialized.
+#[cfg(feature = "serialize")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EdgeChainData {
+    vertices: Vec<Vec2>,
+    closed: bool,
+    one_sided: bool,
+    ghost_start: Option<Vec2>,
+    ghost_end: Option<Vec2>,
+}
+
+#[cfg(feature = "serialize")]
+impl TryFrom<EdgeChainData> for EdgeChain {
+    type Error = EdgeChainError;
+
+    fn try_from(data: EdgeChainData) -> Result<Self, Self::Error> {
+        Ok(Self {
+            closed: data.closed,
+            one_sided: data.one_sided,
+            ghost_start: data.ghost_start,
+            ghost_end: data.ghost_end,
+            ..Self::try_new(data.vertices)?
+        })
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl From<EdgeChain> for EdgeChainData {
+    fn from(chain: EdgeChain) -> Self {
+        Self {
+            vertices: chain.vertices,
+            closed: chain.closed,
+            one_sided: chain.one_sided,
+            ghost_start: chain.ghost_start,
+            ghost_end: chain.ghost_end,
+        }
+    }
+}
+
+impl Primitive2d for EdgeChain {}
+
+impl From<Segment2d> for EdgeChain {
+    fn from(segment: Segment2d) -> Self {
+        let end = segment.direction * segment.half_length;
+        Self::new([-end, end])
+    }
+}
+
+impl<const N: usize> From<Polyline2d<N>> for EdgeChain {
+    fn from(polyline: Polyline2d<N>) -> Self {
+        Self::new(polyline.vertices)
+    }
+}
+
+impl From<BoxedPolyline2d> for EdgeChain {
+    fn from(polyline: BoxedPolyline2d) -> Self {
+        Self::new(polyline.vertices.iter().copied())
+    }
+}
+
+impl Bounded<Aabb2d> for EdgeChain {
+    fn bounding(&self) -> Aabb2d {
+        self.bounding_with_isometry(Isometry2d::IDENTITY)
+    }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> Aabb2d {
+        Aabb2d::from_point_cloud(isometry, &self.vertices)
+    }
+}
+
+impl Bounded<BoundingCircle> for EdgeChain {
+    fn bounding(&self) -> BoundingCircle {
+        self.bounding_with_isometry(Isometry2d::IDENTITY)
+    }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> BoundingCircle {
+        BoundingCircle::from_point_cloud(isometry, &self.vertices)
+    }
 }
 
 /// A trait similar to [`Bounded2d`] except it is generic and
 /// doesn't require user to provide implementations for translated and rotated shapes
 pub trait Bounded<T: BoundingVolume> {
     fn bounding(&self) -> T;
+
+    /// Bounding volume of the shape, transformed by the `isometry`.
+    /// Default implementation transforms the volume returned by [`bounding`](Bounded::bounding),
+    /// which may be not as tight as the volume computed from the transformed shape.
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> T
+    where
+        T: BoundingVolume<Translation = Vec2, Rotation = Rot2>,
+    {
+        let mut bounding = self.bounding();
+        bounding.rotate_by(isometry.rotation);
+        bounding.translate_by(isometry.translation);
+        bounding
+    }
 }
 
 mod bounded_impls {
@@ -50,7 +590,7 @@ mod bounded_impls {
     use bevy::math::{
         bounding::{Aabb2d, Bounded2d, BoundingCircle},
         primitives::*,
-        Vec2,
+        Isometry2d, Vec2,
     };
 
     macro_rules! impl_bounded_for_bounded2d {
@@ -60,12 +600,20 @@ mod bounded_impls {
                     fn bounding(&self) -> Aabb2d {
                         Bounded2d::aabb_2d(self, Vec2::ZERO)
                     }
+
+                    fn bounding_with_isometry(&self, isometry: Isometry2d) -> Aabb2d {
+                        Bounded2d::aabb_2d(self, isometry)
+                    }
                 }
 
                 impl Bounded<BoundingCircle> for $t {
                     fn bounding(&self) -> BoundingCircle {
                         Bounded2d::bounding_circle(self, Vec2::ZERO)
                     }
+
+                    fn bounding_with_isometry(&self, isometry: Isometry2d) -> BoundingCircle {
+                        Bounded2d::bounding_circle(self, isometry)
+                    }
                 }
             )*
         };
@@ -78,12 +626,20 @@ mod bounded_impls {
                     fn bounding(&self) -> Aabb2d {
                         Bounded2d::aabb_2d(self, Vec2::ZERO)
                     }
+
+                    fn bounding_with_isometry(&self, isometry: Isometry2d) -> Aabb2d {
+                        Bounded2d::aabb_2d(self, isometry)
+                    }
                 }
 
                 impl<const N: usize> Bounded<BoundingCircle> for $t<N> {
                     fn bounding(&self) -> BoundingCircle {
                         Bounded2d::bounding_circle(self, Vec2::ZERO)
                     }
+
+                    fn bounding_with_isometry(&self, isometry: Isometry2d) -> BoundingCircle {
+                        Bounded2d::bounding_circle(self, isometry)
+                    }
                 }
             )*
         };
@@ -1,9 +1,27 @@
-use crate::bounded::{Bounded, Point, RoundedRectangle};
-use bevy::math::{
-    bounding::{Aabb2d, BoundingVolume},
-    primitives::{Circle, Rectangle},
-    Dir2, Vec2,
+use crate::bounded::{Bounded, ConvexShape, EdgeChain, Point, RoundedRectangle};
+use bevy::{
+    math::{
+        bounding::{Aabb2d, IntersectsVolume},
+        primitives::{Capsule2d, Circle, Rectangle, Segment2d},
+        Dir2, Isometry2d, Rot2, Vec2,
+    },
+    reflect::Reflect,
 };
+use chain::{chain_cast, chain_intersect, chains_cast, chains_intersect};
+use convex::{
+    convex_cast_isometry, convex_intersect_isometry, polygons_intersect,
+    rectangle_polygon_intersect, AsRoundedConvex,
+};
+
+mod any_shape;
+mod chain;
+mod compound;
+mod convex;
+mod distance;
+mod penetration;
+
+pub use any_shape::AnyShape;
+pub use compound::Compound;
 
 /// Trait allows `Self` to perform collisions with `T`
 pub trait ColliderInteraction<T> {
@@ -21,20 +39,177 @@ pub trait ColliderInteraction<T> {
         offset_dir: Dir2,
         offset_len: f32,
     ) -> Option<(f32, Dir2)>;
+
+    /// Same as [`cast`](ColliderInteraction::cast),
+    /// but also returns which parts of `self` and `other` collided
+    fn cast_parts(
+        &self,
+        self_position: Vec2,
+        other: &T,
+        other_position: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        self.cast(self_position, other, other_position, offset_dir, offset_len)
+            .map(|(distance, normal)| (distance, normal, ColliderParts::default()))
+    }
+
+    /// Same as [`intersect`](ColliderInteraction::intersect), but shapes are also rotated.
+    /// Only used by the groups with [`ColliderGroup::USE_ROTATION`](crate::ColliderGroup::USE_ROTATION).
+    /// Default implementation ignores rotation, which is only correct if neither of the shapes
+    /// changes when rotated, like [`Circle`], shapes that do change must override it.
+    fn intersect_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &T,
+        other_isometry: Isometry2d,
+    ) -> bool {
+        self.intersect(self_isometry.translation, other, other_isometry.translation)
+    }
+
+    /// Same as [`cast_parts`](ColliderInteraction::cast_parts), but shapes are also rotated.
+    /// Default implementation ignores rotation, same as the one of
+    /// [`intersect_isometry`](ColliderInteraction::intersect_isometry).
+    fn cast_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &T,
+        other_isometry: Isometry2d,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        self.cast_parts(
+            self_isometry.translation,
+            other,
+            other_isometry.translation,
+            offset_dir,
+            offset_len,
+        )
+    }
+}
+
+/// Indices of the parts of the colliding shapes.
+/// Only [`Compound`] consists of several parts, any other shape is a single part with index 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct ColliderParts {
+    /// Part of the moving shape, `self` of [`ColliderInteraction`]
+    pub hitbox: usize,
+    /// Part of the shape it collided with, `other` of [`ColliderInteraction`]
+    pub hurtbox: usize,
+}
+
+/// Allows `Self` to find out how deep it overlaps with `T`
+pub trait ColliderPenetration<T>: ColliderInteraction<T> {
+    /// Returns [`Penetration`] if `self` overlaps or touches `other`
+    fn penetration(
+        &self,
+        self_isometry: Isometry2d,
+        other: &T,
+        other_isometry: Isometry2d,
+    ) -> Option<Penetration>;
+}
+
+/// Describes how two overlapping shapes can be separated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Penetration {
+    /// Distance that shapes overlap along the normal
+    pub depth: f32,
+    /// Direction from `other` to `self`, in which `self` should be moved to stop overlapping
+    pub normal: Dir2,
+    contacts: [Vec2; 2],
+    contacts_count: usize,
+}
+
+impl Penetration {
+    /// Creates new penetration, only first two of the `contacts` are used.
+    pub fn new(depth: f32, normal: Dir2, contacts: impl IntoIterator<Item = Vec2>) -> Self {
+        let mut result = Self {
+            depth,
+            normal,
+            contacts: [Vec2::ZERO; 2],
+            contacts_count: 0,
+        };
+        for (contact, slot) in contacts.into_iter().zip(&mut result.contacts) {
+            *slot = contact;
+            result.contacts_count += 1;
+        }
+        result
+    }
+
+    /// Minimum translation vector, moving `self` by which resolves the overlap.
+    #[inline]
+    pub fn translation(&self) -> Vec2 {
+        self.normal * self.depth
+    }
+
+    /// One or two points, lying halfway between the surfaces of the shapes, at which they touch.
+    #[inline]
+    pub fn contacts(&self) -> &[Vec2] {
+        &self.contacts[..self.contacts_count]
+    }
+
+    /// Same penetration, as seen from `other`.
+    pub fn reversed(self) -> Self {
+        Self {
+            normal: -self.normal,
+            ..self
+        }
+    }
+}
+
+/// Allows `Self` to find out how far it is from `T`
+pub trait ColliderDistance<T>: ColliderInteraction<T> {
+    /// Returns [`Distance`] between `self` and `other`.
+    /// Returns `None` only if one of the shapes has no parts to measure distance to.
+    fn distance(
+        &self,
+        self_isometry: Isometry2d,
+        other: &T,
+        other_isometry: Isometry2d,
+    ) -> Option<Distance>;
+}
+
+/// Signed distance between two shapes and the closest points on them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distance {
+    /// Distance between the shapes, negative if they overlap
+    pub distance: f32,
+    /// Direction from `other` to `self`, along which the distance is measured
+    pub normal: Dir2,
+    /// Closest point on `self`.
+    /// If shapes overlap, it is the point of `self` that is the deepest inside of `other`
+    pub point: Vec2,
+    /// Closest point on `other`.
+    /// If shapes overlap, it is the point of `other` that is the deepest inside of `self`
+    pub other_point: Vec2,
+}
+
+impl Distance {
+    /// Same distance, as seen from `other`.
+    pub fn reversed(self) -> Self {
+        Self {
+            distance: self.distance,
+            normal: -self.normal,
+            point: self.other_point,
+            other_point: self.point,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Collider<'a, S> {
     pub shape: &'a S,
     pub position: Vec2,
+    /// Rotation of the shape around the position.
+    /// Collisions of rotated colliders are resolved by
+    /// [`intersect_isometry`](ColliderInteraction::intersect_isometry) and
+    /// [`cast_isometry`](ColliderInteraction::cast_isometry).
+    pub rotation: Rot2,
 }
 
 impl<'a, S> Clone for Collider<'a, S> {
     fn clone(&self) -> Self {
-        Self {
-            shape: self.shape,
-            position: self.position,
-        }
+        *self
     }
 }
 
@@ -42,13 +217,41 @@ impl<'a, S> Copy for Collider<'a, S> {}
 
 impl<'a, S> Collider<'a, S> {
     pub fn new(shape: &'a S, position: Vec2) -> Self {
-        Self { shape, position }
+        Self {
+            shape,
+            position,
+            rotation: Rot2::IDENTITY,
+        }
+    }
+
+    pub fn from_isometry(shape: &'a S, isometry: Isometry2d) -> Self {
+        Self {
+            shape,
+            position: isometry.translation,
+            rotation: isometry.rotation,
+        }
+    }
+
+    #[inline]
+    pub fn isometry(&self) -> Isometry2d {
+        Isometry2d::new(self.position, self.rotation)
+    }
+
+    #[inline]
+    fn is_rotated<O>(&self, other: &Collider<'a, O>) -> bool {
+        self.rotation != Rot2::IDENTITY || other.rotation != Rot2::IDENTITY
     }
 
     pub fn intersect<O>(&self, other: Collider<'a, O>) -> bool
     where
         S: ColliderInteraction<O>,
     {
+        if self.is_rotated(&other) {
+            return self
+                .shape
+                .intersect_isometry(self.isometry(), other.shape, other.isometry());
+        }
+
         self.shape
             .intersect(self.position, other.shape, other.position)
     }
@@ -61,6 +264,12 @@ impl<'a, S> Collider<'a, S> {
     where
         S: ColliderInteraction<O>,
     {
+        if self.is_rotated(&other) {
+            return self
+                .cast_parts(other, offset_dir, offset_len)
+                .map(|(distance, normal, _)| (distance, normal));
+        }
+
         self.shape.cast(
             self.position,
             other.shape,
@@ -69,10 +278,58 @@ impl<'a, S> Collider<'a, S> {
             offset_len,
         )
     }
+
+    pub fn penetration<O>(&self, other: Collider<'a, O>) -> Option<Penetration>
+    where
+        S: ColliderPenetration<O>,
+    {
+        self.shape
+            .penetration(self.isometry(), other.shape, other.isometry())
+    }
+
+    pub fn distance<O>(&self, other: Collider<'a, O>) -> Option<Distance>
+    where
+        S: ColliderDistance<O>,
+    {
+        self.shape
+            .distance(self.isometry(), other.shape, other.isometry())
+    }
+
+    pub fn cast_parts<O>(
+        &self,
+        other: Collider<'a, O>,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)>
+    where
+        S: ColliderInteraction<O>,
+    {
+        if self.is_rotated(&other) {
+            return self.shape.cast_isometry(
+                self.isometry(),
+                other.shape,
+                other.isometry(),
+                offset_dir,
+                offset_len,
+            );
+        }
+
+        self.shape.cast_parts(
+            self.position,
+            other.shape,
+            other.position,
+            offset_dir,
+            offset_len,
+        )
+    }
 }
 
 impl<'a, S: Bounded<Aabb2d>> Bounded<Aabb2d> for Collider<'a, S> {
     fn bounding(&self) -> Aabb2d {
+        if self.rotation != Rot2::IDENTITY {
+            return self.shape.bounding_with_isometry(self.isometry());
+        }
+
         let shape_bounding = self.shape.bounding();
         Aabb2d {
             min: shape_bounding.min + self.position,
@@ -96,6 +353,92 @@ impl<C, T: ?Sized + ColliderInteraction<C>> ColliderInteraction<C> for Box<T> {
     ) -> Option<(f32, Dir2)> {
         (**self).cast(self_position, other, other_position, offset_dir, offset_len)
     }
+
+    fn cast_parts(
+        &self,
+        self_position: Vec2,
+        other: &C,
+        other_position: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        (**self).cast_parts(self_position, other, other_position, offset_dir, offset_len)
+    }
+
+    fn intersect_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &C,
+        other_isometry: Isometry2d,
+    ) -> bool {
+        (**self).intersect_isometry(self_isometry, other, other_isometry)
+    }
+
+    fn cast_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &C,
+        other_isometry: Isometry2d,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        (**self).cast_isometry(self_isometry, other, other_isometry, offset_dir, offset_len)
+    }
+}
+
+impl<C, T: ?Sized + ColliderPenetration<C>> ColliderPenetration<C> for Box<T> {
+    fn penetration(
+        &self,
+        self_isometry: Isometry2d,
+        other: &C,
+        other_isometry: Isometry2d,
+    ) -> Option<Penetration> {
+        (**self).penetration(self_isometry, other, other_isometry)
+    }
+}
+
+impl<C, T: ?Sized + ColliderDistance<C>> ColliderDistance<C> for Box<T> {
+    fn distance(
+        &self,
+        self_isometry: Isometry2d,
+        other: &C,
+        other_isometry: Isometry2d,
+    ) -> Option<Distance> {
+        (**self).distance(self_isometry, other, other_isometry)
+    }
+}
+
+/// Implements rotated collisions through the Minkowski difference of the rotated shapes.
+/// Used inside of `impl ColliderInteraction<$other> for Self` block.
+macro_rules! isometry_as_convex {
+    ($other:ty) => {
+        fn intersect_isometry(
+            &self,
+            self_isometry: Isometry2d,
+            other: &$other,
+            other_isometry: Isometry2d,
+        ) -> bool {
+            convex_intersect_isometry(self, self_isometry, other, other_isometry)
+        }
+
+        fn cast_isometry(
+            &self,
+            self_isometry: Isometry2d,
+            other: &$other,
+            other_isometry: Isometry2d,
+            offset_dir: Dir2,
+            offset_len: f32,
+        ) -> Option<(f32, Dir2, ColliderParts)> {
+            convex_cast_isometry(
+                self,
+                self_isometry,
+                other,
+                other_isometry,
+                offset_dir,
+                offset_len,
+            )
+        }
+    };
 }
 
 //
@@ -134,6 +477,8 @@ impl ColliderInteraction<Point> for Point {
 }
 
 impl ColliderInteraction<Rectangle> for Rectangle {
+    isometry_as_convex!(Rectangle);
+
     fn intersect(&self, self_position: Vec2, other: &Rectangle, other_position: Vec2) -> bool {
         let self_aabb = Aabb2d {
             min: self_position - self.half_size,
@@ -144,7 +489,7 @@ impl ColliderInteraction<Rectangle> for Rectangle {
             max: other_position + other.half_size,
         };
 
-        self_aabb.contains(&other_aabb)
+        self_aabb.intersects(&other_aabb)
     }
 
     fn cast(
@@ -164,6 +509,8 @@ impl ColliderInteraction<Rectangle> for Rectangle {
 }
 
 impl ColliderInteraction<Rectangle> for Point {
+    isometry_as_convex!(Rectangle);
+
     fn intersect(&self, self_position: Vec2, other: &Rectangle, other_position: Vec2) -> bool {
         let aabb = Aabb2d {
             min: other_position - other.half_size,
@@ -189,6 +536,28 @@ impl ColliderInteraction<Rectangle> for Point {
             max: other_position + other.half_size,
         };
 
+        // Point that already touches the rectangle collides only if it moves
+        // through the closest side, so it is able to slide along or move away from it
+        if self.intersect(self_position, other, other_position) {
+            let to_min = self_position - aabb.min;
+            let to_max = aabb.max - self_position;
+            let (_, normal) = [
+                (to_max.x, Dir2::X),
+                (to_min.y, Dir2::NEG_Y),
+                (to_max.y, Dir2::Y),
+            ]
+            .into_iter()
+            .fold((to_min.x, Dir2::NEG_X), |closest, side| {
+                if side.0 < closest.0 {
+                    side
+                } else {
+                    closest
+                }
+            });
+
+            return (offset_dir.dot(*normal) < 0.).then_some((0., normal));
+        }
+
         let (min_x, max_x) = if offset_dir.x.is_sign_positive() {
             (aabb.min.x, aabb.max.x)
         } else {
@@ -240,6 +609,8 @@ impl ColliderInteraction<Rectangle> for Point {
 }
 
 impl ColliderInteraction<Point> for Rectangle {
+    isometry_as_convex!(Point);
+
     #[inline]
     fn intersect(&self, self_position: Vec2, other: &Point, other_position: Vec2) -> bool {
         other.intersect(other_position, self, self_position)
@@ -337,6 +708,8 @@ impl ColliderInteraction<Point> for Circle {
 }
 
 impl ColliderInteraction<Rectangle> for Circle {
+    isometry_as_convex!(Rectangle);
+
     fn intersect(&self, self_position: Vec2, other: &Rectangle, other_position: Vec2) -> bool {
         let rounded = RoundedRectangle {
             rect: Rectangle {
@@ -373,6 +746,8 @@ impl ColliderInteraction<Rectangle> for Circle {
 }
 
 impl ColliderInteraction<Circle> for Rectangle {
+    isometry_as_convex!(Circle);
+
     fn intersect(&self, self_position: Vec2, other: &Circle, other_position: Vec2) -> bool {
         other.intersect(other_position, self, self_position)
     }
@@ -391,14 +766,17 @@ impl ColliderInteraction<Circle> for Rectangle {
 }
 
 impl ColliderInteraction<RoundedRectangle> for Point {
+    isometry_as_convex!(RoundedRectangle);
+
     fn intersect(
         &self,
         self_position: Vec2,
         other: &RoundedRectangle,
         other_position: Vec2,
     ) -> bool {
-        // TODO: this is wrong
-        other.rect.intersect(other_position, self, self_position)
+        let inner = other.inner_half_size();
+        let outside_inner = ((self_position - other_position).abs() - inner).max(Vec2::ZERO);
+        outside_inner.length_squared() <= other.radius.powi(2)
     }
 
     fn cast(
@@ -409,31 +787,453 @@ impl ColliderInteraction<RoundedRectangle> for Point {
         offset_dir: Dir2,
         offset_len: f32,
     ) -> Option<(f32, Dir2)> {
-        let Some((rect_dist, rect_norm)) = self.cast(
-            self_position,
-            &other.rect,
-            other_position,
-            offset_dir,
-            offset_len,
-        ) else {
-            return None;
+        let inner = other.inner_half_size();
+
+        // Point that already touches the rectangle collides only if it moves
+        // through the closest side, so it is able to slide along or move away from it
+        if self.intersect(self_position, other, other_position) {
+            let relative = self_position - other_position;
+            let outside_inner = relative - relative.clamp(-inner, inner);
+
+            let normal = match Dir2::new(outside_inner) {
+                Ok(normal) => normal,
+                Err(_) => {
+                    let to_side = other.rect.half_size - relative.abs();
+                    if to_side.x < to_side.y {
+                        Dir2::new_unchecked(Vec2::new(relative.x.signum(), 0.))
+                    } else {
+                        Dir2::new_unchecked(Vec2::new(0., relative.y.signum()))
+                    }
+                }
+            };
+
+            return (offset_dir.dot(*normal) < 0.).then_some((0., normal));
+        }
+
+        // Rounded rectangle is a union of two rectangles crossing each other
+        // and four circles at the corners.
+        // Corners are checked first, so they win ties at the ends of the sides
+        let horizontal = Rectangle {
+            half_size: Vec2::new(other.rect.half_size.x, inner.y),
         };
+        let vertical = Rectangle {
+            half_size: Vec2::new(inner.x, other.rect.half_size.y),
+        };
+        let corner = Circle::new(other.radius);
 
-        let collision_relative_to_other_pos = offset_dir * rect_dist - other_position;
-        let before_radius = other.rect.half_size - Vec2::ONE * other.radius;
-        let abs_collision = collision_relative_to_other_pos.abs();
-        if abs_collision.x > before_radius.x && abs_collision.y > before_radius.y {
+        let rect_casts = [horizontal, vertical]
+            .map(|rect| self.cast(self_position, &rect, other_position, offset_dir, offset_len));
+        let corner_casts = [
+            Vec2::new(inner.x, inner.y),
+            Vec2::new(-inner.x, inner.y),
+            Vec2::new(inner.x, -inner.y),
+            Vec2::new(-inner.x, -inner.y),
+        ]
+        .map(|corner_position| {
             self.cast(
                 self_position,
-                &Circle::new(other.radius),
-                other_position,
+                &corner,
+                other_position + corner_position,
                 offset_dir,
                 offset_len,
             )
-        } else {
-            Some((rect_dist, rect_norm))
+        });
+
+        corner_casts
+            .into_iter()
+            .chain(rect_casts)
+            .flatten()
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+    }
+}
+
+/// Implements `ColliderInteraction<$other> for $self` by casting [`Point`]
+/// against the Minkowski sum of both shapes, which is a [`RoundedRectangle`].
+/// `$sum` is a function taking `&$self` and `&$other` and returning the sum.
+macro_rules! impl_interaction_with_rounded_sum {
+    ($self:ty, $other:ty, $sum:expr) => {
+        impl ColliderInteraction<$other> for $self {
+            isometry_as_convex!($other);
+
+            fn intersect(&self, self_position: Vec2, other: &$other, other_position: Vec2) -> bool {
+                let sum: RoundedRectangle = ($sum)(self, other);
+                Point.intersect(self_position, &sum, other_position)
+            }
+
+            fn cast(
+                &self,
+                self_position: Vec2,
+                other: &$other,
+                other_position: Vec2,
+                offset_dir: Dir2,
+                offset_len: f32,
+            ) -> Option<(f32, Dir2)> {
+                let sum: RoundedRectangle = ($sum)(self, other);
+                Point.cast(self_position, &sum, other_position, offset_dir, offset_len)
+            }
         }
+    };
+}
+
+/// Minkowski sum of the capsule and the rectangle with rounded corners,
+/// given by its half size and radius of the corners.
+fn capsule_sum(capsule: &Capsule2d, half_size: Vec2, radius: f32) -> RoundedRectangle {
+    RoundedRectangle {
+        rect: Rectangle {
+            half_size: half_size + Vec2::new(capsule.radius, capsule.half_length + capsule.radius),
+        },
+        radius: radius + capsule.radius,
     }
 }
 
-// TODO: rounded rectangle implementations
\ No newline at end of file
+impl_interaction_with_rounded_sum!(Capsule2d, Point, |capsule, _| capsule_sum(
+    capsule,
+    Vec2::ZERO,
+    0.
+));
+impl_interaction_with_rounded_sum!(Point, Capsule2d, |_, capsule| capsule_sum(
+    capsule,
+    Vec2::ZERO,
+    0.
+));
+impl_interaction_with_rounded_sum!(Capsule2d, Rectangle, |capsule, rect: &Rectangle| {
+    capsule_sum(capsule, rect.half_size, 0.)
+});
+impl_interaction_with_rounded_sum!(Rectangle, Capsule2d, |rect: &Rectangle, capsule| {
+    capsule_sum(capsule, rect.half_size, 0.)
+});
+impl_interaction_with_rounded_sum!(Capsule2d, Circle, |capsule, circle: &Circle| capsule_sum(
+    capsule,
+    Vec2::splat(circle.radius),
+    circle.radius
+));
+impl_interaction_with_rounded_sum!(Circle, Capsule2d, |circle: &Circle, capsule| capsule_sum(
+    capsule,
+    Vec2::splat(circle.radius),
+    circle.radius
+));
+impl_interaction_with_rounded_sum!(Capsule2d, Capsule2d, |capsule, other: &Capsule2d| {
+    capsule_sum(
+        capsule,
+        Vec2::new(other.radius, other.half_length + other.radius),
+        other.radius,
+    )
+});
+impl_interaction_with_rounded_sum!(
+    Capsule2d,
+    RoundedRectangle,
+    |capsule, rounded: &RoundedRectangle| capsule_sum(
+        capsule,
+        rounded.rect.half_size,
+        rounded.radius
+    )
+);
+impl_interaction_with_rounded_sum!(
+    RoundedRectangle,
+    Capsule2d,
+    |rounded: &RoundedRectangle, capsule| capsule_sum(
+        capsule,
+        rounded.rect.half_size,
+        rounded.radius
+    )
+);
+
+/// Minkowski sum of the rectangle with rounded corners and other rectangle with rounded corners,
+/// given by its half size and radius of the corners.
+fn rounded_sum(rounded: &RoundedRectangle, half_size: Vec2, radius: f32) -> RoundedRectangle {
+    RoundedRectangle {
+        rect: Rectangle {
+            half_size: rounded.rect.half_size + half_size,
+        },
+        radius: rounded.radius + radius,
+    }
+}
+
+impl_interaction_with_rounded_sum!(RoundedRectangle, Point, |rounded, _| rounded_sum(
+    rounded,
+    Vec2::ZERO,
+    0.
+));
+impl_interaction_with_rounded_sum!(RoundedRectangle, Rectangle, |rounded, rect: &Rectangle| {
+    rounded_sum(rounded, rect.half_size, 0.)
+});
+impl_interaction_with_rounded_sum!(Rectangle, RoundedRectangle, |rect: &Rectangle, rounded| {
+    rounded_sum(rounded, rect.half_size, 0.)
+});
+impl_interaction_with_rounded_sum!(RoundedRectangle, Circle, |rounded, circle: &Circle| {
+    rounded_sum(rounded, Vec2::splat(circle.radius), circle.radius)
+});
+impl_interaction_with_rounded_sum!(Circle, RoundedRectangle, |circle: &Circle, rounded| {
+    rounded_sum(rounded, Vec2::splat(circle.radius), circle.radius)
+});
+impl_interaction_with_rounded_sum!(
+    RoundedRectangle,
+    RoundedRectangle,
+    |rounded, other: &RoundedRectangle| rounded_sum(rounded, other.rect.half_size, other.radius)
+);
+
+/// Implements `ColliderInteraction<$other> for $self` by casting point
+/// against the Minkowski difference of both shapes.
+/// `$intersect` optionally overrides `intersect` implementation.
+macro_rules! impl_interaction_with_convex {
+    ($self:ty, $other:ty) => {
+        impl_interaction_with_convex!(
+            $self,
+            $other,
+            |a: &$self, a_position, b: &$other, b_position| {
+                a.as_rounded_convex()
+                    .minkowski_difference(&b.as_rounded_convex())
+                    .contains(a_position - b_position)
+            }
+        );
+    };
+    ($self:ty, $other:ty, $intersect:expr) => {
+        impl ColliderInteraction<$other> for $self {
+            isometry_as_convex!($other);
+
+            fn intersect(&self, self_position: Vec2, other: &$other, other_position: Vec2) -> bool {
+                ($intersect)(self, self_position, other, other_position)
+            }
+
+            fn cast(
+                &self,
+                self_position: Vec2,
+                other: &$other,
+                other_position: Vec2,
+                offset_dir: Dir2,
+                offset_len: f32,
+            ) -> Option<(f32, Dir2)> {
+                self.as_rounded_convex()
+                    .minkowski_difference(&other.as_rounded_convex())
+                    .cast_point(self_position - other_position, offset_dir, offset_len)
+            }
+        }
+    };
+}
+
+impl_interaction_with_convex!(ConvexShape, ConvexShape, polygons_intersect);
+impl_interaction_with_convex!(
+    ConvexShape,
+    Rectangle,
+    |polygon, polygon_position, rect: &Rectangle, rect_position| {
+        rectangle_polygon_intersect(rect, rect_position, polygon, polygon_position)
+    }
+);
+impl_interaction_with_convex!(
+    Rectangle,
+    ConvexShape,
+    |rect: &Rectangle, rect_position, polygon, polygon_position| {
+        rectangle_polygon_intersect(rect, rect_position, polygon, polygon_position)
+    }
+);
+impl_interaction_with_convex!(ConvexShape, Point);
+impl_interaction_with_convex!(Point, ConvexShape);
+impl_interaction_with_convex!(ConvexShape, Circle);
+impl_interaction_with_convex!(Circle, ConvexShape);
+impl_interaction_with_convex!(ConvexShape, Capsule2d);
+impl_interaction_with_convex!(Capsule2d, ConvexShape);
+impl_interaction_with_convex!(ConvexShape, RoundedRectangle);
+impl_interaction_with_convex!(RoundedRectangle, ConvexShape);
+impl_interaction_with_convex!(Point, Segment2d);
+impl_interaction_with_convex!(Circle, Segment2d);
+impl_interaction_with_convex!(Rectangle, Segment2d);
+impl_interaction_with_convex!(Capsule2d, Segment2d);
+impl_interaction_with_convex!(RoundedRectangle, Segment2d);
+impl_interaction_with_convex!(ConvexShape, Segment2d);
+impl_interaction_with_convex!(Segment2d, Point);
+impl_interaction_with_convex!(Segment2d, Circle);
+impl_interaction_with_convex!(Segment2d, Rectangle);
+impl_interaction_with_convex!(Segment2d, Capsule2d);
+impl_interaction_with_convex!(Segment2d, RoundedRectangle);
+impl_interaction_with_convex!(Segment2d, ConvexShape);
+impl_interaction_with_convex!(Segment2d, Segment2d);
+
+/// Implements `ColliderInteraction<EdgeChain> for $self`.
+macro_rules! impl_interaction_with_chain {
+    ($($self:ty),*) => {
+        $(
+            impl ColliderInteraction<EdgeChain> for $self {
+                fn intersect(&self, self_position: Vec2, other: &EdgeChain, other_position: Vec2) -> bool {
+                    chain_intersect(&self.as_rounded_convex(), self_position, other, other_position)
+                }
+
+                fn cast(
+                    &self,
+                    self_position: Vec2,
+                    other: &EdgeChain,
+                    other_position: Vec2,
+                    offset_dir: Dir2,
+                    offset_len: f32,
+                ) -> Option<(f32, Dir2)> {
+                    chain_cast(
+                        &self.as_rounded_convex(),
+                        self_position,
+                        other,
+                        other_position,
+                        offset_dir,
+                        offset_len,
+                    )
+                }
+
+                fn intersect_isometry(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &EdgeChain,
+                    other_isometry: Isometry2d,
+                ) -> bool {
+                    chain_intersect(
+                        &self.as_rounded_convex().rotated(self_isometry.rotation),
+                        self_isometry.translation,
+                        &other.rotated(other_isometry.rotation),
+                        other_isometry.translation,
+                    )
+                }
+
+                fn cast_isometry(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &EdgeChain,
+                    other_isometry: Isometry2d,
+                    offset_dir: Dir2,
+                    offset_len: f32,
+                ) -> Option<(f32, Dir2, ColliderParts)> {
+                    chain_cast(
+                        &self.as_rounded_convex().rotated(self_isometry.rotation),
+                        self_isometry.translation,
+                        &other.rotated(other_isometry.rotation),
+                        other_isometry.translation,
+                        offset_dir,
+                        offset_len,
+                    )
+                    .map(|(distance, normal)| (distance, normal, ColliderParts::default()))
+                }
+            }
+        )*
+    };
+}
+
+impl_interaction_with_chain!(
+    Point,
+    Circle,
+    Rectangle,
+    Capsule2d,
+    RoundedRectangle,
+    ConvexShape,
+    Segment2d
+);
+
+/// Implements `ColliderInteraction<$other> for EdgeChain`
+/// using `ColliderInteraction<EdgeChain> for $other` with the opposite offset.
+macro_rules! impl_chain_interaction {
+    ($($other:ty),*) => {
+        $(
+            impl ColliderInteraction<$other> for EdgeChain {
+                fn intersect(&self, self_position: Vec2, other: &$other, other_position: Vec2) -> bool {
+                    other.intersect(other_position, self, self_position)
+                }
+
+                fn cast(
+                    &self,
+                    self_position: Vec2,
+                    other: &$other,
+                    other_position: Vec2,
+                    offset_dir: Dir2,
+                    offset_len: f32,
+                ) -> Option<(f32, Dir2)> {
+                    other
+                        .cast(other_position, self, self_position, -offset_dir, offset_len)
+                        .map(|(len, normal)| (len, -normal))
+                }
+
+                fn intersect_isometry(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &$other,
+                    other_isometry: Isometry2d,
+                ) -> bool {
+                    other.intersect_isometry(other_isometry, self, self_isometry)
+                }
+
+                fn cast_isometry(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &$other,
+                    other_isometry: Isometry2d,
+                    offset_dir: Dir2,
+                    offset_len: f32,
+                ) -> Option<(f32, Dir2, ColliderParts)> {
+                    other
+                        .cast_isometry(other_isometry, self, self_isometry, -offset_dir, offset_len)
+                        .map(|(len, normal, _)| (len, -normal, ColliderParts::default()))
+                }
+            }
+        )*
+    };
+}
+
+impl_chain_interaction!(
+    Point,
+    Circle,
+    Rectangle,
+    Capsule2d,
+    RoundedRectangle,
+    ConvexShape,
+    Segment2d
+);
+
+impl ColliderInteraction<EdgeChain> for EdgeChain {
+    fn intersect(&self, self_position: Vec2, other: &EdgeChain, other_position: Vec2) -> bool {
+        chains_intersect(self, self_position, other, other_position)
+    }
+
+    fn cast(
+        &self,
+        self_position: Vec2,
+        other: &EdgeChain,
+        other_position: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2)> {
+        chains_cast(
+            self,
+            self_position,
+            other,
+            other_position,
+            offset_dir,
+            offset_len,
+        )
+    }
+
+    fn intersect_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &EdgeChain,
+        other_isometry: Isometry2d,
+    ) -> bool {
+        chains_intersect(
+            &self.rotated(self_isometry.rotation),
+            self_isometry.translation,
+            &other.rotated(other_isometry.rotation),
+            other_isometry.translation,
+        )
+    }
+
+    fn cast_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &EdgeChain,
+        other_isometry: Isometry2d,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        chains_cast(
+            &self.rotated(self_isometry.rotation),
+            self_isometry.translation,
+            &other.rotated(other_isometry.rotation),
+            other_isometry.translation,
+            offset_dir,
+            offset_len,
+        )
+        .map(|(distance, normal)| (distance, normal, ColliderParts::default()))
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    bounded::{Bounded, ConvexShape, EdgeChain, Point, RoundedRectangle},
+    collider::{
+        ColliderDistance, ColliderInteraction, ColliderParts, ColliderPenetration, Distance,
+        Penetration,
+    },
+};
+use bevy::{
+    math::{
+        bounding::{Aabb2d, BoundingCircle},
+        primitives::{Capsule2d, Circle, Rectangle, Segment2d},
+        Dir2, Isometry2d, Vec2,
+    },
+    reflect::Reflect,
+};
+
+/// Any of the built-in shapes.
+/// Allows colliders of the different shapes to be used in the same [`ColliderGroup`](crate::ColliderGroup).
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnyShape {
+    Point(Point),
+    Circle(Circle),
+    Rectangle(Rectangle),
+    Capsule(Capsule2d),
+    RoundedRectangle(RoundedRectangle),
+    Convex(ConvexShape),
+    Segment(Segment2d),
+    EdgeChain(EdgeChain),
+}
+
+/// Evaluates `$body` with `$inner` bound to the shape stored in `$shape`.
+macro_rules! dispatch {
+    ($shape:expr, |$inner:ident| $body:expr) => {
+        match $shape {
+            AnyShape::Point($inner) => $body,
+            AnyShape::Circle($inner) => $body,
+            AnyShape::Rectangle($inner) => $body,
+            AnyShape::Capsule($inner) => $body,
+            AnyShape::RoundedRectangle($inner) => $body,
+            AnyShape::Convex($inner) => $body,
+            AnyShape::Segment($inner) => $body,
+            AnyShape::EdgeChain($inner) => $body,
+        }
+    };
+}
+
+impl ColliderInteraction<AnyShape> for AnyShape {
+    fn intersect(&self, self_position: Vec2, other: &AnyShape, other_position: Vec2) -> bool {
+        dispatch!(self, |shape| dispatch!(other, |other| shape.intersect(
+            self_position,
+            other,
+            other_position
+        )))
+    }
+
+    fn cast(
+        &self,
+        self_position: Vec2,
+        other: &AnyShape,
+        other_position: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2)> {
+        dispatch!(self, |shape| dispatch!(other, |other| shape.cast(
+            self_position,
+            other,
+            other_position,
+            offset_dir,
+            offset_len
+        )))
+    }
+
+    fn cast_parts(
+        &self,
+        self_position: Vec2,
+        other: &AnyShape,
+        other_position: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        dispatch!(self, |shape| dispatch!(other, |other| shape.cast_parts(
+            self_position,
+            other,
+            other_position,
+            offset_dir,
+            offset_len
+        )))
+    }
+
+    fn intersect_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &AnyShape,
+        other_isometry: Isometry2d,
+    ) -> bool {
+        dispatch!(self, |shape| dispatch!(other, |other| shape
+            .intersect_isometry(self_isometry, other, other_isometry)))
+    }
+
+    fn cast_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &AnyShape,
+        other_isometry: Isometry2d,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        dispatch!(self, |shape| dispatch!(other, |other| shape.cast_isometry(
+            self_isometry,
+            other,
+            other_isometry,
+            offset_dir,
+            offset_len
+        )))
+    }
+}
+
+impl ColliderPenetration<AnyShape> for AnyShape {
+    fn penetration(
+        &self,
+        self_isometry: Isometry2d,
+        other: &AnyShape,
+        other_isometry: Isometry2d,
+    ) -> Option<Penetration> {
+        dispatch!(self, |shape| dispatch!(other, |other| shape.penetration(
+            self_isometry,
+            other,
+            other_isometry
+        )))
+    }
+}
+
+impl ColliderDistance<AnyShape> for AnyShape {
+    fn distance(
+        &self,
+        self_isometry: Isometry2d,
+        other: &AnyShape,
+        other_isometry: Isometry2d,
+    ) -> Option<Distance> {
+        dispatch!(self, |shape| dispatch!(other, |other| shape.distance(
+            self_isometry,
+            other,
+            other_isometry
+        )))
+    }
+}
+
+impl Bounded<Aabb2d> for AnyShape {
+    fn bounding(&self) -> Aabb2d {
+        dispatch!(self, |shape| shape.bounding())
+    }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> Aabb2d {
+        dispatch!(self, |shape| shape.bounding_with_isometry(isometry))
+    }
+}
+
+impl Bounded<BoundingCircle> for AnyShape {
+    fn bounding(&self) -> BoundingCircle {
+        dispatch!(self, |shape| shape.bounding())
+    }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> BoundingCircle {
+        dispatch!(self, |shape| shape.bounding_with_isometry(isometry))
+    }
+}
+
+macro_rules! impl_from_shape {
+    ($($variant:ident($shape:ty)),*) => {
+        $(
+            impl From<$shape> for AnyShape {
+                fn from(shape: $shape) -> Self {
+                    AnyShape::$variant(shape)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_shape!(
+    Point(Point),
+    Circle(Circle),
+    Rectangle(Rectangle),
+    Capsule(Capsule2d),
+    RoundedRectangle(RoundedRectangle),
+    Convex(ConvexShape),
+    Segment(Segment2d),
+    EdgeChain(EdgeChain)
+);
@@ -0,0 +1,191 @@
+//! Collisions with [`EdgeChain`].
+//! Every segment of the chain is checked separately and collisions with the ends of the segments
+//! that are hidden behind the neighbouring segments are discarded.
+
+use super::{
+    convex::{rounded_distance, rounded_penetration, RoundedConvex},
+    Distance, Penetration,
+};
+use crate::bounded::EdgeChain;
+use bevy::math::{Dir2, Vec2};
+use smallvec::smallvec;
+
+const EPSILON: f32 = 1e-4;
+
+fn segment(chain: &EdgeChain, i: usize) -> RoundedConvex {
+    let (start, end) = chain.segment(i);
+    RoundedConvex {
+        vertices: smallvec![start, end],
+        radius: 0.,
+    }
+}
+
+pub(crate) fn chain_intersect(
+    shape: &RoundedConvex,
+    shape_position: Vec2,
+    chain: &EdgeChain,
+    chain_position: Vec2,
+) -> bool {
+    let point = shape_position - chain_position;
+    (0..chain.segments_count()).any(|i| {
+        shape
+            .minkowski_difference(&segment(chain, i))
+            .contains(point)
+    })
+}
+
+pub(crate) fn chain_cast(
+    shape: &RoundedConvex,
+    shape_position: Vec2,
+    chain: &EdgeChain,
+    chain_position: Vec2,
+    offset_dir: Dir2,
+    offset_len: f32,
+) -> Option<(f32, Dir2)> {
+    let point = shape_position - chain_position;
+    (0..chain.segments_count())
+        .filter_map(|i| {
+            let (distance, normal) = shape
+                .minkowski_difference(&segment(chain, i))
+                .cast_point(point, offset_dir, offset_len)?;
+            normal_allowed(chain, i, normal).then_some((distance, normal))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+}
+
+/// Every segment of the `chain` is treated as a separate shape moving against the `other` chain.
+pub(crate) fn chains_intersect(
+    chain: &EdgeChain,
+    chain_position: Vec2,
+    other: &EdgeChain,
+    other_position: Vec2,
+) -> bool {
+    (0..chain.segments_count())
+        .any(|i| chain_intersect(&segment(chain, i), chain_position, other, other_position))
+}
+
+/// Every segment of the `chain` is treated as a separate shape moving against the `other` chain.
+pub(crate) fn chains_cast(
+    chain: &EdgeChain,
+    chain_position: Vec2,
+    other: &EdgeChain,
+    other_position: Vec2,
+    offset_dir: Dir2,
+    offset_len: f32,
+) -> Option<(f32, Dir2)> {
+    (0..chain.segments_count())
+        .filter_map(|i| {
+            chain_cast(
+                &segment(chain, i),
+                chain_position,
+                other,
+                other_position,
+                offset_dir,
+                offset_len,
+            )
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+}
+
+/// Deepest penetration of the shape into the segments of the chain.
+pub(crate) fn chain_penetration(
+    shape: &RoundedConvex,
+    shape_position: Vec2,
+    chain: &EdgeChain,
+    chain_position: Vec2,
+) -> Option<Penetration> {
+    (0..chain.segments_count())
+        .filter_map(|i| {
+            let penetration =
+                rounded_penetration(shape, shape_position, &segment(chain, i), chain_position)?;
+            normal_allowed(chain, i, penetration.normal).then_some(penetration)
+        })
+        .max_by(|a, b| a.depth.total_cmp(&b.depth))
+}
+
+/// Every segment of the `chain` is treated as a separate shape penetrating the `other` chain.
+pub(crate) fn chains_penetration(
+    chain: &EdgeChain,
+    chain_position: Vec2,
+    other: &EdgeChain,
+    other_position: Vec2,
+) -> Option<Penetration> {
+    (0..chain.segments_count())
+        .filter_map(|i| {
+            chain_penetration(&segment(chain, i), chain_position, other, other_position)
+        })
+        .max_by(|a, b| a.depth.total_cmp(&b.depth))
+}
+
+/// Distance from the shape to the closest segment of the chain.
+/// One-sidedness of the chain is not taken into account.
+pub(crate) fn chain_distance(
+    shape: &RoundedConvex,
+    shape_position: Vec2,
+    chain: &EdgeChain,
+    chain_position: Vec2,
+) -> Option<Distance> {
+    (0..chain.segments_count())
+        .map(|i| rounded_distance(shape, shape_position, &segment(chain, i), chain_position))
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// Distance from the closest segment of the `chain` to the `other` chain.
+pub(crate) fn chains_distance(
+    chain: &EdgeChain,
+    chain_position: Vec2,
+    other: &EdgeChain,
+    other_position: Vec2,
+) -> Option<Distance> {
+    (0..chain.segments_count())
+        .filter_map(|i| chain_distance(&segment(chain, i), chain_position, other, other_position))
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// Returns false if the collision with the segment `i` happened
+/// from the back of one-sided segment or with the hidden part of the segment's end.
+fn normal_allowed(chain: &EdgeChain, i: usize, normal: Dir2) -> bool {
+    let (start, end) = chain.segment(i);
+    let Some(direction) = (end - start).try_normalize() else {
+        // Degenerate segments are covered by their neighbours
+        return false;
+    };
+
+    let facing = normal.dot(direction.perp());
+    if chain.is_one_sided() && facing < -EPSILON {
+        return false;
+    }
+    if facing.abs() > 1. - EPSILON {
+        return true;
+    }
+
+    let side = if facing >= 0. { 1. } else { -1. };
+    if normal.dot(direction) > 0. {
+        chain
+            .next_vertex(i)
+            .is_none_or(|next| joint_allows(direction, next - end, normal, side))
+    } else {
+        chain
+            .previous_vertex(i)
+            .is_none_or(|previous| joint_allows(start - previous, direction, normal, side))
+    }
+}
+
+/// Returns true if the joint between segments going in `before` and `after` directions
+/// sticks out on the `side` of the chain, and `normal` lies between normals of the segments.
+fn joint_allows(before: Vec2, after: Vec2, normal: Dir2, side: f32) -> bool {
+    let (Some(before), Some(after)) = (before.try_normalize(), after.try_normalize()) else {
+        return false;
+    };
+
+    let turn = before.perp_dot(after);
+    // Joint is flat or concave, so it's end is fully covered by the neighbour
+    if turn * side > -EPSILON {
+        return false;
+    }
+
+    let before_normal = before.perp() * side;
+    let after_normal = after.perp() * side;
+    before_normal.perp_dot(*normal) * turn.signum() >= -EPSILON
+        && normal.perp_dot(after_normal) * turn.signum() >= -EPSILON
+}
@@ -0,0 +1,337 @@
+use crate::{
+    bounded::{Bounded, ConvexShape, EdgeChain, Point, RoundedRectangle},
+    collider::{
+        AnyShape, Collider, ColliderDistance, ColliderInteraction, ColliderParts,
+        ColliderPenetration, Distance, Penetration,
+    },
+};
+use bevy::{
+    math::{
+        bounding::{Aabb2d, BoundingCircle, BoundingVolume},
+        primitives::{Capsule2d, Circle, Rectangle, Segment2d},
+        Dir2, Isometry2d, Vec2,
+    },
+    reflect::Reflect,
+};
+
+/// Shape made of several parts, each of which is offset from the position of the collider.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Compound<S> {
+    /// Offsets and shapes of the parts.
+    /// Index of the part in this list is reported in [`ColliderParts`].
+    pub parts: Vec<(Vec2, S)>,
+}
+
+impl<S> Compound<S> {
+    pub fn new(parts: impl IntoIterator<Item = (Vec2, S)>) -> Self {
+        Self {
+            parts: parts.into_iter().collect(),
+        }
+    }
+}
+
+/// Isometry of the part with given `offset` in the compound with given `isometry`.
+fn part_isometry(isometry: Isometry2d, offset: Vec2) -> Isometry2d {
+    isometry * Isometry2d::from_translation(offset)
+}
+
+impl<S: Bounded<Aabb2d>> Bounded<Aabb2d> for Compound<S> {
+    fn bounding(&self) -> Aabb2d {
+        self.parts
+            .iter()
+            .map(|(offset, shape)| Collider::new(shape, *offset).bounding())
+            .reduce(|a, b| a.merge(&b))
+            .unwrap_or(Aabb2d {
+                min: Vec2::ZERO,
+                max: Vec2::ZERO,
+            })
+    }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> Aabb2d {
+        self.parts
+            .iter()
+            .map(|(offset, shape)| shape.bounding_with_isometry(part_isometry(isometry, *offset)))
+            .reduce(|a, b| a.merge(&b))
+            .unwrap_or(Aabb2d {
+                min: isometry.translation,
+                max: isometry.translation,
+            })
+    }
+}
+
+impl<S: Bounded<BoundingCircle>> Bounded<BoundingCircle> for Compound<S> {
+    fn bounding(&self) -> BoundingCircle {
+        self.bounding_with_isometry(Isometry2d::IDENTITY)
+    }
+
+    fn bounding_with_isometry(&self, isometry: Isometry2d) -> BoundingCircle {
+        self.parts
+            .iter()
+            .map(|(offset, shape)| shape.bounding_with_isometry(part_isometry(isometry, *offset)))
+            .reduce(|a, b| a.merge(&b))
+            .unwrap_or(BoundingCircle::new(isometry.translation, 0.))
+    }
+}
+
+impl<S: ColliderInteraction<T>, T> ColliderInteraction<T> for Compound<S> {
+    fn intersect(&self, self_position: Vec2, other: &T, other_position: Vec2) -> bool {
+        self.parts
+            .iter()
+            .any(|(offset, shape)| shape.intersect(self_position + *offset, other, other_position))
+    }
+
+    fn cast(
+        &self,
+        self_position: Vec2,
+        other: &T,
+        other_position: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2)> {
+        self.cast_parts(self_position, other, other_position, offset_dir, offset_len)
+            .map(|(distance, normal, _)| (distance, normal))
+    }
+
+    fn cast_parts(
+        &self,
+        self_position: Vec2,
+        other: &T,
+        other_position: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        self.parts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (offset, shape))| {
+                let (distance, normal, parts) = shape.cast_parts(
+                    self_position + *offset,
+                    other,
+                    other_position,
+                    offset_dir,
+                    offset_len,
+                )?;
+                Some((distance, normal, ColliderParts { hitbox: i, ..parts }))
+            })
+            .min_by(|(a, ..), (b, ..)| a.total_cmp(b))
+    }
+
+    fn intersect_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &T,
+        other_isometry: Isometry2d,
+    ) -> bool {
+        self.parts.iter().any(|(offset, shape)| {
+            shape.intersect_isometry(part_isometry(self_isometry, *offset), other, other_isometry)
+        })
+    }
+
+    fn cast_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &T,
+        other_isometry: Isometry2d,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        self.parts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (offset, shape))| {
+                let (distance, normal, parts) = shape.cast_isometry(
+                    part_isometry(self_isometry, *offset),
+                    other,
+                    other_isometry,
+                    offset_dir,
+                    offset_len,
+                )?;
+                Some((distance, normal, ColliderParts { hitbox: i, ..parts }))
+            })
+            .min_by(|(a, ..), (b, ..)| a.total_cmp(b))
+    }
+}
+
+impl<S: ColliderPenetration<T>, T> ColliderPenetration<T> for Compound<S> {
+    fn penetration(
+        &self,
+        self_isometry: Isometry2d,
+        other: &T,
+        other_isometry: Isometry2d,
+    ) -> Option<Penetration> {
+        self.parts
+            .iter()
+            .filter_map(|(offset, shape)| {
+                shape.penetration(part_isometry(self_isometry, *offset), other, other_isometry)
+            })
+            .max_by(|a, b| a.depth.total_cmp(&b.depth))
+    }
+}
+
+impl<S: ColliderDistance<T>, T> ColliderDistance<T> for Compound<S> {
+    fn distance(
+        &self,
+        self_isometry: Isometry2d,
+        other: &T,
+        other_isometry: Isometry2d,
+    ) -> Option<Distance> {
+        self.parts
+            .iter()
+            .filter_map(|(offset, shape)| {
+                shape.distance(part_isometry(self_isometry, *offset), other, other_isometry)
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+}
+
+/// Implements `ColliderInteraction<Compound<S>> for $self` for every `S` that `$self` interacts with.
+macro_rules! impl_interaction_with_compound {
+    ($($self:ty),*) => {
+        $(
+            impl<S> ColliderInteraction<Compound<S>> for $self
+            where
+                $self: ColliderInteraction<S>,
+            {
+                fn intersect(&self, self_position: Vec2, other: &Compound<S>, other_position: Vec2) -> bool {
+                    other
+                        .parts
+                        .iter()
+                        .any(|(offset, shape)| self.intersect(self_position, shape, other_position + *offset))
+                }
+
+                fn cast(
+                    &self,
+                    self_position: Vec2,
+                    other: &Compound<S>,
+                    other_position: Vec2,
+                    offset_dir: Dir2,
+                    offset_len: f32,
+                ) -> Option<(f32, Dir2)> {
+                    ColliderInteraction::<Compound<S>>::cast_parts(
+                        self,
+                        self_position,
+                        other,
+                        other_position,
+                        offset_dir,
+                        offset_len,
+                    )
+                    .map(|(distance, normal, _)| (distance, normal))
+                }
+
+                fn cast_parts(
+                    &self,
+                    self_position: Vec2,
+                    other: &Compound<S>,
+                    other_position: Vec2,
+                    offset_dir: Dir2,
+                    offset_len: f32,
+                ) -> Option<(f32, Dir2, ColliderParts)> {
+                    other
+                        .parts
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, (offset, shape))| {
+                            let (distance, normal, parts) = self.cast_parts(
+                                self_position,
+                                shape,
+                                other_position + *offset,
+                                offset_dir,
+                                offset_len,
+                            )?;
+                            Some((distance, normal, ColliderParts { hurtbox: i, ..parts }))
+                        })
+                        .min_by(|(a, ..), (b, ..)| a.total_cmp(b))
+                }
+
+                fn intersect_isometry(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &Compound<S>,
+                    other_isometry: Isometry2d,
+                ) -> bool {
+                    other.parts.iter().any(|(offset, shape)| {
+                        self.intersect_isometry(self_isometry, shape, part_isometry(other_isometry, *offset))
+                    })
+                }
+
+                fn cast_isometry(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &Compound<S>,
+                    other_isometry: Isometry2d,
+                    offset_dir: Dir2,
+                    offset_len: f32,
+                ) -> Option<(f32, Dir2, ColliderParts)> {
+                    other
+                        .parts
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, (offset, shape))| {
+                            let (distance, normal, parts) = self.cast_isometry(
+                                self_isometry,
+                                shape,
+                                part_isometry(other_isometry, *offset),
+                                offset_dir,
+                                offset_len,
+                            )?;
+                            Some((distance, normal, ColliderParts { hurtbox: i, ..parts }))
+                        })
+                        .min_by(|(a, ..), (b, ..)| a.total_cmp(b))
+                }
+            }
+
+            impl<S> ColliderPenetration<Compound<S>> for $self
+            where
+                $self: ColliderPenetration<S>,
+            {
+                fn penetration(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &Compound<S>,
+                    other_isometry: Isometry2d,
+                ) -> Option<Penetration> {
+                    other
+                        .parts
+                        .iter()
+                        .filter_map(|(offset, shape)| {
+                            self.penetration(self_isometry, shape, part_isometry(other_isometry, *offset))
+                        })
+                        .max_by(|a, b| a.depth.total_cmp(&b.depth))
+                }
+            }
+
+            impl<S> ColliderDistance<Compound<S>> for $self
+            where
+                $self: ColliderDistance<S>,
+            {
+                fn distance(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &Compound<S>,
+                    other_isometry: Isometry2d,
+                ) -> Option<Distance> {
+                    other
+                        .parts
+                        .iter()
+                        .filter_map(|(offset, shape)| {
+                            self.distance(self_isometry, shape, part_isometry(other_isometry, *offset))
+                        })
+                        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+                }
+            }
+        )*
+    };
+}
+
+impl_interaction_with_compound!(
+    Point,
+    Circle,
+    Rectangle,
+    Capsule2d,
+    RoundedRectangle,
+    ConvexShape,
+    Segment2d,
+    EdgeChain,
+    AnyShape
+);
@@ -0,0 +1,533 @@
+//! Collisions between convex shapes inflated by some radius.
+//! Every built-in shape can be represented as such,
+//! so this is used as a fallback for shape pairs that don't have specialized implementation.
+
+use crate::{
+    bounded::{ConvexShape, Point, RoundedRectangle},
+    collider::{ColliderInteraction, ColliderParts, Distance, Penetration},
+};
+use bevy::math::{
+    primitives::{Capsule2d, Circle, Rectangle, Segment2d},
+    Dir2, Isometry2d, Rot2, Vec2,
+};
+use smallvec::{smallvec, SmallVec};
+
+/// Vertices of the [`RoundedConvex`], built-in shapes and their Minkowski differences
+/// fit without allocating.
+pub(crate) type Vertices = SmallVec<[Vec2; 16]>;
+
+/// Convex polygon (or segment, or point), inflated by `radius` in every direction.
+/// Vertices are stored in counterclockwise order.
+#[derive(Debug, Clone)]
+pub(crate) struct RoundedConvex {
+    pub vertices: Vertices,
+    pub radius: f32,
+}
+
+/// Shapes that can be represented as [`RoundedConvex`].
+pub(crate) trait AsRoundedConvex {
+    fn as_rounded_convex(&self) -> RoundedConvex;
+}
+
+impl AsRoundedConvex for Point {
+    fn as_rounded_convex(&self) -> RoundedConvex {
+        RoundedConvex {
+            vertices: smallvec![Vec2::ZERO],
+            radius: 0.,
+        }
+    }
+}
+
+impl AsRoundedConvex for Circle {
+    fn as_rounded_convex(&self) -> RoundedConvex {
+        RoundedConvex {
+            vertices: smallvec![Vec2::ZERO],
+            radius: self.radius,
+        }
+    }
+}
+
+impl AsRoundedConvex for Rectangle {
+    fn as_rounded_convex(&self) -> RoundedConvex {
+        RoundedConvex {
+            vertices: rectangle_vertices(self.half_size),
+            radius: 0.,
+        }
+    }
+}
+
+impl AsRoundedConvex for RoundedRectangle {
+    fn as_rounded_convex(&self) -> RoundedConvex {
+        RoundedConvex {
+            vertices: rectangle_vertices(self.inner_half_size()),
+            radius: self.radius,
+        }
+    }
+}
+
+impl AsRoundedConvex for Capsule2d {
+    fn as_rounded_convex(&self) -> RoundedConvex {
+        RoundedConvex {
+            vertices: smallvec![
+                Vec2::new(0., -self.half_length),
+                Vec2::new(0., self.half_length),
+            ],
+            radius: self.radius,
+        }
+    }
+}
+
+impl AsRoundedConvex for Segment2d {
+    fn as_rounded_convex(&self) -> RoundedConvex {
+        let end = self.direction * self.half_length;
+        RoundedConvex {
+            vertices: smallvec![-end, end],
+            radius: 0.,
+        }
+    }
+}
+
+impl AsRoundedConvex for ConvexShape {
+    fn as_rounded_convex(&self) -> RoundedConvex {
+        RoundedConvex {
+            vertices: Vertices::from_slice(self.vertices()),
+            radius: 0.,
+        }
+    }
+}
+
+fn rectangle_vertices(half_size: Vec2) -> Vertices {
+    smallvec![
+        Vec2::new(-half_size.x, -half_size.y),
+        Vec2::new(half_size.x, -half_size.y),
+        Vec2::new(half_size.x, half_size.y),
+        Vec2::new(-half_size.x, half_size.y),
+    ]
+}
+
+/// Convex hull of the points in counterclockwise order, without collinear points.
+/// Returns one point if all points are equal and two points if all of them lie on one line.
+fn convex_hull(mut points: SmallVec<[Vec2; 32]>) -> Vertices {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+
+    if points.len() < 3 {
+        return Vertices::from_slice(&points);
+    }
+
+    fn push_half_hull<'a>(hull: &mut Vertices, points: impl Iterator<Item = &'a Vec2>) {
+        let start = hull.len();
+        for &point in points {
+            while let [.., previous, last] = hull[start..] {
+                if (last - previous).perp_dot(point - last) > 0. {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // Last point is the first point of the other half
+        hull.pop();
+    }
+
+    let mut hull = Vertices::new();
+    push_half_hull(&mut hull, points.iter());
+    push_half_hull(&mut hull, points.iter().rev());
+    hull
+}
+
+impl RoundedConvex {
+    /// Same shape rotated around the origin.
+    pub fn rotated(mut self, rotation: Rot2) -> Self {
+        for vertex in &mut self.vertices {
+            *vertex = rotation * *vertex;
+        }
+        self
+    }
+
+    /// Minkowski difference `other - self`.
+    /// Point lying in the result is a position of `self`, relative to `other`, at which they intersect.
+    pub fn minkowski_difference(&self, other: &RoundedConvex) -> RoundedConvex {
+        let points = other
+            .vertices
+            .iter()
+            .flat_map(|&b| self.vertices.iter().map(move |&a| b - a))
+            .collect();
+
+        RoundedConvex {
+            vertices: convex_hull(points),
+            radius: self.radius + other.radius,
+        }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2, Dir2)> + '_ {
+        let len = self.vertices.len();
+        let edges_count = if len < 2 { 0 } else { len };
+        (0..edges_count).filter_map(move |i| {
+            let start = self.vertices[i];
+            let end = self.vertices[(i + 1) % len];
+            let edge = end - start;
+            let normal = Dir2::new(Vec2::new(edge.y, -edge.x)).ok()?;
+            Some((start, end, normal))
+        })
+    }
+
+    /// Returns true if the point lies inside of the polygon without radius.
+    fn core_contains(&self, point: Vec2) -> bool {
+        self.vertices.len() >= 3
+            && self
+                .edges()
+                .all(|(start, _, normal)| normal.dot(point - start) <= 0.)
+    }
+
+    /// Closest point on the boundary of the polygon without radius.
+    fn closest_core_boundary_point(&self, point: Vec2) -> Vec2 {
+        if self.vertices.len() == 1 {
+            return self.vertices[0];
+        }
+
+        self.edges()
+            .map(|(start, end, _)| {
+                let edge = end - start;
+                let t = ((point - start).dot(edge) / edge.length_squared()).clamp(0., 1.);
+                start + edge * t
+            })
+            .min_by(|a, b| {
+                a.distance_squared(point)
+                    .total_cmp(&b.distance_squared(point))
+            })
+            .unwrap_or(self.vertices[0])
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.core_contains(point)
+            || self
+                .closest_core_boundary_point(point)
+                .distance_squared(point)
+                <= self.radius.powi(2)
+    }
+
+    /// Normal of the closest side to the point that lies inside the shape.
+    fn inside_normal(&self, point: Vec2, offset_dir: Dir2) -> Dir2 {
+        if self.core_contains(point) {
+            return self
+                .edges()
+                .max_by(|(a_start, _, a_normal), (b_start, _, b_normal)| {
+                    a_normal
+                        .dot(point - *a_start)
+                        .total_cmp(&b_normal.dot(point - *b_start))
+                })
+                .map(|(_, _, normal)| normal)
+                .unwrap_or(-offset_dir);
+        }
+
+        Dir2::new(point - self.closest_core_boundary_point(point)).unwrap_or(-offset_dir)
+    }
+
+    /// Casts point from `point` along the offset.
+    /// Point that already touches the shape collides only if it moves
+    /// through the closest side, so it is able to slide along or move away from it.
+    pub fn cast_point(
+        &self,
+        point: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2)> {
+        if self.contains(point) {
+            let normal = self.inside_normal(point, offset_dir);
+            return (offset_dir.dot(*normal) < 0.).then_some((0., normal));
+        }
+
+        // Boundary of the shape consists of the sides moved along their normals by radius
+        // and arcs around vertices. Arcs are checked first, so they win ties at their ends.
+        let corner = Circle::new(self.radius);
+        let corner_casts = self
+            .vertices
+            .iter()
+            .filter(|_| self.radius > 0.)
+            .filter_map(|&vertex| Point.cast(point, &corner, vertex, offset_dir, offset_len));
+
+        let side_casts = self.edges().filter_map(|(start, end, normal)| {
+            let approach = normal.dot(*offset_dir);
+            if approach >= 0. {
+                return None;
+            }
+
+            let start = start + normal * self.radius;
+            let end = end + normal * self.radius;
+            let distance = normal.dot(start - point) / approach;
+            if !(0. ..=offset_len).contains(&distance) {
+                return None;
+            }
+
+            let edge = end - start;
+            let along = (point + offset_dir * distance - start).dot(edge);
+            (0. ..=edge.length_squared())
+                .contains(&along)
+                .then_some((distance, normal))
+        });
+
+        corner_casts
+            .chain(side_casts)
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+    }
+}
+
+pub(crate) fn convex_intersect_isometry(
+    a: &impl AsRoundedConvex,
+    a_isometry: Isometry2d,
+    b: &impl AsRoundedConvex,
+    b_isometry: Isometry2d,
+) -> bool {
+    a.as_rounded_convex()
+        .rotated(a_isometry.rotation)
+        .minkowski_difference(&b.as_rounded_convex().rotated(b_isometry.rotation))
+        .contains(a_isometry.translation - b_isometry.translation)
+}
+
+pub(crate) fn convex_cast_isometry(
+    a: &impl AsRoundedConvex,
+    a_isometry: Isometry2d,
+    b: &impl AsRoundedConvex,
+    b_isometry: Isometry2d,
+    offset_dir: Dir2,
+    offset_len: f32,
+) -> Option<(f32, Dir2, ColliderParts)> {
+    a.as_rounded_convex()
+        .rotated(a_isometry.rotation)
+        .minkowski_difference(&b.as_rounded_convex().rotated(b_isometry.rotation))
+        .cast_point(
+            a_isometry.translation - b_isometry.translation,
+            offset_dir,
+            offset_len,
+        )
+        .map(|(distance, normal)| (distance, normal, ColliderParts::default()))
+}
+
+impl RoundedConvex {
+    /// Depth at which the point lies inside the shape and the normal
+    /// of the closest side, pointing outside of the shape.
+    pub fn point_penetration(&self, point: Vec2) -> Option<(f32, Dir2)> {
+        if self.core_contains(point) {
+            let (distance, normal) = self
+                .edges()
+                .map(|(start, _, normal)| (normal.dot(point - start), normal))
+                .max_by(|(a, _), (b, _)| a.total_cmp(b))?;
+            return Some((self.radius - distance, normal));
+        }
+
+        let offset = point - self.closest_core_boundary_point(point);
+        let distance = offset.length();
+        if distance > self.radius {
+            return None;
+        }
+
+        // Point lies right on the segment or the point, so any direction is as good as the other
+        let normal = Dir2::new(offset)
+            .ok()
+            .or_else(|| self.edges().next().map(|(_, _, normal)| normal))
+            .unwrap_or(Dir2::Y);
+        Some((self.radius - distance, normal))
+    }
+
+    /// One or two vertices that are the farthest in the `direction`.
+    fn support_feature(&self, direction: Vec2) -> (Vec2, Option<Vec2>) {
+        let max = self
+            .vertices
+            .iter()
+            .map(|vertex| vertex.dot(direction))
+            .fold(f32::NEG_INFINITY, f32::max);
+        let tolerance = 1e-4 * (1. + max.abs());
+
+        let mut feature = self
+            .vertices
+            .iter()
+            .copied()
+            .filter(|vertex| vertex.dot(direction) >= max - tolerance);
+        let first = feature.next().unwrap_or(Vec2::ZERO);
+        (first, feature.next())
+    }
+}
+
+/// Penetration of `a` at `a_position` into `b` at `b_position`.
+pub(crate) fn rounded_penetration(
+    a: &RoundedConvex,
+    a_position: Vec2,
+    b: &RoundedConvex,
+    b_position: Vec2,
+) -> Option<Penetration> {
+    let (depth, normal) = a
+        .minkowski_difference(b)
+        .point_penetration(a_position - b_position)?;
+
+    // Contact points lie halfway between the surfaces of the shapes
+    let to_middle = normal * (depth / 2.);
+    let (a_first, a_second) = a.support_feature(-*normal);
+    let (b_first, b_second) = b.support_feature(*normal);
+    let a_surface = |vertex: Vec2| vertex + a_position - normal * a.radius + to_middle;
+    let b_surface = |vertex: Vec2| vertex + b_position + normal * b.radius - to_middle;
+
+    let contacts = match (a_second, b_second) {
+        (None, _) => vec![a_surface(a_first)],
+        (Some(_), None) => vec![b_surface(b_first)],
+        (Some(a_second), Some(b_second)) => {
+            // Both features are sides, clip side of `a` by the extent of the side of `b`
+            let tangent = normal.perp();
+            let (a_first, a_second) = (a_surface(a_first), a_surface(a_second));
+            let (b_min, b_max) = {
+                let first = tangent.dot(b_surface(b_first));
+                let second = tangent.dot(b_surface(b_second));
+                (first.min(second), first.max(second))
+            };
+
+            let a_first_along = tangent.dot(a_first);
+            let a_length = tangent.dot(a_second) - a_first_along;
+            if a_length.abs() <= f32::EPSILON {
+                vec![a_first]
+            } else {
+                let clip = |along: f32| {
+                    let t = ((along - a_first_along) / a_length).clamp(0., 1.);
+                    a_first.lerp(a_second, t)
+                };
+                vec![clip(b_min), clip(b_max)]
+            }
+        }
+    };
+
+    Some(Penetration::new(depth, normal, contacts))
+}
+
+pub(crate) fn convex_penetration_isometry(
+    a: &impl AsRoundedConvex,
+    a_isometry: Isometry2d,
+    b: &impl AsRoundedConvex,
+    b_isometry: Isometry2d,
+) -> Option<Penetration> {
+    rounded_penetration(
+        &a.as_rounded_convex().rotated(a_isometry.rotation),
+        a_isometry.translation,
+        &b.as_rounded_convex().rotated(b_isometry.rotation),
+        b_isometry.translation,
+    )
+}
+
+/// Closest points between the polygons without radius, that don't overlap.
+fn closest_core_points(
+    a: &RoundedConvex,
+    a_position: Vec2,
+    b: &RoundedConvex,
+    b_position: Vec2,
+) -> (Vec2, Vec2) {
+    // Vertex of one of the polygons is always among the closest points
+    let offset = a_position - b_position;
+    let from_a = a.vertices.iter().map(|&vertex| {
+        let vertex = vertex + offset;
+        (vertex, b.closest_core_boundary_point(vertex))
+    });
+    let from_b = b.vertices.iter().map(|&vertex| {
+        (
+            a.closest_core_boundary_point(vertex - offset) + offset,
+            vertex,
+        )
+    });
+
+    let (a_point, b_point) = from_a
+        .chain(from_b)
+        .min_by(|(a1, b1), (a2, b2)| {
+            a1.distance_squared(*b1)
+                .total_cmp(&a2.distance_squared(*b2))
+        })
+        .unwrap_or((offset, Vec2::ZERO));
+    (a_point + b_position, b_point + b_position)
+}
+
+/// Signed distance from `a` at `a_position` to `b` at `b_position`.
+pub(crate) fn rounded_distance(
+    a: &RoundedConvex,
+    a_position: Vec2,
+    b: &RoundedConvex,
+    b_position: Vec2,
+) -> Distance {
+    let minkowski = a.minkowski_difference(b);
+    let relative = a_position - b_position;
+
+    if !minkowski.core_contains(relative) {
+        let (a_point, b_point) = closest_core_points(a, a_position, b, b_position);
+        if let Ok((normal, core_distance)) = Dir2::new_and_length(a_point - b_point) {
+            return Distance {
+                distance: core_distance - a.radius - b.radius,
+                normal,
+                point: a_point - normal * a.radius,
+                other_point: b_point + normal * b.radius,
+            };
+        }
+    }
+
+    // Polygons overlap, so the deepest points are the closest ones
+    let (depth, normal) = minkowski
+        .point_penetration(relative)
+        .unwrap_or((minkowski.radius, Dir2::Y));
+    Distance {
+        distance: -depth,
+        normal,
+        point: a.support_feature(-*normal).0 + a_position - normal * a.radius,
+        other_point: b.support_feature(*normal).0 + b_position + normal * b.radius,
+    }
+}
+
+pub(crate) fn convex_distance_isometry(
+    a: &impl AsRoundedConvex,
+    a_isometry: Isometry2d,
+    b: &impl AsRoundedConvex,
+    b_isometry: Isometry2d,
+) -> Distance {
+    rounded_distance(
+        &a.as_rounded_convex().rotated(a_isometry.rotation),
+        a_isometry.translation,
+        &b.as_rounded_convex().rotated(b_isometry.rotation),
+        b_isometry.translation,
+    )
+}
+
+/// Separating axis test for two convex polygons without radius.
+/// True if one of the sides, given by its normal and a vertex, has all of the `other` vertices in front of it.
+fn separated_by(normals: &[Dir2], vertices: &[Vec2], other: &[Vec2], offset: Vec2) -> bool {
+    normals.iter().zip(vertices).any(|(normal, &vertex)| {
+        other
+            .iter()
+            .all(|&other_vertex| normal.dot(other_vertex + offset - vertex) > 0.)
+    })
+}
+
+pub(crate) fn polygons_intersect(
+    a: &ConvexShape,
+    a_position: Vec2,
+    b: &ConvexShape,
+    b_position: Vec2,
+) -> bool {
+    let offset = b_position - a_position;
+    !separated_by(a.normals(), a.vertices(), b.vertices(), offset)
+        && !separated_by(b.normals(), b.vertices(), a.vertices(), -offset)
+}
+
+/// Same as [`polygons_intersect`], with sides of the rectangle taken as is,
+/// so rectangles with zero width or height work too.
+pub(crate) fn rectangle_polygon_intersect(
+    rect: &Rectangle,
+    rect_position: Vec2,
+    polygon: &ConvexShape,
+    polygon_position: Vec2,
+) -> bool {
+    let Rectangle { half_size } = *rect;
+    let vertices = [
+        Vec2::new(-half_size.x, -half_size.y),
+        Vec2::new(half_size.x, -half_size.y),
+        Vec2::new(half_size.x, half_size.y),
+        Vec2::new(-half_size.x, half_size.y),
+    ];
+    let normals = [Dir2::NEG_Y, Dir2::X, Dir2::Y, Dir2::NEG_X];
+
+    let offset = polygon_position - rect_position;
+    !separated_by(&normals, &vertices, polygon.vertices(), offset)
+        && !separated_by(polygon.normals(), polygon.vertices(), &vertices, -offset)
+}
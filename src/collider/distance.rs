@@ -0,0 +1,124 @@
+//! [`ColliderDistance`] implementations for the built-in shapes.
+
+use super::{
+    chain::{chain_distance, chains_distance},
+    convex::{convex_distance_isometry, AsRoundedConvex},
+    ColliderDistance, Distance,
+};
+use crate::bounded::{ConvexShape, EdgeChain, Point, RoundedRectangle};
+use bevy::math::{
+    primitives::{Capsule2d, Circle, Rectangle, Segment2d},
+    Isometry2d,
+};
+
+/// Implements `ColliderDistance` for every pair of `$self` and `$other`
+/// by finding the closest points of the polygons the shapes are made of.
+macro_rules! impl_distance_as_convex {
+    ([$($self:ty),*], $others:tt) => {
+        $(
+            impl_distance_as_convex!(@self $self, $others);
+        )*
+    };
+    (@self $self:ty, [$($other:ty),*]) => {
+        $(
+            impl ColliderDistance<$other> for $self {
+                fn distance(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &$other,
+                    other_isometry: Isometry2d,
+                ) -> Option<Distance> {
+                    Some(convex_distance_isometry(
+                        self,
+                        self_isometry,
+                        other,
+                        other_isometry,
+                    ))
+                }
+            }
+        )*
+    };
+}
+
+impl_distance_as_convex!(
+    [
+        Point,
+        Circle,
+        Rectangle,
+        Capsule2d,
+        RoundedRectangle,
+        ConvexShape,
+        Segment2d
+    ],
+    [
+        Point,
+        Circle,
+        Rectangle,
+        Capsule2d,
+        RoundedRectangle,
+        ConvexShape,
+        Segment2d
+    ]
+);
+
+/// Implements `ColliderDistance` between `$shape` and [`EdgeChain`] in both directions.
+macro_rules! impl_distance_with_chain {
+    ($($shape:ty),*) => {
+        $(
+            impl ColliderDistance<EdgeChain> for $shape {
+                fn distance(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &EdgeChain,
+                    other_isometry: Isometry2d,
+                ) -> Option<Distance> {
+                    chain_distance(
+                        &self.as_rounded_convex().rotated(self_isometry.rotation),
+                        self_isometry.translation,
+                        &other.rotated(other_isometry.rotation),
+                        other_isometry.translation,
+                    )
+                }
+            }
+
+            impl ColliderDistance<$shape> for EdgeChain {
+                fn distance(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &$shape,
+                    other_isometry: Isometry2d,
+                ) -> Option<Distance> {
+                    other
+                        .distance(other_isometry, self, self_isometry)
+                        .map(Distance::reversed)
+                }
+            }
+        )*
+    };
+}
+
+impl_distance_with_chain!(
+    Point,
+    Circle,
+    Rectangle,
+    Capsule2d,
+    RoundedRectangle,
+    ConvexShape,
+    Segment2d
+);
+
+impl ColliderDistance<EdgeChain> for EdgeChain {
+    fn distance(
+        &self,
+        self_isometry: Isometry2d,
+        other: &EdgeChain,
+        other_isometry: Isometry2d,
+    ) -> Option<Distance> {
+        chains_distance(
+            &self.rotated(self_isometry.rotation),
+            self_isometry.translation,
+            &other.rotated(other_isometry.rotation),
+            other_isometry.translation,
+        )
+    }
+}
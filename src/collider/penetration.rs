@@ -0,0 +1,119 @@
+//! [`ColliderPenetration`] implementations for the built-in shapes.
+
+use super::{
+    chain::{chain_penetration, chains_penetration},
+    convex::{convex_penetration_isometry, AsRoundedConvex},
+    ColliderPenetration, Penetration,
+};
+use crate::bounded::{ConvexShape, EdgeChain, Point, RoundedRectangle};
+use bevy::math::{
+    primitives::{Capsule2d, Circle, Rectangle, Segment2d},
+    Isometry2d,
+};
+
+/// Implements `ColliderPenetration` for every pair of `$self` and `$other`
+/// through the Minkowski difference of the shapes.
+macro_rules! impl_penetration_as_convex {
+    ([$($self:ty),*], $others:tt) => {
+        $(
+            impl_penetration_as_convex!(@self $self, $others);
+        )*
+    };
+    (@self $self:ty, [$($other:ty),*]) => {
+        $(
+            impl ColliderPenetration<$other> for $self {
+                fn penetration(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &$other,
+                    other_isometry: Isometry2d,
+                ) -> Option<Penetration> {
+                    convex_penetration_isometry(self, self_isometry, other, other_isometry)
+                }
+            }
+        )*
+    };
+}
+
+impl_penetration_as_convex!(
+    [
+        Point,
+        Circle,
+        Rectangle,
+        Capsule2d,
+        RoundedRectangle,
+        ConvexShape,
+        Segment2d
+    ],
+    [
+        Point,
+        Circle,
+        Rectangle,
+        Capsule2d,
+        RoundedRectangle,
+        ConvexShape,
+        Segment2d
+    ]
+);
+
+/// Implements `ColliderPenetration` between `$shape` and [`EdgeChain`] in both directions.
+macro_rules! impl_penetration_with_chain {
+    ($($shape:ty),*) => {
+        $(
+            impl ColliderPenetration<EdgeChain> for $shape {
+                fn penetration(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &EdgeChain,
+                    other_isometry: Isometry2d,
+                ) -> Option<Penetration> {
+                    chain_penetration(
+                        &self.as_rounded_convex().rotated(self_isometry.rotation),
+                        self_isometry.translation,
+                        &other.rotated(other_isometry.rotation),
+                        other_isometry.translation,
+                    )
+                }
+            }
+
+            impl ColliderPenetration<$shape> for EdgeChain {
+                fn penetration(
+                    &self,
+                    self_isometry: Isometry2d,
+                    other: &$shape,
+                    other_isometry: Isometry2d,
+                ) -> Option<Penetration> {
+                    other
+                        .penetration(other_isometry, self, self_isometry)
+                        .map(Penetration::reversed)
+                }
+            }
+        )*
+    };
+}
+
+impl_penetration_with_chain!(
+    Point,
+    Circle,
+    Rectangle,
+    Capsule2d,
+    RoundedRectangle,
+    ConvexShape,
+    Segment2d
+);
+
+impl ColliderPenetration<EdgeChain> for EdgeChain {
+    fn penetration(
+        &self,
+        self_isometry: Isometry2d,
+        other: &EdgeChain,
+        other_isometry: Isometry2d,
+    ) -> Option<Penetration> {
+        chains_penetration(
+            &self.rotated(self_isometry.rotation),
+            self_isometry.translation,
+            &other.rotated(other_isometry.rotation),
+            other_isometry.translation,
+        )
+    }
+}
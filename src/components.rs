@@ -1,9 +1,28 @@
-use super::ColliderGroup;
-use bevy::prelude::{Component, Deref, DerefMut};
+use super::{spatial_index::components::SpatialIndexRegistry, ColliderGroup};
+use bevy::{
+    math::{Isometry2d, Rot2, Vec3Swizzles},
+    prelude::{Component, Deref, DerefMut, GlobalTransform, Transform},
+};
 /// Shape of the hitbox. Stores [`ColliderGroup::Hitbox`](crate::core::ColliderGroup::Hitbox).
 /// Every entity can have only one hitbox.
 #[derive(Component, Deref, DerefMut)]
 pub struct HitboxShape<Group: ColliderGroup>(pub Group::Hitbox);
 
+/// Shape of the hurtbox. Stores [`ColliderGroup::Hurtbox`].
+/// Entity is registered in the [`SpatialIndex`](crate::spatial_index::spatial_index::SpatialIndex)
+/// of the group as soon as the shape is inserted, inserting it again registers the hurtbox anew.
 #[derive(Component, Deref)]
+#[require(Transform, SpatialIndexRegistry<Group>)]
 pub struct HurtboxShape<Group: ColliderGroup>(pub Group::Hurtbox);
+
+/// Isometry of the collider of the `Group` with given [`GlobalTransform`].
+/// Rotation is only used if [`ColliderGroup::USE_ROTATION`] is true.
+pub fn collider_isometry<Group: ColliderGroup>(global_transform: &GlobalTransform) -> Isometry2d {
+    let translation = global_transform.translation().xy();
+    if !Group::USE_ROTATION {
+        return Isometry2d::from_translation(translation);
+    }
+
+    let x_axis = global_transform.affine().matrix3.x_axis.xy();
+    Isometry2d::new(translation, Rot2::radians(x_axis.to_angle()))
+}
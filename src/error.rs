@@ -0,0 +1,154 @@
+use bevy::{prelude::*, transform::helper::ComputeGlobalTransformError};
+use std::{any::TypeId, collections::HashSet, sync::Mutex};
+
+/// Error that happened because of a malformed entity. Reported through the [`BumpErrors`].
+#[derive(Event, Debug)]
+pub enum BumpError {
+    /// Global position of the hurtbox couldn't be computed while registering it.
+    /// Registration is retried on the next frame.
+    HurtboxPosition {
+        entity: Entity,
+        cause: ComputeGlobalTransformError,
+    },
+    /// Global position of the collider couldn't be computed while updating it.
+    /// The collider is skipped until its position can be computed again.
+    ColliderPosition {
+        entity: Entity,
+        cause: ComputeGlobalTransformError,
+    },
+    /// Component required by the [`ColliderGroup::Filter`](crate::ColliderGroup::Filter) is missing.
+    /// The collider doesn't collide with anything until the component is inserted.
+    /// Reported once per entity and component, again if the component is removed after being inserted.
+    MissingFilterComponent {
+        entity: Entity,
+        component: &'static str,
+    },
+    /// Hurtbox is stored in the [`SpatialIndex`](crate::spatial_index::spatial_index::SpatialIndex)
+    /// differently from what its registry says.
+    /// Found by the [`SpatialIndexDebugPlugin`](crate::spatial_index::SpatialIndexDebugPlugin).
+    InconsistentSpatialIndex {
+        entity: Entity,
+        problem: &'static str,
+    },
+}
+
+impl std::fmt::Display for BumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BumpError::HurtboxPosition { entity, cause } => write!(
+                f,
+                "unable to compute global position of hurtbox {entity} while registering it: {cause}"
+            ),
+            BumpError::ColliderPosition { entity, cause } => write!(
+                f,
+                "unable to compute global position of collider {entity}, skipping it: {cause}"
+            ),
+            BumpError::MissingFilterComponent { entity, component } => write!(
+                f,
+                "{entity} is missing `{component}` required by the collision filter"
+            ),
+            BumpError::InconsistentSpatialIndex { entity, problem } => {
+                write!(f, "spatial index is inconsistent, {entity} {problem}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BumpError {}
+
+/// What happens when a [`BumpError`] is reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Panic with the error.
+    Panic,
+    /// Log the error as a warning and skip the entity.
+    #[default]
+    Warn,
+    /// Skip the entity and send the error as an event, at the end of the frame.
+    Event,
+}
+
+/// Collects [`BumpError`]s according to the [`ErrorPolicy`].
+/// Errors are reported through a shared reference, so read only system params can report them too.
+#[derive(Resource, Debug, Default)]
+pub struct BumpErrors {
+    pub policy: ErrorPolicy,
+    queued: Mutex<Vec<BumpError>>,
+    missing_filter_components: Mutex<HashSet<(Entity, TypeId)>>,
+}
+
+impl BumpErrors {
+    pub fn new(policy: ErrorPolicy) -> Self {
+        Self {
+            policy,
+            queued: default(),
+            missing_filter_components: default(),
+        }
+    }
+
+    pub fn report(&self, error: BumpError) {
+        match self.policy {
+            ErrorPolicy::Panic => panic!("{error}"),
+            ErrorPolicy::Warn => warn!("{error}"),
+            ErrorPolicy::Event => self
+                .queued
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .push(error),
+        }
+    }
+
+    /// Reports [`BumpError::MissingFilterComponent`] the first time it's found for the entity,
+    /// filters look the component up for every pair on every frame.
+    /// Entity is forgotten once it's despawned or has the component, in the [`Last`] schedule.
+    pub fn report_missing_filter_component<C: Component>(&self, entity: Entity) {
+        let first = self
+            .missing_filter_components
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert((entity, TypeId::of::<C>()));
+        if first {
+            self.report(BumpError::MissingFilterComponent {
+                entity,
+                component: std::any::type_name::<C>(),
+            });
+        }
+    }
+}
+
+/// Adds the [`BumpErrors`] and sends the queued [`BumpError`] events in the [`Last`] schedule.
+/// Added by the [`SpatialIndexPlugin`](crate::spatial_index::SpatialIndexPlugin) if it's not added yet,
+/// add it before to choose the policy.
+#[derive(Debug, Default)]
+pub struct BumpErrorPlugin {
+    pub policy: ErrorPolicy,
+}
+
+impl Plugin for BumpErrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BumpErrors::new(self.policy))
+            .add_event::<BumpError>()
+            .add_systems(Last, (send_bump_errors, forget_found_filter_components));
+    }
+}
+
+fn send_bump_errors(errors: Res<BumpErrors>, mut events: EventWriter<BumpError>) {
+    let mut queued = errors
+        .queued
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    events.send_batch(queued.drain(..));
+}
+
+fn forget_found_filter_components(world: &World) {
+    world
+        .resource::<BumpErrors>()
+        .missing_filter_components
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .retain(|&(entity, component)| {
+            world
+                .get_entity(entity)
+                .is_ok_and(|entity| !entity.contains_type_id(component))
+        });
+}
@@ -1,8 +1,14 @@
-use bevy::{ecs::system::SystemParam, prelude::*, utils::all_tuples};
+use bevy::{
+    ecs::{schedule::InternedScheduleLabel, system::SystemParam},
+    prelude::*,
+    utils::all_tuples,
+};
 
+use super::response::ResponseCollisionInformation;
 use crate::{
-    core::{response::ResponseCollisionInformation, ColliderGroup},
-    ecs_core::{components::{HitboxLayer, HurtboxLayer}, LayerGroup},
+    collider::ColliderParts,
+    components::{HitboxShape, HurtboxShape},
+    ColliderGroup, CollisionDetectionSet,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -11,30 +17,31 @@ pub struct CollisionInformation {
     pub hurtbox: Entity,
     pub global_position: Vec2,
     pub normal: Option<Dir2>,
+    /// Parts of the hitbox and the hurtbox that collided, if known
+    pub parts: Option<ColliderParts>,
 }
 
 impl CollisionInformation {
-    pub fn from_response<Group: ColliderGroup>(
+    pub fn from_response<Data: Into<Entity>>(
         hitbox: Entity,
-        response: ResponseCollisionInformation<Group>,
-    ) -> Self
-    where
-        Group::HurtboxData: Into<Entity>,
-    {
+        response: ResponseCollisionInformation<Data>,
+    ) -> Self {
         Self {
             hitbox,
             hurtbox: response.data.into(),
             global_position: response.global_position,
             normal: Some(response.normal),
+            parts: Some(response.parts),
         }
     }
 }
 
 // TODO: Document about system state
-pub trait CollisionReportStrategy<Layer: LayerGroup> {
+pub trait CollisionReportStrategy<Group: ColliderGroup> {
     type Param: SystemParam;
 
-    fn register(app: &mut App);
+    /// Adds the systems of the strategy to the `schedule` the collision detection of the group runs in.
+    fn register(app: &mut App, schedule: InternedScheduleLabel);
 
     fn report_collisions(
         collisions: impl Iterator<Item = CollisionInformation>,
@@ -44,12 +51,12 @@ pub trait CollisionReportStrategy<Layer: LayerGroup> {
 
 macro_rules! impl_collision_report_strategy {
     ($(($t:ident, $p:ident)),*) => {
-        impl<Layer: LayerGroup, $($t: CollisionReportStrategy<Layer>),*> CollisionReportStrategy<Layer> for ($($t,)*)
+        impl<Group: ColliderGroup, $($t: CollisionReportStrategy<Group>),*> CollisionReportStrategy<Group> for ($($t,)*)
         {
             type Param = ParamSet<'static, 'static, ($($t::Param,)*)>;
 
-            fn register(app: &mut App) {
-                $($t::register(app);)*
+            fn register(app: &mut App, schedule: InternedScheduleLabel) {
+                $($t::register(app, schedule);)*
             }
 
             fn report_collisions(
@@ -71,10 +78,10 @@ pub struct Collided(pub CollisionInformation);
 
 pub struct SendCollisionEvent;
 
-impl<Layer: LayerGroup> CollisionReportStrategy<Layer> for SendCollisionEvent {
+impl<Group: ColliderGroup> CollisionReportStrategy<Group> for SendCollisionEvent {
     type Param = EventWriter<'static, Collided>;
 
-    fn register(app: &mut App) {
+    fn register(app: &mut App, _schedule: InternedScheduleLabel) {
         app.add_event::<Collided>();
     }
 
@@ -95,10 +102,10 @@ pub struct HitboxCollided(pub CollisionInformation);
 #[derive(Event)]
 pub struct HurtboxCollided(pub CollisionInformation);
 
-impl<Layer: LayerGroup> CollisionReportStrategy<Layer> for ObserveCollision {
+impl<Group: ColliderGroup> CollisionReportStrategy<Group> for ObserveCollision {
     type Param = Commands<'static, 'static>;
 
-    fn register(_app: &mut App) {}
+    fn register(_app: &mut App, _schedule: InternedScheduleLabel) {}
 
     fn report_collisions(
         collisions: impl Iterator<Item = CollisionInformation>,
@@ -114,43 +121,56 @@ impl<Layer: LayerGroup> CollisionReportStrategy<Layer> for ObserveCollision {
 }
 
 #[derive(Component)]
-pub struct HitboxCollisions<Layer: LayerGroup>(
+pub struct HitboxCollisions<Group: ColliderGroup>(
     pub Vec<CollisionInformation>,
-    std::marker::PhantomData<Layer>,
+    std::marker::PhantomData<Group>,
 );
 
-impl<Layer: LayerGroup> Default for HitboxCollisions<Layer> {
+impl<Group: ColliderGroup> Default for HitboxCollisions<Group> {
     fn default() -> Self {
         Self(Vec::new(), std::marker::PhantomData)
     }
 }
 
-fn add_hitbox_collisions<Layer: LayerGroup>(
-    trigger: Trigger<OnAdd, HitboxLayer<Layer>>,
+fn add_hitbox_collisions<Group: ColliderGroup>(
+    trigger: Trigger<OnAdd, HitboxShape<Group>>,
     mut commands: Commands,
 ) {
     commands
         .entity(trigger.entity())
-        .insert(HitboxCollisions::<Layer>::default());
+        .insert(HitboxCollisions::<Group>::default());
 }
 
-fn remove_hitbox_collisions<Layer: LayerGroup>(
-    trigger: Trigger<OnRemove, HitboxLayer<Layer>>,
+fn remove_hitbox_collisions<Group: ColliderGroup>(
+    trigger: Trigger<OnRemove, HitboxShape<Group>>,
     mut commands: Commands,
 ) {
     commands
         .entity(trigger.entity())
-        .remove::<HitboxCollisions<Layer>>();
+        .remove::<HitboxCollisions<Group>>();
+}
+
+fn clear_hitbox_collisions<Group: ColliderGroup>(
+    mut collisions: Query<&mut HitboxCollisions<Group>>,
+) {
+    for mut collisions in collisions.iter_mut() {
+        collisions.0.clear();
+    }
 }
 
+/// Stores collisions of the current frame in [`HitboxCollisions`] component of the hitbox.
 pub struct ExtendHitboxComponent;
 
-impl<Layer: LayerGroup> CollisionReportStrategy<Layer> for ExtendHitboxComponent {
-    type Param = Query<'static, 'static, &'static mut HitboxCollisions<Layer>>;
+impl<Group: ColliderGroup> CollisionReportStrategy<Group> for ExtendHitboxComponent {
+    type Param = Query<'static, 'static, &'static mut HitboxCollisions<Group>>;
 
-    fn register(app: &mut App) {
-        app.observe(add_hitbox_collisions::<Layer>);
-        app.observe(remove_hitbox_collisions::<Layer>);
+    fn register(app: &mut App, schedule: InternedScheduleLabel) {
+        app.add_observer(add_hitbox_collisions::<Group>);
+        app.add_observer(remove_hitbox_collisions::<Group>);
+        app.add_systems(
+            schedule,
+            clear_hitbox_collisions::<Group>.in_set(CollisionDetectionSet::First),
+        );
     }
 
     fn report_collisions(
@@ -159,7 +179,7 @@ impl<Layer: LayerGroup> CollisionReportStrategy<Layer> for ExtendHitboxComponent
     ) {
         for collision in collisions {
             let Ok(mut component) = param.get_mut(collision.hitbox) else {
-                return;
+                continue;
             };
 
             component.0.push(collision);
@@ -168,43 +188,56 @@ impl<Layer: LayerGroup> CollisionReportStrategy<Layer> for ExtendHitboxComponent
 }
 
 #[derive(Component)]
-pub struct HurtboxCollisions<Layer: LayerGroup>(
+pub struct HurtboxCollisions<Group: ColliderGroup>(
     pub Vec<CollisionInformation>,
-    std::marker::PhantomData<Layer>,
+    std::marker::PhantomData<Group>,
 );
 
-impl<Layer: LayerGroup> Default for HurtboxCollisions<Layer> {
+impl<Group: ColliderGroup> Default for HurtboxCollisions<Group> {
     fn default() -> Self {
         Self(Vec::new(), std::marker::PhantomData)
     }
 }
 
-fn add_hurtbox_collisions<Layer: LayerGroup>(
-    trigger: Trigger<OnAdd, HurtboxLayer<Layer>>,
+fn add_hurtbox_collisions<Group: ColliderGroup>(
+    trigger: Trigger<OnAdd, HurtboxShape<Group>>,
     mut commands: Commands,
 ) {
     commands
         .entity(trigger.entity())
-        .insert(HurtboxCollisions::<Layer>::default());
+        .insert(HurtboxCollisions::<Group>::default());
 }
 
-fn remove_hurtbox_collisions<Layer: LayerGroup>(
-    trigger: Trigger<OnRemove, HurtboxLayer<Layer>>,
+fn remove_hurtbox_collisions<Group: ColliderGroup>(
+    trigger: Trigger<OnRemove, HurtboxShape<Group>>,
     mut commands: Commands,
 ) {
     commands
         .entity(trigger.entity())
-        .remove::<HurtboxCollisions<Layer>>();
+        .remove::<HurtboxCollisions<Group>>();
+}
+
+fn clear_hurtbox_collisions<Group: ColliderGroup>(
+    mut collisions: Query<&mut HurtboxCollisions<Group>>,
+) {
+    for mut collisions in collisions.iter_mut() {
+        collisions.0.clear();
+    }
 }
 
+/// Stores collisions of the current frame in [`HurtboxCollisions`] component of the hurtbox.
 pub struct ExtendHurtboxComponent;
 
-impl<Layer: LayerGroup> CollisionReportStrategy<Layer> for ExtendHurtboxComponent {
-    type Param = Query<'static, 'static, &'static mut HurtboxCollisions<Layer>>;
+impl<Group: ColliderGroup> CollisionReportStrategy<Group> for ExtendHurtboxComponent {
+    type Param = Query<'static, 'static, &'static mut HurtboxCollisions<Group>>;
 
-    fn register(app: &mut App) {
-        app.observe(add_hurtbox_collisions::<Layer>);
-        app.observe(remove_hurtbox_collisions::<Layer>);
+    fn register(app: &mut App, schedule: InternedScheduleLabel) {
+        app.add_observer(add_hurtbox_collisions::<Group>);
+        app.add_observer(remove_hurtbox_collisions::<Group>);
+        app.add_systems(
+            schedule,
+            clear_hurtbox_collisions::<Group>.in_set(CollisionDetectionSet::First),
+        );
     }
 
     fn report_collisions(
@@ -213,7 +246,7 @@ impl<Layer: LayerGroup> CollisionReportStrategy<Layer> for ExtendHurtboxComponen
     ) {
         for collision in collisions {
             let Ok(mut component) = param.get_mut(collision.hurtbox) else {
-                return;
+                continue;
             };
 
             component.0.push(collision);
@@ -3,21 +3,52 @@ pub mod response;
 mod scanner;
 mod velocity;
 
-
-use bevy::prelude::*;
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 
 pub use scanner::*;
-
-/// Implements ScheduleLabel
-const COLLISION_DETECTION_SCHEDULE: Update = Update;
+pub use velocity::*;
 
 pub trait BumpAppExtension {
     fn add_scanner_group<T: ScannerGroup>(&mut self) -> &mut Self;
+
+    /// Same as [`BumpAppExtension::add_scanner_group`], but scanners are updated in the given `schedule`.
+    fn add_scanner_group_in_schedule<T: ScannerGroup>(
+        &mut self,
+        schedule: impl ScheduleLabel,
+    ) -> &mut Self;
+
+    fn add_velocity_group<T: VelocityGroup>(&mut self) -> &mut Self;
+
+    /// Same as [`BumpAppExtension::add_velocity_group`], but hitboxes are moved in the given `schedule`,
+    /// for example in [`FixedUpdate`].
+    fn add_velocity_group_in_schedule<T: VelocityGroup>(
+        &mut self,
+        schedule: impl ScheduleLabel,
+    ) -> &mut Self;
 }
 
 impl BumpAppExtension for App {
     fn add_scanner_group<T: ScannerGroup>(&mut self) -> &mut Self {
-        register_scanner_group::<T>(self);
+        self.add_scanner_group_in_schedule::<T>(Update)
+    }
+
+    fn add_scanner_group_in_schedule<T: ScannerGroup>(
+        &mut self,
+        schedule: impl ScheduleLabel,
+    ) -> &mut Self {
+        register_scanner_group::<T>(self, schedule.intern());
+        self
+    }
+
+    fn add_velocity_group<T: VelocityGroup>(&mut self) -> &mut Self {
+        self.add_velocity_group_in_schedule::<T>(Update)
+    }
+
+    fn add_velocity_group_in_schedule<T: VelocityGroup>(
+        &mut self,
+        schedule: impl ScheduleLabel,
+    ) -> &mut Self {
+        register_velocity_group::<T>(self, schedule.intern());
         self
     }
 }
@@ -1,58 +1,51 @@
-use crate::{collider::{Collider, ColliderInteraction}, spatial_query::SpatialQuery};
+use crate::{
+    collider::{Collider, ColliderParts},
+    spatial_query::{filter::HitboxParam, SpatialQuery},
+    ColliderGroup,
+};
 use bevy::math::{Dir2, Vec2};
 
 /// Contains information about one of collisions that was processed with [`CollisionResponse`].
-#[derive(Debug)]
-pub struct ResponseCollisionInformation<T: SpatialQuery> {
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseCollisionInformation<Data> {
     /// The point on the desired path (or on the path corrected by solver) at wich collision was detected
     /// Should make sense for it to be [`Collider::position`] of actor that performed movement
     pub global_position: Vec2,
-    /// Result of [`Collider::normal`] of body against which collision was detected
+    /// Normal of the collision, as returned by [`Collider::cast`]
     pub normal: Dir2,
-    pub data: T::HurtboxData,
+    /// Parts of the hitbox and the hurtbox that collided
+    pub parts: ColliderParts,
+    /// [`SpatialQuery::HurtboxData`] of the hurtbox against which collision was detected
+    pub data: Data,
 }
 
-impl<T: SpatialQuery> Clone for ResponseCollisionInformation<T>
-where
-    T::HurtboxData: Clone,
-{
-    fn clone(&self) -> Self {
-        Self {
-            global_position: self.global_position,
-            normal: self.normal,
-            data: self.data.clone(),
-        }
-    }
-}
-
-impl<T: SpatialQuery> Copy for ResponseCollisionInformation<T> where T::HurtboxData: Copy {}
-
-impl<T: SpatialQuery> ResponseCollisionInformation<T> {
+impl<Data> ResponseCollisionInformation<Data> {
     fn from_cast(
         position: Vec2,
         direction: Dir2,
-    ) -> impl FnMut((f32, Dir2, T::HurtboxData)) -> Self {
-        move |(dist, normal, data)| Self {
+    ) -> impl FnMut((f32, Dir2, ColliderParts, Data)) -> Self {
+        move |(dist, normal, parts, data)| Self {
             global_position: position + direction * dist,
             normal,
+            parts,
             data,
         }
     }
 }
 
-pub trait RunningResponse<T: SpatialQuery>: Sized {
-    type AfterOutput: Iterator<Item = ResponseCollisionInformation<T>>;
+pub trait RunningResponse<Data>: Sized {
+    type AfterOutput: Iterator<Item = ResponseCollisionInformation<Data>>;
 
-    fn next(self) -> RunningResponseVariant<Self, T>;
+    fn next(self) -> RunningResponseVariant<Self, Data>;
 
-    fn into_iter(self, buf: &mut Vec2) -> ResponseIterator<Self, T> {
+    fn into_iter(self, buf: &mut Vec2) -> ResponseIterator<'_, Self, Data> {
         ResponseIterator {
             buf,
             current_iter: ResponseIteratorVariant::BeforeOutput(self),
         }
     }
 
-    fn ignore_resulting_offset(self) -> IgnoreResultingOffsetIterator<Self, T> {
+    fn ignore_resulting_offset(self) -> IgnoreResultingOffsetIterator<Self, Data> {
         IgnoreResultingOffsetIterator {
             curent_iter: ResponseIteratorVariant::BeforeOutput(self),
         }
@@ -60,7 +53,7 @@ pub trait RunningResponse<T: SpatialQuery>: Sized {
 
     fn until_resulting_offset(
         mut self,
-        mut f: impl FnMut(ResponseCollisionInformation<T>),
+        mut f: impl FnMut(ResponseCollisionInformation<Data>),
     ) -> (Vec2, Self::AfterOutput) {
         use RunningResponseVariant::*;
 
@@ -75,32 +68,32 @@ pub trait RunningResponse<T: SpatialQuery>: Sized {
         }
     }
 
-    fn foreach(self, f: impl FnMut(ResponseCollisionInformation<T>)) -> Vec2 {
+    fn foreach(self, f: impl FnMut(ResponseCollisionInformation<Data>)) -> Vec2 {
         let mut buf = Vec2::ZERO;
         self.into_iter(&mut buf).for_each(f);
         buf
     }
 }
 
-pub enum RunningResponseVariant<T: RunningResponse<Group>, Group: ColliderGroup> {
-    Collision(ResponseCollisionInformation<Group>, T),
+pub enum RunningResponseVariant<T: RunningResponse<Data>, Data> {
+    Collision(ResponseCollisionInformation<Data>, T),
     ResultingOffset(Vec2, T::AfterOutput),
 }
 
-enum ResponseIteratorVariant<T: RunningResponse<Group>, Group: ColliderGroup> {
+enum ResponseIteratorVariant<T: RunningResponse<Data>, Data> {
     BeforeOutput(T),
     AfterOutput(T::AfterOutput),
 }
 
-pub struct ResponseIterator<'a, T: RunningResponse<Group>, Group: ColliderGroup> {
+pub struct ResponseIterator<'a, T: RunningResponse<Data>, Data> {
     buf: &'a mut Vec2,
-    current_iter: ResponseIteratorVariant<T, Group>,
+    current_iter: ResponseIteratorVariant<T, Data>,
 }
 
-impl<'a, T: RunningResponse<Group>, Group: ColliderGroup> Iterator
-    for ResponseIterator<'a, T, Group>
+impl<'a, T: RunningResponse<Data>, Data> Iterator
+    for ResponseIterator<'a, T, Data>
 {
-    type Item = ResponseCollisionInformation<Group>;
+    type Item = ResponseCollisionInformation<Data>;
 
     fn next(&mut self) -> Option<Self::Item> {
         use ResponseIteratorVariant::*;
@@ -123,14 +116,14 @@ impl<'a, T: RunningResponse<Group>, Group: ColliderGroup> Iterator
     }
 }
 
-pub struct IgnoreResultingOffsetIterator<T: RunningResponse<Group>, Group: ColliderGroup> {
-    curent_iter: ResponseIteratorVariant<T, Group>,
+pub struct IgnoreResultingOffsetIterator<T: RunningResponse<Data>, Data> {
+    curent_iter: ResponseIteratorVariant<T, Data>,
 }
 
-impl<T: RunningResponse<Group>, Group: ColliderGroup> Iterator
-    for IgnoreResultingOffsetIterator<T, Group>
+impl<T: RunningResponse<Data>, Data> Iterator
+    for IgnoreResultingOffsetIterator<T, Data>
 {
-    type Item = ResponseCollisionInformation<Group>;
+    type Item = ResponseCollisionInformation<Data>;
 
     fn next(&mut self) -> Option<Self::Item> {
         use ResponseIteratorVariant::*;
@@ -150,51 +143,41 @@ impl<T: RunningResponse<Group>, Group: ColliderGroup> Iterator
 }
 
 #[inline(always)]
-fn empty<Group: ColliderGroup>() -> std::iter::Empty<ResponseCollisionInformation<Group>> {
+fn empty<Data>() -> std::iter::Empty<ResponseCollisionInformation<Data>> {
     std::iter::empty()
 }
 
-pub struct ImmediateResultingOffset<
-    Collisions: Iterator<Item = ResponseCollisionInformation<Group>>,
-    Group: ColliderGroup,
-> {
+pub struct ImmediateResultingOffset<Collisions> {
     offset: Vec2,
     collisions: Collisions,
 }
 
-impl<Collisions: Iterator<Item = ResponseCollisionInformation<Group>>, Group: ColliderGroup>
-    ImmediateResultingOffset<Collisions, Group>
-{
+impl<Collisions> ImmediateResultingOffset<Collisions> {
     fn new(offset: Vec2, collisions: Collisions) -> Self {
         ImmediateResultingOffset { offset, collisions }
     }
 }
 
-impl<Collisions: Iterator<Item = ResponseCollisionInformation<Group>>, Group: ColliderGroup>
-    RunningResponse<Group> for ImmediateResultingOffset<Collisions, Group>
+impl<Collisions: Iterator<Item = ResponseCollisionInformation<Data>>, Data> RunningResponse<Data>
+    for ImmediateResultingOffset<Collisions>
 {
     type AfterOutput = Collisions;
 
-    fn next(self) -> RunningResponseVariant<Self, Group> {
+    fn next(self) -> RunningResponseVariant<Self, Data> {
         RunningResponseVariant::ResultingOffset(self.offset, self.collisions)
     }
 }
 
-pub struct LazyResponse<
+pub struct LazyResponse<F>(pub F);
+
+impl<F, Collisions, Data> RunningResponse<Data> for LazyResponse<F>
+where
     F: FnOnce() -> (Vec2, Collisions),
-    Group: ColliderGroup,
-    Collisions: Iterator<Item = ResponseCollisionInformation<Group>>,
->(pub F);
-
-impl<
-        F: FnOnce() -> (Vec2, Collisions),
-        Group: ColliderGroup,
-        Collisions: Iterator<Item = ResponseCollisionInformation<Group>>,
-    > RunningResponse<Group> for LazyResponse<F, Group, Collisions>
+    Collisions: Iterator<Item = ResponseCollisionInformation<Data>>,
 {
     type AfterOutput = Collisions;
 
-    fn next(self) -> RunningResponseVariant<Self, Group> {
+    fn next(self) -> RunningResponseVariant<Self, Data> {
         let (offset, collisions) = self.0();
         RunningResponseVariant::ResultingOffset(offset, collisions)
     }
@@ -203,34 +186,37 @@ impl<
 /// Solver defines how actor will react to met colliders.
 /// When actor meets collider it refers to `CollisionResponse`.
 ///
-/// `colliders` is a broad phase
-/// `actor` is actor that performs the collision
+/// `collisions` is a spatial query used to find hurtboxes on the way
+/// `hitbox` is actor that performs the collision
+/// `hitbox_filter` is filter parameter of the actor, passed to every query
 ///
-/// `offset` is offset that `actor` desires to move this call
+/// `offset_dir` and `offset_len` is offset that `hitbox` desires to move this call
 ///
-/// Returns actual offset that actor should move from the point at with collision was detected
+/// Returns actual offset that actor should move from its starting position
 /// and information about all the collisions that happened
 pub trait CollisionResponse {
-    fn respond<'a, Hitbox: ColliderInteraction<SQ::Hurtbox>, SQ: SpatialQuery + 'a>(
+    fn respond<'a, 'p: 'a, Group: ColliderGroup, SQ: SpatialQuery<Group> + 'a>(
         &'a mut self,
-        collisions: SQ,
-        hitbox: Collider<'a, Hitbox>,
+        collisions: &'a mut SQ,
+        hitbox: Collider<'a, Group::Hitbox>,
         offset_dir: Dir2,
         offset_len: f32,
-    ) -> impl RunningResponse<SQ> + 'a;
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl RunningResponse<SQ::HurtboxData> + 'a;
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Ignore;
 
 impl CollisionResponse for Ignore {
-    fn respond<'a, Group: ColliderGroup, Collisions: SpatialQuery<Group> + 'a>(
+    fn respond<'a, 'p: 'a, Group: ColliderGroup, SQ: SpatialQuery<Group> + 'a>(
         &'a mut self,
-        _collisions: Collisions,
+        _collisions: &'a mut SQ,
         _hitbox: Collider<'a, Group::Hitbox>,
         offset_dir: Dir2,
         offset_len: f32,
-    ) -> impl RunningResponse<Group> + 'a {
+        _hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl RunningResponse<SQ::HurtboxData> + 'a {
         ImmediateResultingOffset::new(offset_dir * offset_len, empty())
     }
 }
@@ -239,18 +225,22 @@ impl CollisionResponse for Ignore {
 pub struct Pass;
 
 impl CollisionResponse for Pass {
-    fn respond<'a, Group: ColliderGroup, Collisions: SpatialQuery<Group> + 'a>(
+    fn respond<'a, 'p: 'a, Group: ColliderGroup, SQ: SpatialQuery<Group> + 'a>(
         &'a mut self,
-        collisions: Collisions,
+        collisions: &'a mut SQ,
         hitbox: Collider<'a, Group::Hitbox>,
         offset_dir: Dir2,
         offset_len: f32,
-    ) -> impl RunningResponse<Group> + 'a {
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl RunningResponse<SQ::HurtboxData> + 'a {
         ImmediateResultingOffset::new(
             offset_dir * offset_len,
-            collisions.cast(hitbox, offset_dir, offset_len).map(
-                ResponseCollisionInformation::from_cast(hitbox.position, offset_dir),
-            ),
+            collisions
+                .cast(hitbox, offset_dir, offset_len, hitbox_filter)
+                .map(ResponseCollisionInformation::from_cast(
+                    hitbox.position,
+                    offset_dir,
+                )),
         )
     }
 }
@@ -259,50 +249,56 @@ impl CollisionResponse for Pass {
 pub struct Touch;
 
 impl CollisionResponse for Touch {
-    fn respond<'a, Group: ColliderGroup, Collisions: SpatialQuery<Group> + 'a>(
+    fn respond<'a, 'p: 'a, Group: ColliderGroup, SQ: SpatialQuery<Group> + 'a>(
         &'a mut self,
-        collisions: Collisions,
+        collisions: &'a mut SQ,
         hitbox: Collider<'a, Group::Hitbox>,
         offset_dir: Dir2,
         offset_len: f32,
-    ) -> impl RunningResponse<Group> + 'a {
-        let mut min_distance = offset_len;
-        let mut resulting_collision = None;
-
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl RunningResponse<SQ::HurtboxData> + 'a {
         LazyResponse(move || {
-            for collision in collisions.cast(hitbox, offset_dir, offset_len) {
-                if min_distance >= collision.0 {
-                    min_distance = collision.0;
-                    resulting_collision = Some(ResponseCollisionInformation::from_cast(
-                        hitbox.position,
-                        offset_dir,
-                    )(collision));
-                }
-            }
-
-            (offset_dir * min_distance, resulting_collision.into_iter())
+            let Some(collision) =
+                collisions.cast_first(hitbox, offset_dir, offset_len, hitbox_filter)
+            else {
+                return (offset_dir * offset_len, None.into_iter());
+            };
+
+            let min_distance = collision.0;
+            let resulting_collision =
+                ResponseCollisionInformation::from_cast(hitbox.position, offset_dir)(collision);
+            (
+                offset_dir * min_distance,
+                Some(resulting_collision).into_iter(),
+            )
         })
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn trajectory_change_on_touch<
     'a,
+    'p: 'a,
     F: FnMut(Vec2, Dir2) -> Vec2,
     Group: ColliderGroup,
-    Collisions: SpatialQuery<Group>,
+    SQ: SpatialQuery<Group>,
 >(
-    collisions: Collisions,
+    collisions: &'a mut SQ,
     mut hitbox: Collider<'a, Group::Hitbox>,
     offset_dir: Dir2,
     offset_len: f32,
+    hitbox_filter: HitboxParam<'p, Group>,
     mut trajectory_change: F,
 ) -> (
     Vec2,
-    std::vec::IntoIter<ResponseCollisionInformation<Group>>,
+    std::vec::IntoIter<ResponseCollisionInformation<SQ::HurtboxData>>,
 ) {
     // Vector with all collisions
     let mut res_vec = Vec::new();
 
+    // Position from which the movement started
+    let start_position = hitbox.position;
+
     // We want to move that distance
     let mut desired_offset = offset_dir * offset_len;
 
@@ -310,7 +306,7 @@ pub fn trajectory_change_on_touch<
     let mut actual_offset = Vec2::ZERO;
     // Moving that distance, checking if we collide
     let mut opt_collision_information = Touch
-        .respond(collisions, hitbox, offset_dir, offset_len)
+        .respond(collisions, hitbox, offset_dir, offset_len, hitbox_filter)
         .into_iter(&mut actual_offset)
         .next();
 
@@ -329,36 +325,43 @@ pub fn trajectory_change_on_touch<
 
         // If desired offset is zero, we are done
         let Ok((desired_dir, desired_len)) = Dir2::new_and_length(desired_offset) else {
+            // Hitbox was already moved by actual offset
+            actual_offset = Vec2::ZERO;
             break;
         };
 
         // If not zero, check if colliding agin, with once again setting actual offset
         opt_collision_information = Touch
-            .respond(collisions, hitbox, desired_dir, desired_len)
+            .respond(collisions, hitbox, desired_dir, desired_len, hitbox_filter)
             .into_iter(&mut actual_offset)
             .next();
     }
 
-    (actual_offset, res_vec.into_iter())
+    (
+        hitbox.position + actual_offset - start_position,
+        res_vec.into_iter(),
+    )
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Slide;
 
 impl CollisionResponse for Slide {
-    fn respond<'a, Group: ColliderGroup, Collisions: SpatialQuery<Group> + 'a>(
+    fn respond<'a, 'p: 'a, Group: ColliderGroup, SQ: SpatialQuery<Group> + 'a>(
         &'a mut self,
-        collisions: Collisions,
+        collisions: &'a mut SQ,
         hitbox: Collider<'a, Group::Hitbox>,
         offset_dir: Dir2,
         offset_len: f32,
-    ) -> impl RunningResponse<Group> + 'a {
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl RunningResponse<SQ::HurtboxData> + 'a {
         LazyResponse(move || {
             trajectory_change_on_touch(
                 collisions,
                 hitbox,
                 offset_dir,
                 offset_len,
+                hitbox_filter,
                 |left_movement, normal| left_movement.project_onto_normalized(normal.perp()),
             )
         })
@@ -373,15 +376,23 @@ fn bounce(left_movement: Vec2, normal: Dir2) -> Vec2 {
 pub struct Bounce;
 
 impl CollisionResponse for Bounce {
-    fn respond<'a, Group: ColliderGroup, Collisions: SpatialQuery<Group> + 'a>(
+    fn respond<'a, 'p: 'a, Group: ColliderGroup, SQ: SpatialQuery<Group> + 'a>(
         &'a mut self,
-        collisions: Collisions,
+        collisions: &'a mut SQ,
         hitbox: Collider<'a, Group::Hitbox>,
         offset_dir: Dir2,
         offset_len: f32,
-    ) -> impl RunningResponse<Group> + 'a {
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl RunningResponse<SQ::HurtboxData> + 'a {
         LazyResponse(move || {
-            trajectory_change_on_touch(collisions, hitbox, offset_dir, offset_len, bounce)
+            trajectory_change_on_touch(
+                collisions,
+                hitbox,
+                offset_dir,
+                offset_len,
+                hitbox_filter,
+                bounce,
+            )
         })
     }
 }
@@ -403,13 +414,14 @@ impl<NextResponse: CollisionResponse> LimitedBounce<NextResponse> {
 }
 
 impl CollisionResponse for LimitedBounce<Ignore> {
-    fn respond<'a, Group: ColliderGroup, Collisions: SpatialQuery<Group> + 'a>(
+    fn respond<'a, 'p: 'a, Group: ColliderGroup, SQ: SpatialQuery<Group> + 'a>(
         &'a mut self,
-        collisions: Collisions,
+        collisions: &'a mut SQ,
         hitbox: Collider<'a, Group::Hitbox>,
         offset_dir: Dir2,
         offset_len: f32,
-    ) -> impl RunningResponse<Group> + 'a {
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl RunningResponse<SQ::HurtboxData> + 'a {
         LazyResponse(move || {
             let mut outer_left_movement = Vec2::ZERO;
             let (offset, collisions) = trajectory_change_on_touch(
@@ -417,6 +429,7 @@ impl CollisionResponse for LimitedBounce<Ignore> {
                 hitbox,
                 offset_dir,
                 offset_len,
+                hitbox_filter,
                 |left_movement, normal| {
                     if self.bounces == 0 {
                         outer_left_movement = left_movement;
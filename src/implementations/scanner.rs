@@ -1,123 +1,200 @@
+use super::{
+    collision_report_strategy::{CollisionInformation, CollisionReportStrategy},
+    response::{CollisionResponse, Pass, RunningResponse},
+};
 use crate::{
-    core::{
-        collider::Collider,
-        response::{Pass, RunningResponse},
-    },
-    ecs_core::{
-        collision_check::CollisionCheck,
-        components::{HitboxLayer, HitboxMonitoring, HitboxShape},
-        CollisionDetectionSet, LayerGroup,
+    collider::Collider,
+    components::{collider_isometry, HitboxShape},
+    error::{BumpError, BumpErrors},
+    spatial_index::{query::SpatialIndexQuery, SpatialIndexColliderGroup},
+    spatial_query::{
+        filter::{HitboxFilterSystemParam, SystemSpatialQueryFilter},
+        SpatialQuery,
     },
-    implementaitons::collision_report_strategy::CollisionInformation,
+    ColliderGroup, CollisionDetectionSet, CollisionImplementation, ImplementationPlugin,
 };
-use bevy::{ecs::system::StaticSystemParam, prelude::*};
-
-use super::collision_report_strategy::CollisionReportStrategy;
+use bevy::{
+    ecs::{schedule::InternedScheduleLabel, system::StaticSystemParam},
+    prelude::*,
+};
+use std::marker::PhantomData;
+
+/// Scanner hitboxes don't move by themselves, they only detect hurtboxes
+/// that were met on the way from the last position to the current one.
+pub trait ScannerImplementation<Group: ColliderGroup<Implementation = Self>>:
+    CollisionImplementation<Group>
+{
+    type ReportStrategy: CollisionReportStrategy<Group>;
+}
 
-pub trait ScannerGroup: LayerGroup {
+pub trait ScannerGroup:
+    SpatialIndexColliderGroup<Implementation: ScannerImplementation<Self>>
+{
     type ReportStrategy: CollisionReportStrategy<Self>;
 }
 
-pub(super) fn register_scanner_group<T: ScannerGroup>(app: &mut App) {
-    T::ReportStrategy::register(app);
+impl<Group: SpatialIndexColliderGroup<Implementation: ScannerImplementation<Group>>> ScannerGroup
+    for Group
+{
+    type ReportStrategy = <Group::Implementation as ScannerImplementation<Group>>::ReportStrategy;
+}
+
+/// Ready made [`ScannerImplementation`] of any group,
+/// that can be added to the app with the [`WithColliderGroup`](crate::WithColliderGroup).
+pub struct ScannerPlugin<ReportStrategy>(PhantomData<fn() -> ReportStrategy>);
+
+impl<R> Default for ScannerPlugin<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<Group: ColliderGroup<Implementation = Self>, R: 'static> CollisionImplementation<Group>
+    for ScannerPlugin<R>
+{
+}
+
+impl<Group, R> ScannerImplementation<Group> for ScannerPlugin<R>
+where
+    Group: ColliderGroup<Implementation = Self>,
+    R: CollisionReportStrategy<Group> + 'static,
+{
+    type ReportStrategy = R;
+}
+
+impl<Group: ScannerGroup<Implementation = Self>, R: 'static> ImplementationPlugin<Group>
+    for ScannerPlugin<R>
+{
+    fn build(&self, app: &mut App, schedule: InternedScheduleLabel) {
+        register_scanner_group::<Group>(app, schedule);
+    }
+}
+
+pub(super) fn register_scanner_group<Group: ScannerGroup>(
+    app: &mut App,
+    schedule: InternedScheduleLabel,
+) {
+    Group::ReportStrategy::register(app, schedule);
+
+    crate::configure_collision_detection_sets(app, schedule);
 
     app.add_systems(
-        super::COLLISION_DETECTION_SCHEDULE,
-        collide_scanner_group::<T>.in_set(CollisionDetectionSet::Colliding),
+        schedule,
+        collide_scanner_group::<Group>.in_set(CollisionDetectionSet::Colliding),
     );
 
-    app.observe(add_scanner_last_position::<T>)
-        .observe(remove_scanner_last_position::<T>);
+    app.add_observer(add_scanner_last_position::<Group>)
+        .add_observer(remove_scanner_last_position::<Group>);
 }
 
 #[derive(Component)]
-struct ScannerHitboxLastPosition<Layer: ScannerGroup>(Vec2, std::marker::PhantomData<Layer>);
+struct ScannerHitboxLastPosition<Group: ScannerGroup>(Vec2, std::marker::PhantomData<Group>);
 
-fn add_scanner_last_position<Layer: ScannerGroup>(
-    trigger: Trigger<OnAdd, HitboxLayer<Layer>>,
+fn add_scanner_last_position<Group: ScannerGroup>(
+    trigger: Trigger<OnAdd, HitboxShape<Group>>,
     transform_helper: TransformHelper,
+    errors: Res<BumpErrors>,
     mut commands: Commands,
 ) {
     let position = transform_helper
         .compute_global_transform(trigger.entity())
-        .map(|global_transform|global_transform.translation().xy())
-        .unwrap_or_else(|_| {
-            warn!("Unable to compute global position of registered scanner of {}. Setting scanner position to (0, 0).", trigger.entity());
+        .map(|global_transform| global_transform.translation().xy())
+        .unwrap_or_else(|cause| {
+            // Scanner starts at (0, 0) then
+            errors.report(BumpError::ColliderPosition {
+                entity: trigger.entity(),
+                cause,
+            });
             Vec2::ZERO
         });
 
     commands
         .entity(trigger.entity())
-        .insert(ScannerHitboxLastPosition::<Layer>(
+        .insert(ScannerHitboxLastPosition::<Group>(
             position,
             std::marker::PhantomData,
         ));
 }
 
-fn remove_scanner_last_position<Layer: ScannerGroup>(
-    trigger: Trigger<OnRemove, HitboxLayer<Layer>>,
+fn remove_scanner_last_position<Group: ScannerGroup>(
+    trigger: Trigger<OnRemove, HitboxShape<Group>>,
     mut commands: Commands,
 ) {
     commands
         .entity(trigger.entity())
-        .remove::<ScannerHitboxLastPosition<Layer>>();
+        .remove::<ScannerHitboxLastPosition<Group>>();
 }
 
-fn collide_scanner_group<T: ScannerGroup>(
-    collision_check: CollisionCheck<T>,
+type ReportParam<Group> =
+    <<Group as ScannerGroup>::ReportStrategy as CollisionReportStrategy<Group>>::Param;
+
+/// Scanner is never collided with itself, when it's a hurtbox of the group too.
+fn collide_scanner_group<Group: ScannerGroup>(
+    spatial_query: SpatialIndexQuery<Group>,
     mut hitboxes: Query<(
         Entity,
-        &mut ScannerHitboxLastPosition<T>,
-        &HitboxShape<T>,
-        &HitboxLayer<T>,
-        Option<&HitboxMonitoring<T>>,
+        &mut ScannerHitboxLastPosition<Group>,
+        &HitboxShape<Group>,
     )>,
+    hitbox_filter: StaticSystemParam<HitboxFilterSystemParam<Group>>,
     transform_helper: TransformHelper,
+    errors: Res<BumpErrors>,
 
-    mut report_param: StaticSystemParam<<T::ReportStrategy as CollisionReportStrategy<T>>::Param>,
+    mut report_param: StaticSystemParam<ReportParam<Group>>,
 ) {
-    for (hitbox_entity, mut last_position, shape, layer, monitoring) in hitboxes.iter_mut() {
-        let Ok(new_position) = transform_helper.compute_global_transform(hitbox_entity) else {
-            warn!("Unable to compute global position of registered scanner of {hitbox_entity}. Skipping scanner update.");
-            continue;
+    for (hitbox_entity, mut last_position, shape) in hitboxes.iter_mut() {
+        let new_position = match transform_helper.compute_global_transform(hitbox_entity) {
+            Ok(new_position) => new_position,
+            Err(cause) => {
+                errors.report(BumpError::ColliderPosition {
+                    entity: hitbox_entity,
+                    cause,
+                });
+                continue;
+            }
         };
-        let new_position = new_position.translation().xy();
-        let position_change = last_position.0 - new_position;
+        let new_isometry = collider_isometry::<Group>(&new_position);
+        let new_position = new_isometry.translation;
+        let position_change = new_position - last_position.0;
+
+        let hitbox_param = Group::Filter::hitbox_filter_param(hitbox_entity, &hitbox_filter);
+        let mut spatial_query = spatial_query.excluding(hitbox_entity);
 
         use iter_n::iter2::*;
         let mut pass = Pass;
 
-        let collisions = if monitoring.copied().unwrap_or_default().0 {
-            let hitbox = Collider::new(&**shape, last_position.0);
-
-            if let Ok((offset_dir, offset_len)) = Dir2::new_and_length(position_change) {
-                collision_check
-                    .check_movement(hitbox, offset_dir, offset_len, &layer.0, &mut pass)
-                    .ignore_resulting_offset()
-                    .map(|x| {
-                        CollisionInformation::from_response::<T>(
-                            hitbox_entity,
-                            x,
-                        )
-                    })
-                    .into_iter0()
-            } else {
-                collision_check
-                    .check_intersection(hitbox, &layer.0)
-                    .map(|hurtbox| CollisionInformation {
-                        hitbox: hitbox_entity,
-                        global_position: new_position,
-                        hurtbox,
-                        normal: None,
-                    })
-                    .into_iter1()
-            }
+        let collisions = if let Ok((offset_dir, offset_len)) = Dir2::new_and_length(position_change)
+        {
+            let hitbox = Collider::from_isometry(
+                &**shape,
+                Isometry2d::new(last_position.0, new_isometry.rotation),
+            );
+
+            pass.respond(
+                &mut spatial_query,
+                hitbox,
+                offset_dir,
+                offset_len,
+                hitbox_param,
+            )
+            .ignore_resulting_offset()
+            .map(|x| CollisionInformation::from_response(hitbox_entity, x))
             .into_iter0()
         } else {
-            std::iter::empty::<CollisionInformation>().into_iter1()
+            let hitbox = Collider::from_isometry(&**shape, new_isometry);
+
+            SpatialQuery::intersect(&mut spatial_query, hitbox, hitbox_param)
+                .map(|hurtbox| CollisionInformation {
+                    hitbox: hitbox_entity,
+                    global_position: new_position,
+                    hurtbox,
+                    normal: None,
+                    parts: None,
+                })
+                .into_iter1()
         };
 
-        T::ReportStrategy::report_collisions(collisions, &mut report_param);
+        Group::ReportStrategy::report_collisions(collisions, &mut report_param);
 
         last_position.0 = new_position;
     }
@@ -1,46 +1,295 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::{schedule::InternedScheduleLabel, system::StaticSystemParam},
+    prelude::*,
+};
 
+use super::{
+    collision_report_strategy::{CollisionInformation, CollisionReportStrategy},
+    response::{CollisionResponse, RunningResponse},
+};
 use crate::{
-    core::response::CollisionResponse,
-    ecs_core::{components::HitboxLayer, CollisionDetectionSet, LayerGroup},
+    collider::{Collider, ColliderPenetration},
+    components::{collider_isometry, HitboxShape},
+    error::{BumpError, BumpErrors},
+    spatial_index::{query::SpatialIndexQuery, SpatialIndexColliderGroup},
+    spatial_query::{
+        filter::{HitboxFilterSystemParam, HitboxParam, SystemSpatialQueryFilter},
+        SpatialQuery,
+    },
+    ColliderGroup, CollisionDetectionSet, CollisionImplementation, ImplementationPlugin,
 };
+use std::marker::PhantomData;
 
-use super::collision_report_strategy::CollisionReportStrategy;
+/// Velocity hitboxes are moved by their [`Velocity`],
+/// resolving collisions on the way with [`VelocityImplementation::Response`].
+pub trait VelocityImplementation<Group: ColliderGroup<Implementation = Self>>:
+    CollisionImplementation<Group>
+{
+    type ReportStrategy: CollisionReportStrategy<Group>;
+    /// Response is created anew for every hitbox on every tick.
+    type Response: CollisionResponse + Default;
+    /// How hitbox is pushed out of the hurtboxes it overlaps before moving,
+    /// [`Depenetrate`] or [`NoDepenetration`] if the hitbox can't find out how deep it overlaps.
+    type Depenetration: Depenetration<Group>;
+}
 
-pub trait VelocityGroup: LayerGroup {
+pub trait VelocityGroup:
+    SpatialIndexColliderGroup<Implementation: VelocityImplementation<Self>>
+{
     type ReportStrategy: CollisionReportStrategy<Self>;
-    type Response: CollisionResponse;
+    type Response: CollisionResponse + Default;
+    type Depenetration: Depenetration<Self>;
+}
+
+impl<Group: SpatialIndexColliderGroup<Implementation: VelocityImplementation<Group>>> VelocityGroup
+    for Group
+{
+    type ReportStrategy = <Group::Implementation as VelocityImplementation<Group>>::ReportStrategy;
+    type Response = <Group::Implementation as VelocityImplementation<Group>>::Response;
+    type Depenetration = <Group::Implementation as VelocityImplementation<Group>>::Depenetration;
+}
+
+/// Ready made [`VelocityImplementation`] of any group,
+/// that can be added to the app with the [`WithColliderGroup`](crate::WithColliderGroup).
+#[allow(clippy::type_complexity)]
+pub struct VelocityPlugin<ReportStrategy, Response, Depenetration = Depenetrate>(
+    PhantomData<fn() -> (ReportStrategy, Response, Depenetration)>,
+);
+
+impl<R, Response, D> Default for VelocityPlugin<R, Response, D> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<Group, R, Response, D> CollisionImplementation<Group> for VelocityPlugin<R, Response, D>
+where
+    Group: ColliderGroup<Implementation = Self>,
+    R: 'static,
+    Response: 'static,
+    D: 'static,
+{
+}
+
+impl<Group, R, Response, D> VelocityImplementation<Group> for VelocityPlugin<R, Response, D>
+where
+    Group: ColliderGroup<Implementation = Self>,
+    R: CollisionReportStrategy<Group> + 'static,
+    Response: CollisionResponse + Default + 'static,
+    D: Depenetration<Group> + 'static,
+{
+    type ReportStrategy = R;
+    type Response = Response;
+    type Depenetration = D;
+}
+
+impl<Group, R, Response, D> ImplementationPlugin<Group> for VelocityPlugin<R, Response, D>
+where
+    Group: VelocityGroup<Implementation = Self>,
+    R: 'static,
+    Response: 'static,
+    D: 'static,
+{
+    fn build(&self, app: &mut App, schedule: InternedScheduleLabel) {
+        register_velocity_group::<Group>(app, schedule);
+    }
 }
 
-pub(super) fn register_velocity_group<T: VelocityGroup>(app: &mut App) {
-    T::ReportStrategy::register(app);
+pub(super) fn register_velocity_group<Group: VelocityGroup>(
+    app: &mut App,
+    schedule: InternedScheduleLabel,
+) {
+    Group::ReportStrategy::register(app, schedule);
+
+    crate::configure_collision_detection_sets(app, schedule);
 
     app.add_systems(
-        super::COLLISION_DETECTION_SCHEDULE,
-        collide_velocity_group::<T>.in_set(CollisionDetectionSet::Colliding),
+        schedule,
+        collide_velocity_group::<Group>.in_set(CollisionDetectionSet::Colliding),
     );
 
-    app.observe(add_velocity::<T>);
+    app.add_observer(add_velocity::<Group>);
 }
 
+/// Offset per second that hitbox desires to move.
 #[derive(Reflect, Component, Copy, Clone, Default, PartialEq, Debug, Deref, DerefMut)]
 pub struct Velocity(pub Vec2);
 
-fn add_velocity<Layer: VelocityGroup>(
-    trigger: Trigger<OnAdd, HitboxLayer<Layer>>,
+fn add_velocity<Group: VelocityGroup>(
+    trigger: Trigger<OnAdd, HitboxShape<Group>>,
     mut commands: Commands,
 ) {
     commands
         .entity(trigger.entity())
-        .add(|entity: Entity, world: &mut World| {
-            let mut entity_mut = world.entity_mut(entity);
+        .queue(|entity: Entity, world: &mut World| {
+            let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+                return;
+            };
             if !entity_mut.contains::<Velocity>() {
                 entity_mut.insert(Velocity::default());
             }
         });
 }
 
-fn collide_velocity_group<T: VelocityGroup>() {
-    todo!()
+/// Pushes velocity hitbox out of the hurtboxes it starts inside of, before it moves.
+pub trait Depenetration<Group: ColliderGroup> {
+    /// Returns the offset `hitbox` is pushed by.
+    fn depenetrate<'a, 'p: 'a, SQ: SpatialQuery<Group>>(
+        spatial_query: &'a mut SQ,
+        hitbox: Collider<'a, Group::Hitbox>,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Vec2;
+}
+
+/// Hitbox is pushed out of the deepest overlap `ITERATIONS` times, see [`depenetrate`].
+/// Requires the hitbox to be [`ColliderPenetration`] with the hurtbox.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Depenetrate<const ITERATIONS: usize = 4>;
+
+impl<Group: ColliderGroup, const ITERATIONS: usize> Depenetration<Group> for Depenetrate<ITERATIONS>
+where
+    Group::Hitbox: ColliderPenetration<Group::Hurtbox>,
+{
+    fn depenetrate<'a, 'p: 'a, SQ: SpatialQuery<Group>>(
+        spatial_query: &'a mut SQ,
+        hitbox: Collider<'a, Group::Hitbox>,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Vec2 {
+        depenetrate(spatial_query, hitbox, hitbox_filter, ITERATIONS)
+    }
+}
+
+/// Hitbox is left inside of the hurtboxes it overlaps, it can only move out of them.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoDepenetration;
+
+impl<Group: ColliderGroup> Depenetration<Group> for NoDepenetration {
+    fn depenetrate<'a, 'p: 'a, SQ: SpatialQuery<Group>>(
+        _: &'a mut SQ,
+        _: Collider<'a, Group::Hitbox>,
+        _: HitboxParam<'p, Group>,
+    ) -> Vec2 {
+        Vec2::ZERO
+    }
 }
 
+/// Overlaps shallower than that are considered touching and are not resolved.
+const DEPENETRATION_EPSILON: f32 = 1e-4;
+
+/// Pushes `hitbox` out of the hurtboxes it starts inside of, returns the offset it was pushed by.
+/// On each of the `iterations` hitbox is moved out of the deepest overlap only,
+/// so it may still overlap something if there is not enough iterations.
+pub fn depenetrate<'a, 'p: 'a, Group: ColliderGroup, SQ: SpatialQuery<Group>>(
+    spatial_query: &'a mut SQ,
+    hitbox: Collider<'a, Group::Hitbox>,
+    hitbox_filter: HitboxParam<'p, Group>,
+    iterations: usize,
+) -> Vec2
+where
+    Group::Hitbox: ColliderPenetration<Group::Hurtbox>,
+{
+    let mut offset = Vec2::ZERO;
+
+    for _ in 0..iterations {
+        let moved = Collider {
+            position: hitbox.position + offset,
+            ..hitbox
+        };
+        let Some((deepest, _)) = spatial_query
+            .penetrate(moved, hitbox_filter)
+            .filter(|(penetration, _)| penetration.depth > DEPENETRATION_EPSILON)
+            .max_by(|(a, _), (b, _)| a.depth.total_cmp(&b.depth))
+        else {
+            break;
+        };
+        offset += deepest.translation();
+    }
+
+    offset
+}
+
+type ReportParam<Group> =
+    <<Group as VelocityGroup>::ReportStrategy as CollisionReportStrategy<Group>>::Param;
+
+/// Turns the offset in global space into the offset of the [`Transform`], that is in space of the `parent`.
+fn local_offset(transform_helper: &TransformHelper, parent: Option<&Parent>, offset: Vec2) -> Vec3 {
+    let offset = offset.extend(0.);
+    let Some(parent) = parent else {
+        return offset;
+    };
+    match transform_helper.compute_global_transform(parent.get()) {
+        Ok(parent) => parent.affine().inverse().transform_vector3(offset),
+        // Can't happen, global position of the child was computed through the parent
+        Err(_) => offset,
+    }
+}
+
+/// Moves every hitbox of the group by its [`Velocity`] multiplied by delta of [`Time`],
+/// so it works both in [`Update`] and [`FixedUpdate`].
+/// Hitbox is never collided with itself, when it's a hurtbox of the group too.
+#[allow(clippy::too_many_arguments)]
+fn collide_velocity_group<Group: VelocityGroup>(
+    spatial_query: SpatialIndexQuery<Group>,
+    hitboxes: Query<(Entity, &Velocity, &HitboxShape<Group>, Option<&Parent>)>,
+    hitbox_filter: StaticSystemParam<HitboxFilterSystemParam<Group>>,
+    mut transforms: ParamSet<(TransformHelper, Query<&mut Transform>)>,
+    time: Res<Time>,
+    errors: Res<BumpErrors>,
+    mut collisions: Local<Vec<CollisionInformation>>,
+    mut offsets: Local<Vec<(Entity, Vec3)>>,
+
+    mut report_param: StaticSystemParam<ReportParam<Group>>,
+) {
+    let delta = time.delta_secs();
+
+    for (hitbox_entity, velocity, shape, parent) in hitboxes.iter() {
+        let position = match transforms.p0().compute_global_transform(hitbox_entity) {
+            Ok(position) => position,
+            Err(cause) => {
+                errors.report(BumpError::ColliderPosition {
+                    entity: hitbox_entity,
+                    cause,
+                });
+                continue;
+            }
+        };
+        let mut hitbox = Collider::from_isometry(&**shape, collider_isometry::<Group>(&position));
+
+        let hitbox_param = Group::Filter::hitbox_filter_param(hitbox_entity, &hitbox_filter);
+        let mut spatial_query = spatial_query.excluding(hitbox_entity);
+
+        let depenetration =
+            Group::Depenetration::depenetrate(&mut spatial_query, hitbox, hitbox_param);
+        hitbox.position += depenetration;
+
+        let Ok((offset_dir, offset_len)) = Dir2::new_and_length(velocity.0 * delta) else {
+            if depenetration != Vec2::ZERO {
+                let local = local_offset(&transforms.p0(), parent, depenetration);
+                offsets.push((hitbox_entity, local));
+            }
+            continue;
+        };
+
+        let offset = Group::Response::default()
+            .respond(
+                &mut spatial_query,
+                hitbox,
+                offset_dir,
+                offset_len,
+                hitbox_param,
+            )
+            .foreach(|x| collisions.push(CollisionInformation::from_response(hitbox_entity, x)));
+
+        Group::ReportStrategy::report_collisions(collisions.drain(..), &mut report_param);
+
+        let local = local_offset(&transforms.p0(), parent, depenetration + offset);
+        offsets.push((hitbox_entity, local));
+    }
+
+    let mut transforms = transforms.p1();
+    for (entity, offset) in offsets.drain(..) {
+        if let Ok(mut transform) = transforms.get_mut(entity) {
+            transform.translation += offset;
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::{app::RunFixedMainLoopSystem, prelude::*};
+
+/// Smooths the movement of the entity that is moved in [`FixedUpdate`].
+///
+/// Between the fixed steps translation and rotation are interpolated from the start to the end of the last step
+/// by the [`Time<Fixed>::overstep_fraction`]. Before every step [`Transform`] is set back to the end of the last one,
+/// so collision detection running in the fixed steps only sees the real positions.
+/// Changes of the [`Transform`] made outside of the fixed steps are overwritten, call [`reset`](Self::reset) to teleport.
+#[derive(Component, Debug, Clone, Copy, Default)]
+#[require(Transform)]
+pub struct TransformInterpolation {
+    start: Option<Transform>,
+    end: Option<Transform>,
+}
+
+impl TransformInterpolation {
+    /// Forgets the last fixed step, so the current [`Transform`] is used as is.
+    pub fn reset(&mut self) {
+        *self = default();
+    }
+}
+
+/// Adds the systems of the [`TransformInterpolation`].
+#[derive(Debug, Default)]
+pub struct TransformInterpolationPlugin;
+
+impl Plugin for TransformInterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, start_fixed_step)
+            .add_systems(FixedLast, end_fixed_step)
+            .add_systems(
+                RunFixedMainLoop,
+                interpolate_transforms.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+            );
+    }
+}
+
+fn start_fixed_step(mut entities: Query<(&mut Transform, &mut TransformInterpolation)>) {
+    for (mut transform, mut interpolation) in entities.iter_mut() {
+        if let Some(end) = interpolation.end {
+            transform.translation = end.translation;
+            transform.rotation = end.rotation;
+        }
+        interpolation.start = Some(*transform);
+    }
+}
+
+fn end_fixed_step(mut entities: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in entities.iter_mut() {
+        interpolation.end = Some(*transform);
+    }
+}
+
+fn interpolate_transforms(
+    mut entities: Query<(&mut Transform, &TransformInterpolation)>,
+    time: Res<Time<Fixed>>,
+) {
+    let fraction = time.overstep_fraction();
+    for (mut transform, interpolation) in entities.iter_mut() {
+        let (Some(start), Some(end)) = (interpolation.start, interpolation.end) else {
+            continue;
+        };
+        transform.translation = start.translation.lerp(end.translation, fraction);
+        transform.rotation = start.rotation.slerp(end.rotation, fraction);
+    }
+}
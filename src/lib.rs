@@ -1,18 +1,69 @@
 use bevy::{
-    app::{App, Plugin},
-    prelude::SystemSet,
+    app::{App, Plugin, Update},
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    prelude::{IntoSystemSetConfigs, SystemSet},
+    transform::TransformSystem,
 };
 use collider::ColliderInteraction;
+use spatial_index::broad_phase::BroadPhase;
 use spatial_query::filter::SystemSpatialQueryFilter;
 
 pub mod bounded;
 pub mod collider;
 pub mod components;
+pub mod error;
+pub mod interpolation;
 pub mod spatial_query;
 pub mod spatial_index;
-// pub mod implementations;
+#[cfg(feature = "implementations")]
+pub mod implementations;
 
-pub mod prelude {}
+pub mod prelude {
+    pub use crate::{
+        bounded::{
+            Bounded, ConvexShape, ConvexShapeError, EdgeChain, EdgeChainError, Point,
+            RoundedRectangle,
+        },
+        collider::{
+            AnyShape, Collider, ColliderDistance, ColliderInteraction, ColliderParts,
+            ColliderPenetration, Compound, Distance, Penetration,
+        },
+        components::{HitboxShape, HurtboxShape},
+        error::{BumpError, BumpErrorPlugin, BumpErrors, ErrorPolicy},
+        interpolation::{TransformInterpolation, TransformInterpolationPlugin},
+        spatial_index::{
+            broad_phase::{AabbTree, BroadPhase, ChunkGrid, HierarchicalGrid, LooseQuadtree},
+            components::StaticHurtbox,
+            pairs::SpatialIndexPairs,
+            query::{SpatialIndexQuery, SpatialIndexQueryAllowDuplication},
+            SpatialIndexDebugPlugin, SpatialIndexPlugin,
+        },
+        spatial_query::{
+            filter::{
+                layer::{CollisionLayer, HitboxLayer, HurtboxLayer, Layer, LayeredImplementation},
+                monitorable::{HurtboxMonitorable, Monitorable},
+                monitoring::{HitboxMonitoring, Monitoring},
+                SpatialQueryFilter, SystemSpatialQueryFilter,
+            },
+            RayHit, SpatialQuery,
+        },
+        ColliderGroup, CollisionDetectionPlugin, CollisionDetectionSet, CollisionImplementation,
+        ImplementationPlugin, WithColliderGroup,
+    };
+
+    #[cfg(feature = "implementations")]
+    pub use crate::implementations::{
+        collision_report_strategy::{
+            Collided, CollisionInformation, CollisionReportStrategy, ExtendHitboxComponent,
+            ExtendHurtboxComponent, HitboxCollided, HitboxCollisions, HurtboxCollided,
+            HurtboxCollisions, ObserveCollision, SendCollisionEvent,
+        },
+        response::{Bounce, CollisionResponse, Ignore, LimitedBounce, Pass, Slide, Touch},
+        BumpAppExtension, Depenetrate, Depenetration, NoDepenetration, ScannerGroup,
+        ScannerImplementation, ScannerPlugin, Velocity, VelocityGroup, VelocityImplementation,
+        VelocityPlugin,
+    };
+}
 
 /// Trait allows for easier to read generic code
 pub trait ColliderGroup: Send + Sync + Sized + 'static {
@@ -24,22 +75,128 @@ pub trait ColliderGroup: Send + Sync + Sized + 'static {
     type Implementation: CollisionImplementation<Self>;
 
     type Filter: SystemSpatialQueryFilter<Self>;
+
+    /// Broad phase used by the [`SpatialIndex`](spatial_index::spatial_index::SpatialIndex) of the group.
+    /// [`ChunkGrid`](spatial_index::broad_phase::ChunkGrid) suits colliders of similar size,
+    /// [`HierarchicalGrid`](spatial_index::broad_phase::HierarchicalGrid), [`AabbTree`](spatial_index::broad_phase::AabbTree)
+    /// and [`LooseQuadtree`](spatial_index::broad_phase::LooseQuadtree) handle colliders of very different sizes.
+    type BroadPhase: BroadPhase;
+
+    /// If true, colliders of the group are rotated by the rotation of their [`GlobalTransform`](bevy::prelude::GlobalTransform)
+    /// around the z axis. Otherwise only translation is used and colliders are always axis aligned.
+    /// Scale is never applied to the colliders.
+    const USE_ROTATION: bool = false;
 }
 
 pub trait CollisionImplementation<Group: ColliderGroup<Implementation = Self>>: Send + Sync + 'static {
 }
 
-pub struct WithColliderGroup<Group: ColliderGroup>(pub Group::Implementation);
+/// Implementation of the `Group` that can be added to the app with the [`WithColliderGroup`].
+/// `VelocityPlugin` and `ScannerPlugin` of the `implementations` are ready made ones.
+///
+/// Every [`Plugin`] is an implementation plugin too, it's built as is and chooses its schedules itself.
+pub trait ImplementationPlugin<Group: ColliderGroup>: Send + Sync + 'static {
+    /// Adds the systems of the implementation to the `schedule`.
+    fn build(&self, app: &mut App, schedule: InternedScheduleLabel);
+}
+
+impl<Group: ColliderGroup, P: Plugin> ImplementationPlugin<Group> for P {
+    fn build(&self, app: &mut App, _schedule: InternedScheduleLabel) {
+        Plugin::build(self, app);
+    }
+}
+
+/// Adds the [`ColliderGroup::Implementation`] to the app, running it in the `schedule`, [`Update`] by default.
+pub struct WithColliderGroup<Group: ColliderGroup> {
+    pub implementation: Group::Implementation,
+    pub schedule: InternedScheduleLabel,
+}
+
+impl<Group: ColliderGroup> WithColliderGroup<Group> {
+    /// Runs the `implementation` in [`Update`], replaces the `WithColliderGroup(implementation)` constructor.
+    pub fn new(implementation: Group::Implementation) -> Self {
+        Self {
+            implementation,
+            schedule: Update.intern(),
+        }
+    }
+
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = schedule.intern();
+        self
+    }
+}
 
-impl<Group: ColliderGroup<Implementation: Plugin>> Plugin for WithColliderGroup<Group> {
+impl<Group: ColliderGroup<Implementation: ImplementationPlugin<Group>>> Plugin
+    for WithColliderGroup<Group>
+{
     fn build(&self, app: &mut App) {
-        Group::Implementation::build(&self.0, app);
+        configure_collision_detection_sets(app, self.schedule);
+        self.implementation.build(app, self.schedule);
     }
 }
 
+/// Sets the collision detection runs in, chained in this order by the [`CollisionDetectionPlugin`].
+///
+/// Positions of the colliders are computed from their [`Transform`](bevy::prelude::Transform)s
+/// and the transforms of their ancestors, so the sets don't depend on the
+/// [`GlobalTransform`](bevy::prelude::GlobalTransform) being propagated.
+/// Systems that move colliders should run before [`CollisionDetectionSet::First`],
+/// systems that read the collisions should run after [`CollisionDetectionSet::Last`].
 #[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CollisionDetectionSet {
+    /// Hurtboxes are updated in the spatial index, collisions of the last run are cleared.
     First,
+    /// Hitboxes are moved and their collisions are resolved.
     Colliding,
+    /// All the collisions of the run are reported.
     Last,
 }
+
+/// Chains the [`CollisionDetectionSet`]s in the `schedule` and runs them before
+/// [`TransformSystem::TransformPropagate`]. In [`Update`] or [`FixedUpdate`](bevy::app::FixedUpdate),
+/// which run before the propagation anyway, [`GlobalTransform`](bevy::prelude::GlobalTransform)s
+/// of the moved hitboxes are up to date at the end of the frame.
+/// In [`PostUpdate`](bevy::app::PostUpdate) the sets run right before the propagation in the same schedule.
+///
+/// [`SpatialIndexPlugin`](spatial_index::SpatialIndexPlugin), [`WithColliderGroup`] and the group registrations
+/// configure the sets in their schedules themselves.
+#[derive(Debug, Clone)]
+pub struct CollisionDetectionPlugin {
+    pub schedule: InternedScheduleLabel,
+}
+
+impl CollisionDetectionPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for CollisionDetectionPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for CollisionDetectionPlugin {
+    fn build(&self, app: &mut App) {
+        configure_collision_detection_sets(app, self.schedule);
+    }
+}
+
+/// Chains the [`CollisionDetectionSet`]s in the `schedule`, see [`CollisionDetectionPlugin`].
+/// The sets can be configured in the same schedule more than once.
+pub fn configure_collision_detection_sets(app: &mut App, schedule: impl ScheduleLabel) {
+    app.configure_sets(
+        schedule,
+        (
+            CollisionDetectionSet::First,
+            CollisionDetectionSet::Colliding,
+            CollisionDetectionSet::Last,
+        )
+            .chain()
+            .before(TransformSystem::TransformPropagate),
+    );
+}
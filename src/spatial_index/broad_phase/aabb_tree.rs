@@ -0,0 +1,354 @@
+use super::BroadPhase;
+use bevy::{
+    ecs::entity::{EntityHashMap, EntityMapper, MapEntities},
+    math::bounding::{Aabb2d, AabbCast2d, BoundingVolume, IntersectsVolume, RayCast2d},
+    prelude::*,
+};
+
+/// Dynamic bounding volume hierarchy, balanced binary tree of [`Aabb2d`]s.
+///
+/// Entities are stored with their [`Aabb2d`] grown by the margin,
+/// so the tree doesn't change while entity moves within it.
+/// Handles colliders of very different sizes well.
+#[derive(Debug, Clone)]
+pub struct AabbTree {
+    nodes: Vec<Node>,
+    free_nodes: Vec<usize>,
+    root: Option<usize>,
+    leaves: EntityHashMap<usize>,
+    margin: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    aabb: Aabb2d,
+    parent: Option<usize>,
+    /// Leaves have height 0
+    height: u32,
+    kind: NodeKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NodeKind {
+    Leaf(Entity),
+    Branch(usize, usize),
+}
+
+impl Default for AabbTree {
+    fn default() -> Self {
+        Self::new(Self::MARGIN_DEFAULT)
+    }
+}
+
+impl MapEntities for AabbTree {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        // Free nodes may still look like leaves, so only the nodes from `leaves` are mapped
+        let leaves = std::mem::take(&mut self.leaves);
+        for (_, index) in leaves {
+            if let NodeKind::Leaf(entity) = &mut self.nodes[index].kind {
+                *entity = entity_mapper.map_entity(*entity);
+                self.leaves.insert(*entity, index);
+            }
+        }
+    }
+}
+
+impl AabbTree {
+    pub const MARGIN_DEFAULT: f32 = 2.;
+
+    /// Creates a new tree.
+    /// # Arguments
+    /// * `margin` - [`Aabb2d`] of every entity is grown by that amount on each side.
+    ///   Entity that moves less than that doesn't need to be moved in the tree.
+    pub fn new(margin: f32) -> Self {
+        Self {
+            nodes: Vec::new(),
+            free_nodes: Vec::new(),
+            root: None,
+            leaves: EntityHashMap::default(),
+            margin,
+        }
+    }
+
+    /// The amount [`Aabb2d`] of every entity is grown by.
+    #[inline]
+    pub fn margin(&self) -> f32 {
+        self.margin
+    }
+
+    /// Height of the tree, zero if it has one entity or none.
+    pub fn height(&self) -> u32 {
+        self.root.map_or(0, |root| self.nodes[root].height)
+    }
+
+    fn allocate(&mut self, node: Node) -> usize {
+        match self.free_nodes.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn replace_child(&mut self, parent: Option<usize>, old: usize, new: usize) {
+        let Some(parent) = parent else {
+            self.root = Some(new);
+            return;
+        };
+        if let NodeKind::Branch(first, second) = &mut self.nodes[parent].kind {
+            if *first == old {
+                *first = new;
+            } else {
+                *second = new;
+            }
+        }
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        let Some(root) = self.root else {
+            self.root = Some(leaf);
+            self.nodes[leaf].parent = None;
+            return;
+        };
+
+        // Descend to the sibling, merging with which increases the perimeter of the tree the least
+        let leaf_aabb = self.nodes[leaf].aabb;
+        let mut index = root;
+        while let NodeKind::Branch(first, second) = self.nodes[index].kind {
+            let node_perimeter = perimeter(&self.nodes[index].aabb);
+            let merged_perimeter = perimeter(&self.nodes[index].aabb.merge(&leaf_aabb));
+
+            // Cost of creating a new parent for this node and the new leaf
+            let cost = 2. * merged_perimeter;
+            // Cost of pushing the leaf further down the tree
+            let inheritance_cost = 2. * (merged_perimeter - node_perimeter);
+            let child_cost = |child: usize| {
+                let node = &self.nodes[child];
+                let merged = perimeter(&node.aabb.merge(&leaf_aabb));
+                match node.kind {
+                    NodeKind::Leaf(_) => merged + inheritance_cost,
+                    NodeKind::Branch(..) => merged - perimeter(&node.aabb) + inheritance_cost,
+                }
+            };
+            let (first_cost, second_cost) = (child_cost(first), child_cost(second));
+
+            if cost < first_cost && cost < second_cost {
+                break;
+            }
+            index = if first_cost < second_cost {
+                first
+            } else {
+                second
+            };
+        }
+
+        let sibling = index;
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate(Node {
+            aabb: self.nodes[sibling].aabb.merge(&leaf_aabb),
+            parent: old_parent,
+            height: self.nodes[sibling].height + 1,
+            kind: NodeKind::Branch(sibling, leaf),
+        });
+        self.replace_child(old_parent, sibling, new_parent);
+        self.nodes[sibling].parent = Some(new_parent);
+        self.nodes[leaf].parent = Some(new_parent);
+
+        self.refit(Some(new_parent));
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        let Some(parent) = self.nodes[leaf].parent else {
+            self.root = None;
+            return;
+        };
+        let NodeKind::Branch(first, second) = self.nodes[parent].kind else {
+            unreachable!("parent is always a branch");
+        };
+        let sibling = if first == leaf { second } else { first };
+        let grandparent = self.nodes[parent].parent;
+
+        self.replace_child(grandparent, parent, sibling);
+        self.nodes[sibling].parent = grandparent;
+        self.free_nodes.push(parent);
+
+        self.refit(grandparent);
+    }
+
+    /// Balances the nodes from `index` up to the root, recalculating their heights and [`Aabb2d`]s.
+    fn refit(&mut self, mut index: Option<usize>) {
+        while let Some(current) = index {
+            let current = self.balance(current);
+            if let NodeKind::Branch(first, second) = self.nodes[current].kind {
+                self.nodes[current].height =
+                    1 + self.nodes[first].height.max(self.nodes[second].height);
+                self.nodes[current].aabb = self.nodes[first].aabb.merge(&self.nodes[second].aabb);
+            }
+            index = self.nodes[current].parent;
+        }
+    }
+
+    /// Rotates the taller child of `index` up, if heights of the children differ by more than one.
+    /// Returns the node that took the place of `index`.
+    fn balance(&mut self, index: usize) -> usize {
+        let NodeKind::Branch(first, second) = self.nodes[index].kind else {
+            return index;
+        };
+        let balance = self.nodes[second].height as i64 - self.nodes[first].height as i64;
+        if balance > 1 {
+            self.rotate(index, second, first)
+        } else if balance < -1 {
+            self.rotate(index, first, second)
+        } else {
+            index
+        }
+    }
+
+    /// Makes `high` the parent of `index`, `low` is the other child of `index`.
+    fn rotate(&mut self, index: usize, high: usize, low: usize) -> usize {
+        let NodeKind::Branch(first, second) = self.nodes[high].kind else {
+            return index;
+        };
+
+        let parent = self.nodes[index].parent;
+        self.replace_child(parent, index, high);
+        self.nodes[high].parent = parent;
+        self.nodes[index].parent = Some(high);
+
+        // Taller grandchild stays under `high`, the other one goes to `index`
+        let (kept, moved) = if self.nodes[first].height > self.nodes[second].height {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        self.nodes[high].kind = NodeKind::Branch(index, kept);
+        self.nodes[index].kind = NodeKind::Branch(low, moved);
+        self.nodes[moved].parent = Some(index);
+
+        self.nodes[index].aabb = self.nodes[low].aabb.merge(&self.nodes[moved].aabb);
+        self.nodes[index].height = 1 + self.nodes[low].height.max(self.nodes[moved].height);
+        self.nodes[high].aabb = self.nodes[index].aabb.merge(&self.nodes[kept].aabb);
+        self.nodes[high].height = 1 + self.nodes[index].height.max(self.nodes[kept].height);
+
+        high
+    }
+
+    /// Visits the nodes for which `enter` returns distance, collects the leaves sorted by it.
+    fn collect_sorted(&self, enter: impl Fn(&Aabb2d) -> Option<f32>) -> Vec<(f32, Entity)> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let Some(distance) = enter(&node.aabb) else {
+                continue;
+            };
+            match node.kind {
+                NodeKind::Leaf(entity) => found.push((distance, entity)),
+                NodeKind::Branch(first, second) => stack.extend([first, second]),
+            }
+        }
+
+        found.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        found
+    }
+}
+
+fn perimeter(aabb: &Aabb2d) -> f32 {
+    let size = aabb.max - aabb.min;
+    2. * (size.x + size.y)
+}
+
+impl BroadPhase for AabbTree {
+    fn insert(&mut self, entity: Entity, aabb: Aabb2d) {
+        if let Some(leaf) = self.leaves.remove(&entity) {
+            self.remove_leaf(leaf);
+            self.free_nodes.push(leaf);
+        }
+
+        let leaf = self.allocate(Node {
+            aabb: aabb.grow(Vec2::splat(self.margin)),
+            parent: None,
+            height: 0,
+            kind: NodeKind::Leaf(entity),
+        });
+        self.insert_leaf(leaf);
+        self.leaves.insert(entity, leaf);
+    }
+
+    fn update(&mut self, entity: Entity, _old_aabb: Aabb2d, new_aabb: Aabb2d) {
+        let Some(&leaf) = self.leaves.get(&entity) else {
+            self.insert(entity, new_aabb);
+            return;
+        };
+        if self.nodes[leaf].aabb.contains(&new_aabb) {
+            return;
+        }
+
+        self.remove_leaf(leaf);
+        self.nodes[leaf].aabb = new_aabb.grow(Vec2::splat(self.margin));
+        self.nodes[leaf].parent = None;
+        self.insert_leaf(leaf);
+    }
+
+    fn remove(&mut self, entity: Entity, _aabb: Aabb2d) {
+        let Some(leaf) = self.leaves.remove(&entity) else {
+            return;
+        };
+        self.remove_leaf(leaf);
+        self.free_nodes.push(leaf);
+    }
+
+    fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.leaves.keys().copied()
+    }
+
+    fn query_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = Entity> + '_ {
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+
+        std::iter::from_fn(move || {
+            while let Some(index) = stack.pop() {
+                let node = &self.nodes[index];
+                if !node.aabb.intersects(&aabb) {
+                    continue;
+                }
+                match node.kind {
+                    NodeKind::Leaf(entity) => return Some(entity),
+                    NodeKind::Branch(first, second) => stack.extend([first, second]),
+                }
+            }
+            None
+        })
+    }
+
+    fn query_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        let ray = RayCast2d::new(origin, direction, max_distance);
+        self.collect_sorted(|aabb| ray.aabb_intersection_at(aabb))
+            .into_iter()
+    }
+
+    fn query_swept_aabb(
+        &self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        let center = aabb.center();
+        let local = Aabb2d {
+            min: aabb.min - center,
+            max: aabb.max - center,
+        };
+        let cast = AabbCast2d::new(local, center, direction, distance);
+        self.collect_sorted(|aabb| cast.aabb_collision_at(*aabb))
+            .into_iter()
+    }
+}
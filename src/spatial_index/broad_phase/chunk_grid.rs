@@ -0,0 +1,456 @@
+use super::BroadPhase;
+use bevy::ecs::entity::{EntityHashMap, MapEntities};
+use bevy::math::bounding::Aabb2d;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+pub type Chunk = Vec<Entity>;
+
+/// Uniform grid of square chunks, every entity is stored in all the chunks its [`Aabb2d`] intersects.
+/// Works best when colliders are of similar size.
+///
+/// Empty chunks are removed, moving entity only touches the chunks it entered or left.
+#[derive(Debug, Clone, Reflect)]
+pub struct ChunkGrid {
+    chunks: HashMap<IVec2, Chunk>,
+    /// Chunks every entity is stored in
+    #[reflect(ignore)]
+    entities: EntityHashMap<Membership>,
+    pixels_per_chunk: f32,
+}
+
+/// Rectangle of chunks, both corners are included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ChunkRange {
+    min: IVec2,
+    max: IVec2,
+}
+
+impl ChunkRange {
+    fn contains(&self, chunk: IVec2) -> bool {
+        chunk.cmpge(self.min).all() && chunk.cmple(self.max).all()
+    }
+
+    /// Index of the `chunk` among the chunks of the range, in the order of `iter`.
+    fn offset(&self, chunk: IVec2) -> usize {
+        let width = self.max.x - self.min.x + 1;
+        ((chunk.y - self.min.y) * width + chunk.x - self.min.x) as usize
+    }
+
+    fn iter(self) -> impl Iterator<Item = IVec2> {
+        (self.min.y..=self.max.y)
+            .flat_map(move |y| (self.min.x..=self.max.x).map(move |x| IVec2::new(x, y)))
+    }
+}
+
+/// Chunks the entity is stored in.
+#[derive(Debug, Clone)]
+struct Membership {
+    range: ChunkRange,
+    /// Index of the entity in every chunk of the range, in the order of [`ChunkRange::iter`]
+    slots: Vec<usize>,
+}
+
+impl Default for ChunkGrid {
+    fn default() -> Self {
+        Self::new(Self::PIXELS_PER_CHUNK_DEFAULT)
+    }
+}
+
+impl MapEntities for ChunkGrid {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for (_, chunk) in self.chunks.iter_mut() {
+            for entity in chunk.iter_mut() {
+                *entity = entity_mapper.map_entity(*entity);
+            }
+        }
+        self.entities = std::mem::take(&mut self.entities)
+            .into_iter()
+            .map(|(entity, membership)| (entity_mapper.map_entity(entity), membership))
+            .collect();
+    }
+}
+
+/// Coordinates of the chunk in which the `global` lies in
+fn global_to_chunk(pixels_per_chunk: f32, global: Vec2) -> IVec2 {
+    IVec2 {
+        x: (global.x / pixels_per_chunk).floor() as i32,
+        y: (global.y / pixels_per_chunk).floor() as i32,
+    }
+}
+
+/// Bottom left corner of the chunk
+fn chunk_to_global(pixels_per_chunk: f32, chunk: IVec2) -> Vec2 {
+    Vec2 {
+        x: chunk.x as f32 * pixels_per_chunk,
+        y: chunk.y as f32 * pixels_per_chunk,
+    }
+}
+
+impl ChunkGrid {
+    pub const PIXELS_PER_CHUNK_DEFAULT: f32 = 100.;
+
+    /// Creates a new chunk grid.
+    /// # Arguments
+    /// * `pixels_per_chunk` - Spacial index is divided into chunks of size `pixels_per_chunk`.
+    ///   Every hurtbox, Aabb2d of which is intersected with the chunk, is added to the chunk.
+    ///   This is done to reduce the number of collision checks, only neccessary chunks are iterated.
+    ///   Generally, this should match size of the colliders for best performance,
+    ///   but this really depends on lots of factors.   
+    pub fn new(pixels_per_chunk: f32) -> Self {
+        ChunkGrid {
+            chunks: HashMap::default(),
+            entities: EntityHashMap::default(),
+            pixels_per_chunk,
+        }
+    }
+
+    /// Coordinates of the chunk in which the `global` lies in.
+    #[inline]
+    pub fn global_to_chunk(&self, global: Vec2) -> IVec2 {
+        global_to_chunk(self.pixels_per_chunk, global)
+    }
+
+    /// Bottom left corner of the chunk.
+    #[inline]
+    pub fn chunk_to_global(&self, chunk: IVec2) -> Vec2 {
+        chunk_to_global(self.pixels_per_chunk, chunk)
+    }
+
+    /// The size of the chunk in pixels.
+    #[inline]
+    pub fn pixels_per_chunk(&self) -> f32 {
+        self.pixels_per_chunk
+    }
+
+    /// Number of chunks that store at least one entity.
+    #[inline]
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Iterates over all chunks that intersect with the given `aabb`.
+    pub fn iter_chunks_on_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = &Chunk> {
+        let min = self.global_to_chunk(aabb.min);
+        let max = self.global_to_chunk(aabb.max);
+
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| (x, y)))
+            .filter_map(|(x, y)| self.chunks.get(&IVec2::new(x, y)))
+    }
+
+    /// Iterates over all chunks that the ray passes through, in the order it passes through them.
+    /// Each chunk comes with the distance along the ray at which the ray enters it.
+    pub fn iter_chunks_on_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+    ) -> impl Iterator<Item = (f32, &Chunk)> {
+        RayTraversal::new(self.pixels_per_chunk, origin, direction, max_distance)
+            .filter_map(|(distance, chunk)| Some((distance, self.chunks.get(&chunk)?)))
+    }
+
+    /// Iterates over all chunks that the `aabb` passes through while moving by `distance` in the `direction`,
+    /// in the order it enters them.
+    /// Each chunk comes with the distance along the `direction` at which the `aabb` enters it.
+    ///
+    /// Unlike iterating over chunks on the [`Aabb2d`] merged from the start and the end,
+    /// it doesn't visit the chunks that are far away from the path on the long diagonal movements.
+    pub fn iter_chunks_on_swept_aabb(
+        &self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, &Chunk)> {
+        swept_aabb_chunks(self.pixels_per_chunk, aabb, direction, distance)
+            .into_iter()
+            .filter_map(|(distance, chunk)| Some((distance, self.chunks.get(&chunk)?)))
+    }
+
+    fn chunk_range(&self, aabb: Aabb2d) -> ChunkRange {
+        ChunkRange {
+            min: self.global_to_chunk(aabb.min),
+            max: self.global_to_chunk(aabb.max),
+        }
+    }
+
+    /// Adds the entity to the end of the chunk, returns its index there.
+    fn push_to_chunk(&mut self, chunk: IVec2, entity: Entity) -> usize {
+        let chunk = self.chunks.entry(chunk).or_default();
+        chunk.push(entity);
+        chunk.len() - 1
+    }
+
+    /// Removes the entity at `slot` from the chunk, fixing the slot of the entity that took its place.
+    fn remove_from_chunk(&mut self, chunk_position: IVec2, slot: usize) {
+        let Some(chunk) = self.chunks.get_mut(&chunk_position) else {
+            return;
+        };
+        chunk.swap_remove(slot);
+
+        if let Some(&moved) = chunk.get(slot) {
+            if let Some(membership) = self.entities.get_mut(&moved) {
+                let offset = membership.range.offset(chunk_position);
+                membership.slots[offset] = slot;
+            }
+        }
+        if chunk.is_empty() {
+            self.chunks.remove(&chunk_position);
+        }
+    }
+
+    pub fn add_entity(&mut self, entity: Entity, aabb: Aabb2d) {
+        if self.entities.contains_key(&entity) {
+            self.remove_entity(entity, aabb);
+        }
+
+        let range = self.chunk_range(aabb);
+        let slots = range
+            .iter()
+            .map(|chunk| self.push_to_chunk(chunk, entity))
+            .collect();
+        self.entities.insert(entity, Membership { range, slots });
+    }
+
+    /// Moves the entity, only touching the chunks it entered or left.
+    /// `old_aabb` is not needed, as the chunks of every entity are remembered.
+    pub fn change_entity(&mut self, entity: Entity, _old_aabb: Aabb2d, new_aabb: Aabb2d) {
+        let new_range = self.chunk_range(new_aabb);
+        let Some(old) = self.entities.get(&entity) else {
+            self.add_entity(entity, new_aabb);
+            return;
+        };
+        if old.range == new_range {
+            return;
+        }
+
+        let old = self.entities.remove(&entity).expect("entity is stored");
+        for (chunk, &slot) in old.range.iter().zip(&old.slots) {
+            if !new_range.contains(chunk) {
+                self.remove_from_chunk(chunk, slot);
+            }
+        }
+
+        let slots = new_range
+            .iter()
+            .map(|chunk| {
+                if old.range.contains(chunk) {
+                    old.slots[old.range.offset(chunk)]
+                } else {
+                    self.push_to_chunk(chunk, entity)
+                }
+            })
+            .collect();
+        self.entities.insert(
+            entity,
+            Membership {
+                range: new_range,
+                slots,
+            },
+        );
+    }
+
+    /// Removes the entity from all of its chunks.
+    /// `aabb` is not needed, as the chunks of every entity are remembered.
+    pub fn remove_entity(&mut self, entity: Entity, _aabb: Aabb2d) {
+        let Some(membership) = self.entities.remove(&entity) else {
+            return;
+        };
+        for (chunk, slot) in membership.range.iter().zip(membership.slots) {
+            self.remove_from_chunk(chunk, slot);
+        }
+    }
+}
+
+impl BroadPhase for ChunkGrid {
+    fn insert(&mut self, entity: Entity, aabb: Aabb2d) {
+        self.add_entity(entity, aabb);
+    }
+
+    fn update(&mut self, entity: Entity, old_aabb: Aabb2d, new_aabb: Aabb2d) {
+        self.change_entity(entity, old_aabb, new_aabb);
+    }
+
+    fn remove(&mut self, entity: Entity, aabb: Aabb2d) {
+        self.remove_entity(entity, aabb);
+    }
+
+    fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.keys().copied()
+    }
+
+    fn query_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = Entity> + '_ {
+        self.iter_chunks_on_aabb(aabb)
+            .flat_map(|chunk| chunk.iter().copied())
+    }
+
+    fn query_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        self.iter_chunks_on_ray(origin, direction, max_distance)
+            .flat_map(|(distance, chunk)| chunk.iter().map(move |&entity| (distance, entity)))
+    }
+
+    fn query_swept_aabb(
+        &self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        self.iter_chunks_on_swept_aabb(aabb, direction, distance)
+            .flat_map(|(distance, chunk)| chunk.iter().map(move |&entity| (distance, entity)))
+    }
+}
+
+/// Interval of time in `0..=max_time`, during which the range from `min` to `max`,
+/// moving with `velocity`, overlaps the range from `low` to `high`.
+fn overlap_interval(
+    min: f32,
+    max: f32,
+    velocity: f32,
+    low: f32,
+    high: f32,
+    max_time: f32,
+) -> Option<(f32, f32)> {
+    let (start, end) = if velocity == 0. {
+        if max < low || min > high {
+            return None;
+        }
+        (0., max_time)
+    } else {
+        let enter = (low - max) / velocity;
+        let exit = (high - min) / velocity;
+        (enter.min(exit).max(0.), enter.max(exit).min(max_time))
+    };
+    (start <= end).then_some((start, end))
+}
+
+/// Chunks that the moving `aabb` passes through, sorted by the distance at which it enters them.
+/// Chunks are found column by column, rows of the column are the ones
+/// the `aabb` passes through while it overlaps the column.
+fn swept_aabb_chunks(
+    pixels_per_chunk: f32,
+    aabb: Aabb2d,
+    direction: Dir2,
+    distance: f32,
+) -> Vec<(f32, IVec2)> {
+    let offset = direction * distance;
+    let first_column = global_to_chunk(pixels_per_chunk, aabb.min.min(aabb.min + offset)).x;
+    let last_column = global_to_chunk(pixels_per_chunk, aabb.max.max(aabb.max + offset)).x;
+
+    let mut chunks = Vec::new();
+    for x in first_column..=last_column {
+        let column_min = x as f32 * pixels_per_chunk;
+        let Some((column_enter, column_exit)) = overlap_interval(
+            aabb.min.x,
+            aabb.max.x,
+            direction.x,
+            column_min,
+            column_min + pixels_per_chunk,
+            distance,
+        ) else {
+            continue;
+        };
+
+        let (enter_y, exit_y) = (direction.y * column_enter, direction.y * column_exit);
+        let min_y = aabb.min.y + enter_y.min(exit_y);
+        let max_y = aabb.max.y + enter_y.max(exit_y);
+        let first_row = global_to_chunk(pixels_per_chunk, Vec2::new(0., min_y)).y;
+        let last_row = global_to_chunk(pixels_per_chunk, Vec2::new(0., max_y)).y;
+
+        for y in first_row..=last_row {
+            let row_min = y as f32 * pixels_per_chunk;
+            let row_enter = overlap_interval(
+                aabb.min.y,
+                aabb.max.y,
+                direction.y,
+                row_min,
+                row_min + pixels_per_chunk,
+                distance,
+            )
+            .map_or(column_enter, |(row_enter, _)| row_enter);
+
+            chunks.push((row_enter.clamp(column_enter, column_exit), IVec2::new(x, y)));
+        }
+    }
+
+    chunks.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    chunks
+}
+
+/// Grid DDA, walks the chunks along the ray one by one.
+struct RayTraversal {
+    chunk: IVec2,
+    step: IVec2,
+    /// Distance at which the ray enters the current chunk
+    distance: f32,
+    /// Distance at which the ray crosses the next chunk border on each axis
+    next_border: Vec2,
+    /// Distance between two chunk borders on each axis
+    border_delta: Vec2,
+    max_distance: f32,
+}
+
+impl RayTraversal {
+    fn new(pixels_per_chunk: f32, origin: Vec2, direction: Dir2, max_distance: f32) -> Self {
+        let chunk = global_to_chunk(pixels_per_chunk, origin);
+        let corner = chunk_to_global(pixels_per_chunk, chunk);
+
+        let axis = |direction: f32, origin: f32, corner: f32| -> (i32, f32, f32) {
+            if direction > 0. {
+                let border = corner + pixels_per_chunk;
+                (
+                    1,
+                    (border - origin) / direction,
+                    pixels_per_chunk / direction,
+                )
+            } else if direction < 0. {
+                (
+                    -1,
+                    (corner - origin) / direction,
+                    -pixels_per_chunk / direction,
+                )
+            } else {
+                (0, f32::INFINITY, f32::INFINITY)
+            }
+        };
+        let (step_x, next_x, delta_x) = axis(direction.x, origin.x, corner.x);
+        let (step_y, next_y, delta_y) = axis(direction.y, origin.y, corner.y);
+
+        Self {
+            chunk,
+            step: IVec2::new(step_x, step_y),
+            distance: 0.,
+            next_border: Vec2::new(next_x, next_y),
+            border_delta: Vec2::new(delta_x, delta_y),
+            max_distance,
+        }
+    }
+}
+
+impl Iterator for RayTraversal {
+    type Item = (f32, IVec2);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.distance > self.max_distance {
+            return None;
+        }
+        let current = (self.distance, self.chunk);
+
+        if self.next_border.x < self.next_border.y {
+            self.chunk.x += self.step.x;
+            self.distance = self.next_border.x;
+            self.next_border.x += self.border_delta.x;
+        } else {
+            self.chunk.y += self.step.y;
+            self.distance = self.next_border.y;
+            self.next_border.y += self.border_delta.y;
+        }
+
+        Some(current)
+    }
+}
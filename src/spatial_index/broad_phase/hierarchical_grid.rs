@@ -0,0 +1,149 @@
+use super::{BroadPhase, ChunkGrid};
+use bevy::{
+    ecs::entity::{EntityMapper, MapEntities},
+    math::bounding::{Aabb2d, BoundingVolume},
+    prelude::*,
+};
+use std::iter::Peekable;
+
+/// Several [`ChunkGrid`]s of increasing chunk size.
+/// Every entity is stored only in the level, chunks of which are at least as large as its [`Aabb2d`],
+/// so it is in no more than four chunks no matter how large it is.
+/// Queries visit every level.
+///
+/// Handles colliders of very different sizes, for example big static walls together with small moving objects.
+#[derive(Debug, Clone, Reflect)]
+pub struct HierarchicalGrid {
+    /// Sorted from the smallest chunks to the largest ones
+    levels: Vec<ChunkGrid>,
+}
+
+impl Default for HierarchicalGrid {
+    fn default() -> Self {
+        Self::new(Self::PIXELS_PER_CHUNK_DEFAULT, Self::LEVELS_DEFAULT)
+    }
+}
+
+impl MapEntities for HierarchicalGrid {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for level in self.levels.iter_mut() {
+            level.map_entities(entity_mapper);
+        }
+    }
+}
+
+impl HierarchicalGrid {
+    pub const PIXELS_PER_CHUNK_DEFAULT: f32 = 25.;
+    pub const LEVELS_DEFAULT: usize = 5;
+    /// Chunks of every level are that many times larger than the chunks of the previous level.
+    pub const LEVEL_SCALE: f32 = 4.;
+
+    /// Creates a new hierarchical grid.
+    /// # Arguments
+    /// * `pixels_per_chunk` - Size of the chunks of the first level,
+    ///   should be around the size of the smallest colliders.
+    /// * `levels` - Number of levels, size of the chunks of the last level is
+    ///   `pixels_per_chunk * LEVEL_SCALE.powi(levels - 1)`.
+    ///   Colliders larger than that are stored in the last level.
+    pub fn new(pixels_per_chunk: f32, levels: usize) -> Self {
+        assert!(
+            levels > 0,
+            "hierarchical grid should have at least one level"
+        );
+        HierarchicalGrid {
+            levels: (0..levels)
+                .map(|level| {
+                    ChunkGrid::new(pixels_per_chunk * Self::LEVEL_SCALE.powi(level as i32))
+                })
+                .collect(),
+        }
+    }
+
+    /// Levels, sorted from the smallest chunks to the largest ones.
+    #[inline]
+    pub fn levels(&self) -> &[ChunkGrid] {
+        &self.levels
+    }
+
+    /// Index of the level entity with the given `aabb` is stored in.
+    pub fn level_of(&self, aabb: Aabb2d) -> usize {
+        let size = aabb.half_size().max_element() * 2.;
+        self.levels
+            .iter()
+            .position(|level| size <= level.pixels_per_chunk())
+            .unwrap_or(self.levels.len() - 1)
+    }
+}
+
+impl BroadPhase for HierarchicalGrid {
+    fn insert(&mut self, entity: Entity, aabb: Aabb2d) {
+        let level = self.level_of(aabb);
+        self.levels[level].add_entity(entity, aabb);
+    }
+
+    fn update(&mut self, entity: Entity, old_aabb: Aabb2d, new_aabb: Aabb2d) {
+        let (old_level, new_level) = (self.level_of(old_aabb), self.level_of(new_aabb));
+        if old_level == new_level {
+            self.levels[new_level].change_entity(entity, old_aabb, new_aabb);
+        } else {
+            self.levels[old_level].remove_entity(entity, old_aabb);
+            self.levels[new_level].add_entity(entity, new_aabb);
+        }
+    }
+
+    fn remove(&mut self, entity: Entity, aabb: Aabb2d) {
+        let level = self.level_of(aabb);
+        self.levels[level].remove_entity(entity, aabb);
+    }
+
+    fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.levels.iter().flat_map(ChunkGrid::iter_entities)
+    }
+
+    fn query_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = Entity> + '_ {
+        self.levels
+            .iter()
+            .flat_map(move |level| level.query_aabb(aabb))
+    }
+
+    fn query_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        merge_sorted(
+            self.levels
+                .iter()
+                .map(move |level| level.query_ray(origin, direction, max_distance)),
+        )
+    }
+
+    fn query_swept_aabb(
+        &self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        merge_sorted(
+            self.levels
+                .iter()
+                .map(move |level| level.query_swept_aabb(aabb, direction, distance)),
+        )
+    }
+}
+
+/// Merges iterators sorted by distance into one, keeping the order.
+fn merge_sorted<I: Iterator<Item = (f32, Entity)>>(
+    iterators: impl Iterator<Item = I>,
+) -> impl Iterator<Item = (f32, Entity)> {
+    let mut iterators: Vec<Peekable<I>> = iterators.map(Iterator::peekable).collect();
+
+    std::iter::from_fn(move || {
+        let closest = iterators
+            .iter_mut()
+            .filter_map(|iterator| Some((iterator.peek()?.0, iterator)))
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))?;
+        closest.1.next()
+    })
+}
@@ -0,0 +1,267 @@
+use super::BroadPhase;
+use bevy::{
+    ecs::entity::{EntityHashMap, EntityMapper, MapEntities},
+    math::bounding::{Aabb2d, AabbCast2d, BoundingVolume, IntersectsVolume, RayCast2d},
+    prelude::*,
+};
+
+/// Quadtree, every node of which holds the entities that are centered in it
+/// and are not larger than the node.
+/// Nodes are loose: they are queried by their bounds grown by half of the node size on each side,
+/// so every entity is stored in exactly one node.
+///
+/// Entities centered outside of the bounds of the tree, or larger than it, are stored in the root.
+/// Handles colliders of very different sizes well, as long as the most of them are within the bounds.
+#[derive(Debug, Clone)]
+pub struct LooseQuadtree {
+    center: Vec2,
+    half_size: f32,
+    max_depth: u32,
+    /// Root is always the first node, nodes are never removed
+    nodes: Vec<QuadNode>,
+    entities: EntityHashMap<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct QuadNode {
+    children: Option<[usize; 4]>,
+    entities: Vec<Entity>,
+}
+
+/// Node, together with its position in the tree.
+#[derive(Debug, Clone, Copy)]
+struct NodeBounds {
+    index: usize,
+    center: Vec2,
+    half_size: f32,
+    depth: u32,
+}
+
+impl NodeBounds {
+    /// Bounds of the node grown by half of its size on each side.
+    fn loose(&self) -> Aabb2d {
+        Aabb2d::new(self.center, Vec2::splat(self.half_size * 2.))
+    }
+
+    fn quadrant(&self, point: Vec2) -> usize {
+        (point.x >= self.center.x) as usize + 2 * (point.y >= self.center.y) as usize
+    }
+
+    fn child_center(&self, quadrant: usize) -> Vec2 {
+        let offset = self.half_size / 2.;
+        let x = if quadrant & 1 == 0 { -offset } else { offset };
+        let y = if quadrant & 2 == 0 { -offset } else { offset };
+        self.center + Vec2::new(x, y)
+    }
+
+    fn children(&self, children: [usize; 4]) -> impl Iterator<Item = NodeBounds> + '_ {
+        children
+            .into_iter()
+            .enumerate()
+            .map(|(quadrant, index)| NodeBounds {
+                index,
+                center: self.child_center(quadrant),
+                half_size: self.half_size / 2.,
+                depth: self.depth + 1,
+            })
+    }
+}
+
+impl Default for LooseQuadtree {
+    fn default() -> Self {
+        Self::new(Vec2::ZERO, Self::HALF_SIZE_DEFAULT, Self::MAX_DEPTH_DEFAULT)
+    }
+}
+
+impl MapEntities for LooseQuadtree {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.entities.clear();
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            for entity in node.entities.iter_mut() {
+                *entity = entity_mapper.map_entity(*entity);
+                self.entities.insert(*entity, index);
+            }
+        }
+    }
+}
+
+impl LooseQuadtree {
+    pub const HALF_SIZE_DEFAULT: f32 = 10000.;
+    pub const MAX_DEPTH_DEFAULT: u32 = 10;
+
+    /// Creates a new quadtree.
+    /// # Arguments
+    /// * `center` and `half_size` - Bounds of the root node.
+    /// * `max_depth` - Depth of the smallest nodes, root has depth of zero.
+    ///   Size of the smallest nodes should be around the size of the smallest colliders.
+    pub fn new(center: Vec2, half_size: f32, max_depth: u32) -> Self {
+        Self {
+            center,
+            half_size,
+            max_depth,
+            nodes: vec![QuadNode::default()],
+            entities: EntityHashMap::default(),
+        }
+    }
+
+    /// Bounds of the root node.
+    #[inline]
+    pub fn bounds(&self) -> Aabb2d {
+        Aabb2d::new(self.center, Vec2::splat(self.half_size))
+    }
+
+    #[inline]
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    fn root(&self) -> NodeBounds {
+        NodeBounds {
+            index: 0,
+            center: self.center,
+            half_size: self.half_size,
+            depth: 0,
+        }
+    }
+
+    /// Finds the node `aabb` belongs to, creating it if `create` is true.
+    fn find_node(&mut self, aabb: Aabb2d, create: bool) -> Option<usize> {
+        let center = aabb.center();
+        let extent = aabb.half_size().max_element();
+        let mut node = self.root();
+
+        let bounds = self.bounds();
+        if !(center.cmpge(bounds.min).all() && center.cmplt(bounds.max).all()) {
+            return Some(node.index);
+        }
+
+        while node.depth < self.max_depth && extent <= node.half_size / 2. {
+            let quadrant = node.quadrant(center);
+            let children = match self.nodes[node.index].children {
+                Some(children) => children,
+                None if create => {
+                    let first = self.nodes.len();
+                    self.nodes.resize_with(first + 4, QuadNode::default);
+                    let children = [first, first + 1, first + 2, first + 3];
+                    self.nodes[node.index].children = Some(children);
+                    children
+                }
+                None => return None,
+            };
+            node = NodeBounds {
+                index: children[quadrant],
+                center: node.child_center(quadrant),
+                half_size: node.half_size / 2.,
+                depth: node.depth + 1,
+            };
+        }
+
+        Some(node.index)
+    }
+
+    /// Visits the nodes for which `enter` returns distance, collects their entities sorted by it.
+    /// Root is always visited with the distance of zero, as it holds entities outside of the bounds.
+    fn collect_sorted(&self, enter: impl Fn(&Aabb2d) -> Option<f32>) -> Vec<(f32, Entity)> {
+        let mut found = Vec::new();
+        let mut stack = vec![(0., self.root())];
+
+        while let Some((distance, node)) = stack.pop() {
+            let quad_node = &self.nodes[node.index];
+            found.extend(quad_node.entities.iter().map(|&entity| (distance, entity)));
+
+            if let Some(children) = quad_node.children {
+                stack.extend(
+                    node.children(children)
+                        .filter_map(|child| Some((enter(&child.loose())?, child))),
+                );
+            }
+        }
+
+        found.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        found
+    }
+}
+
+impl BroadPhase for LooseQuadtree {
+    fn insert(&mut self, entity: Entity, aabb: Aabb2d) {
+        if let Some(old) = self.entities.remove(&entity) {
+            self.nodes[old].entities.retain(|&stored| stored != entity);
+        }
+
+        let index = self
+            .find_node(aabb, true)
+            .expect("node is created if it doesn't exist");
+        self.nodes[index].entities.push(entity);
+        self.entities.insert(entity, index);
+    }
+
+    fn update(&mut self, entity: Entity, _old_aabb: Aabb2d, new_aabb: Aabb2d) {
+        let old = self.entities.get(&entity).copied();
+        if old.is_some() && old == self.find_node(new_aabb, false) {
+            return;
+        }
+        self.insert(entity, new_aabb);
+    }
+
+    fn remove(&mut self, entity: Entity, _aabb: Aabb2d) {
+        let Some(index) = self.entities.remove(&entity) else {
+            return;
+        };
+        let entities = &mut self.nodes[index].entities;
+        if let Some(position) = entities.iter().position(|&stored| stored == entity) {
+            entities.swap_remove(position);
+        }
+    }
+
+    fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.keys().copied()
+    }
+
+    fn query_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = Entity> + '_ {
+        let mut stack = vec![self.root()];
+        let mut current: std::slice::Iter<Entity> = [].iter();
+
+        std::iter::from_fn(move || loop {
+            if let Some(&entity) = current.next() {
+                return Some(entity);
+            }
+
+            let node = stack.pop()?;
+            let quad_node = &self.nodes[node.index];
+            current = quad_node.entities.iter();
+            if let Some(children) = quad_node.children {
+                stack.extend(
+                    node.children(children)
+                        .filter(|child| child.loose().intersects(&aabb)),
+                );
+            }
+        })
+    }
+
+    fn query_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        let ray = RayCast2d::new(origin, direction, max_distance);
+        self.collect_sorted(|aabb| ray.aabb_intersection_at(aabb))
+            .into_iter()
+    }
+
+    fn query_swept_aabb(
+        &self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        let center = aabb.center();
+        let local = Aabb2d {
+            min: aabb.min - center,
+            max: aabb.max - center,
+        };
+        let cast = AabbCast2d::new(local, center, direction, distance);
+        self.collect_sorted(|aabb| cast.aabb_collision_at(*aabb))
+            .into_iter()
+    }
+}
@@ -0,0 +1,67 @@
+//! Data structures that find entities, [`Aabb2d`] of which may intersect with the queried region.
+//! Broad phase used by the [`SpatialIndex`](super::spatial_index::SpatialIndex) is selected by
+//! [`ColliderGroup::BroadPhase`](crate::ColliderGroup::BroadPhase).
+
+use bevy::{
+    ecs::entity::MapEntities,
+    math::{bounding::Aabb2d, Dir2, Vec2},
+    prelude::Entity,
+};
+
+mod aabb_tree;
+mod chunk_grid;
+mod hierarchical_grid;
+mod loose_quadtree;
+mod static_bvh;
+
+pub use aabb_tree::AabbTree;
+pub use chunk_grid::{Chunk, ChunkGrid};
+pub use hierarchical_grid::HierarchicalGrid;
+pub use loose_quadtree::LooseQuadtree;
+pub use static_bvh::StaticBvh;
+
+/// Stores entities by their [`Aabb2d`].
+///
+/// Queries are conservative: they may return entities that don't actually intersect the queried region,
+/// and may return the same entity more than once, but never miss an entity that does intersect it.
+pub trait BroadPhase: MapEntities + Send + Sync + 'static {
+    fn insert(&mut self, entity: Entity, aabb: Aabb2d);
+
+    /// `old_aabb` is the one entity was inserted or last updated with.
+    fn update(&mut self, entity: Entity, old_aabb: Aabb2d, new_aabb: Aabb2d) {
+        self.remove(entity, old_aabb);
+        self.insert(entity, new_aabb);
+    }
+
+    /// `aabb` is the one entity was inserted or last updated with.
+    /// Entity that is not stored must be ignored.
+    fn remove(&mut self, entity: Entity, aabb: Aabb2d);
+
+    /// Iterates over all the stored entities, each of them once.
+    fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_;
+
+    /// Iterates over entities that may intersect with the `aabb`.
+    fn query_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = Entity> + '_;
+
+    /// Iterates over entities that may be hit by the ray.
+    /// Each entity comes with the distance along the ray at which the ray enters the region entity is stored in,
+    /// which is never greater than the distance at which it enters entity's [`Aabb2d`].
+    /// Entities are returned in the order of that distance.
+    fn query_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_;
+
+    /// Iterates over entities that may be hit by the `aabb` moving by `distance` in the `direction`.
+    /// Each entity comes with the distance at which the `aabb` enters the region entity is stored in,
+    /// which is never greater than the distance at which it hits entity's [`Aabb2d`].
+    /// Entities are returned in the order of that distance.
+    fn query_swept_aabb(
+        &self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_;
+}
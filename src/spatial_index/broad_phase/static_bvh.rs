@@ -0,0 +1,173 @@
+use bevy::{
+    ecs::entity::{EntityMapper, MapEntities},
+    math::bounding::{Aabb2d, AabbCast2d, BoundingVolume, IntersectsVolume, RayCast2d},
+    prelude::*,
+};
+use std::ops::Range;
+
+/// Read-only bounding volume hierarchy, built once from all of its entities.
+///
+/// Nodes are packed in depth-first order, so queries walk them without a stack,
+/// and entities of every leaf are stored next to each other.
+/// Used for the hurtboxes that never move, see [`StaticHurtbox`](crate::spatial_index::components::StaticHurtbox).
+#[derive(Debug, Clone, Default)]
+pub struct StaticBvh {
+    nodes: Vec<BvhNode>,
+    entries: Vec<(Entity, Aabb2d)>,
+}
+
+#[derive(Debug, Clone)]
+struct BvhNode {
+    aabb: Aabb2d,
+    /// Index of the first node after the subtree of this node
+    escape: usize,
+    /// Entries of a leaf, empty for branches
+    entries: Range<usize>,
+}
+
+impl MapEntities for StaticBvh {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for (entity, _) in self.entries.iter_mut() {
+            *entity = entity_mapper.map_entity(*entity);
+        }
+    }
+}
+
+impl StaticBvh {
+    /// Maximum number of entities in a leaf.
+    pub const LEAF_SIZE: usize = 4;
+
+    pub fn build(entries: impl IntoIterator<Item = (Entity, Aabb2d)>) -> Self {
+        let mut bvh = StaticBvh {
+            nodes: Vec::new(),
+            entries: entries.into_iter().collect(),
+        };
+        if !bvh.entries.is_empty() {
+            bvh.build_node(0..bvh.entries.len());
+        }
+        bvh
+    }
+
+    /// Number of entities in the hierarchy.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over entities and [`Aabb2d`]s the hierarchy was built from.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Aabb2d)> + '_ {
+        self.entries.iter().copied()
+    }
+
+    /// Splits the entries at the median of their centers along the longest axis, until they fit in a leaf.
+    fn build_node(&mut self, entries: Range<usize>) {
+        let aabb = self.entries[entries.clone()]
+            .iter()
+            .map(|(_, aabb)| *aabb)
+            .reduce(|merged, aabb| merged.merge(&aabb))
+            .expect("node is never empty");
+
+        let index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            aabb,
+            escape: 0,
+            entries: entries.start..entries.start,
+        });
+
+        if entries.len() <= Self::LEAF_SIZE {
+            self.nodes[index].entries = entries;
+        } else {
+            let size = aabb.max - aabb.min;
+            let axis = if size.x >= size.y { 0 } else { 1 };
+            let middle = entries.len() / 2;
+            self.entries[entries.clone()].select_nth_unstable_by(middle, |(_, a), (_, b)| {
+                a.center()[axis].total_cmp(&b.center()[axis])
+            });
+
+            let middle = entries.start + middle;
+            self.build_node(entries.start..middle);
+            self.build_node(middle..entries.end);
+        }
+
+        self.nodes[index].escape = self.nodes.len();
+    }
+
+    /// Iterates over entities, [`Aabb2d`] of which intersects with the `aabb`.
+    pub fn query_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = Entity> + '_ {
+        let mut node = 0;
+        let mut current: std::slice::Iter<(Entity, Aabb2d)> = [].iter();
+
+        std::iter::from_fn(move || loop {
+            for (entity, entity_aabb) in current.by_ref() {
+                if entity_aabb.intersects(&aabb) {
+                    return Some(*entity);
+                }
+            }
+
+            let bvh_node = self.nodes.get(node)?;
+            if bvh_node.aabb.intersects(&aabb) {
+                current = self.entries[bvh_node.entries.clone()].iter();
+                node += 1;
+            } else {
+                node = bvh_node.escape;
+            }
+        })
+    }
+
+    /// Entities, [`Aabb2d`] of which is hit by the ray, sorted by the distance at which it is hit.
+    pub fn query_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        let ray = RayCast2d::new(origin, direction, max_distance);
+        self.collect_sorted(|aabb| ray.aabb_intersection_at(aabb))
+            .into_iter()
+    }
+
+    /// Entities, [`Aabb2d`] of which is hit by the `aabb` moving by `distance` in the `direction`,
+    /// sorted by the distance at which it is hit.
+    pub fn query_swept_aabb(
+        &self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        let center = aabb.center();
+        let local = Aabb2d {
+            min: aabb.min - center,
+            max: aabb.max - center,
+        };
+        let cast = AabbCast2d::new(local, center, direction, distance);
+        self.collect_sorted(|aabb| cast.aabb_collision_at(*aabb))
+            .into_iter()
+    }
+
+    /// Visits the nodes for which `enter` returns distance, collects entities sorted by it.
+    fn collect_sorted(&self, enter: impl Fn(&Aabb2d) -> Option<f32>) -> Vec<(f32, Entity)> {
+        let mut found = Vec::new();
+        let mut node = 0;
+
+        while let Some(bvh_node) = self.nodes.get(node) {
+            if enter(&bvh_node.aabb).is_none() {
+                node = bvh_node.escape;
+                continue;
+            }
+            found.extend(
+                self.entries[bvh_node.entries.clone()]
+                    .iter()
+                    .filter_map(|(entity, aabb)| Some((enter(aabb)?, *entity))),
+            );
+            node += 1;
+        }
+
+        found.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        found
+    }
+}
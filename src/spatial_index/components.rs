@@ -1,75 +1,83 @@
-use super::{spatial_index::SpatialIndex, SpatialIndexColliderGroup};
-use crate::{bounded::Bounded, components::HurtboxShape};
-use bevy::{math::bounding::Aabb2d, prelude::*};
+use super::{broad_phase::BroadPhase, spatial_index::SpatialIndex, SpatialIndexColliderGroup};
+use crate::{
+    bounded::Bounded,
+    components::{collider_isometry, HurtboxShape},
+    error::{BumpError, BumpErrors},
+};
+use bevy::{math::bounding::Aabb2d, prelude::*, utils::Parallel};
 use std::marker::PhantomData;
 
+/// Marks a hurtbox, registration of which failed. Registration is retried every frame until it succeeds.
 #[derive(Component)]
 #[component(storage = "SparseSet")]
-pub struct RegisterHurtbox<T>(PhantomData<T>);
+pub(super) struct RetryRegistration<T>(PhantomData<T>);
 
-impl<T> RegisterHurtbox<T> {
+/// Marks a hurtbox that never moves, like level geometry.
+/// Static hurtbox is indexed once, when it's registered, and is skipped by the per frame update of the index.
+/// Can be inserted and removed at any time, hurtbox is moved between the static and the moving storage.
+/// To move static hurtbox, insert its [`HurtboxShape`] again.
+#[derive(Component)]
+pub struct StaticHurtbox<T>(PhantomData<T>);
+
+impl<T> StaticHurtbox<T> {
     pub fn new() -> Self {
         Self(PhantomData)
     }
 }
 
-pub(super) fn register_hurtbox<Group: SpatialIndexColliderGroup>(
-    to_register: Query<
-        Entity,
-        (
-            With<RegisterHurtbox<Group>>,
-            With<HurtboxShape<Group>>,
-            With<Transform>,
-        ),
-    >,
-    mut commands: Commands,
-) {
-    for entity in to_register.iter() {
-        commands
-            .entity(entity)
-            .queue(|entity: Entity, world: &mut World| {
-                // Until command is appled to the world, user can remove necessary components
-                // So we need to check before inserting
-                let mut entity_mut = world.entity_mut(entity);
-
-                if !entity_mut.contains::<HurtboxShape<Group>>()
-                    || !entity_mut.contains::<Transform>()
-                {
-                    return;
-                }
-
-                if entity_mut.take::<RegisterHurtbox<Group>>().is_none() {
-                    return;
-                }
-
-                entity_mut.insert(SpatialIndexRegistry::<Group>::not_valid());
-            });
+impl<T> Default for StaticHurtbox<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// Position of the hurtbox in the [`SpatialIndex`].
+/// Required by the [`HurtboxShape`], filled in when the shape is inserted.
 #[derive(Component, Debug, Clone)]
 pub struct SpatialIndexRegistry<Group> {
     current_shape_bounding: Aabb2d,
     current_position: Vec2,
+    current_rotation: Rot2,
     last_shape_bounding: Aabb2d,
     last_position: Vec2,
 
     marker: PhantomData<Group>,
 }
 
-impl<Group: SpatialIndexColliderGroup> SpatialIndexRegistry<Group> {
+impl<Group> Default for SpatialIndexRegistry<Group> {
+    fn default() -> Self {
+        Self::not_valid()
+    }
+}
+
+impl<Group> SpatialIndexRegistry<Group> {
     /// Creates a new instance with invalid fields.
-    /// The
+    /// The hurtbox is not in the index until the registry is filled in.
     pub fn not_valid() -> Self {
         Self {
-            current_shape_bounding: Aabb2d::new(Vec2::NAN, Vec2::NAN),
+            current_shape_bounding: Aabb2d {
+                min: Vec2::NAN,
+                max: Vec2::NAN,
+            },
             current_position: Vec2::NAN,
-            last_shape_bounding: Aabb2d::new(Vec2::NAN, Vec2::NAN),
+            current_rotation: Rot2::IDENTITY,
+            last_shape_bounding: Aabb2d {
+                min: Vec2::NAN,
+                max: Vec2::NAN,
+            },
             last_position: Vec2::NAN,
             marker: PhantomData,
         }
     }
 
+    /// False until the hurtbox is registered in the index.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        !self.current_position.is_nan()
+    }
+}
+
+impl<Group: SpatialIndexColliderGroup> SpatialIndexRegistry<Group> {
     fn global_last_aabb(&self) -> Aabb2d {
         Aabb2d {
             min: self.last_shape_bounding.min + self.last_position,
@@ -95,96 +103,321 @@ impl<Group: SpatialIndexColliderGroup> SpatialIndexRegistry<Group> {
         self.current_position
     }
 
-    fn update(&mut self, hurtbox: &HurtboxShape<Group>, new_position: Vec2) {
-        let new_shape_bounding = hurtbox.bounding();
+    /// Rotation of the hurtbox. Always identity if [`ColliderGroup::USE_ROTATION`](crate::ColliderGroup::USE_ROTATION) is false.
+    #[inline]
+    pub fn current_rotation(&self) -> Rot2 {
+        self.current_rotation
+    }
+
+    #[inline]
+    pub fn current_isometry(&self) -> Isometry2d {
+        Isometry2d::new(self.current_position, self.current_rotation)
+    }
 
+    fn update(&mut self, hurtbox: &HurtboxShape<Group>, new_isometry: Isometry2d) {
         self.last_position = self.current_position;
         self.last_shape_bounding = self.current_shape_bounding;
 
-        self.current_position = new_position;
-        self.current_shape_bounding = new_shape_bounding;
+        self.current_position = new_isometry.translation;
+        self.current_rotation = new_isometry.rotation;
+        self.current_shape_bounding = shape_bounding(hurtbox, new_isometry.rotation);
     }
 }
 
-macro_rules! hurtbox_registering_error {
-    ($x:expr) => {
-        format!(
-            "Trying to register entity as hurtbox{}. \
-            Unable to deduce entity's position in SpacialIndex. \
-            Use `RegisterHurtbox` component to register hurtbox as soon as possible, \
-            instead of trying to do it manually.",
-            $x
-        )
-    };
+/// Bounding of the shape, relative to it's position.
+fn shape_bounding<Group: SpatialIndexColliderGroup>(
+    hurtbox: &HurtboxShape<Group>,
+    rotation: Rot2,
+) -> Aabb2d {
+    if rotation == Rot2::IDENTITY {
+        hurtbox.bounding()
+    } else {
+        hurtbox.bounding_with_isometry(Isometry2d::from_rotation(rotation))
+    }
 }
 
-fn on_insert_spacial_index_registry<Group: SpatialIndexColliderGroup>(
-    trigger: Trigger<OnInsert, SpatialIndexRegistry<Group>>,
+/// Registers the hurtbox in the index, removing it from the old position first.
+/// Entity that can't be registered yet is reported to the [`BumpErrors`] and is retried on the next frame.
+#[allow(clippy::type_complexity)]
+pub(super) fn on_insert_hurtbox_shape<Group: SpatialIndexColliderGroup>(
+    trigger: Trigger<OnInsert, HurtboxShape<Group>>,
     mut index: ResMut<SpatialIndex<Group>>,
     mut hurtboxes: Query<(
         &mut SpatialIndexRegistry<Group>,
-        Option<&HurtboxShape<Group>>,
+        &HurtboxShape<Group>,
+        Has<StaticHurtbox<Group>>,
     )>,
     transform_helper: TransformHelper,
+    errors: Res<BumpErrors>,
+    mut commands: Commands,
 ) {
     let entity = trigger.entity();
+    let Ok((mut registry, shape, is_static)) = hurtboxes.get_mut(entity) else {
+        return;
+    };
+
+    if registry.is_valid() {
+        index.remove(entity, registry.global_aabb());
+        *registry = SpatialIndexRegistry::not_valid();
+    }
+    if let Err(error) = register::<Group>(
+        entity,
+        &mut registry,
+        shape,
+        is_static,
+        &mut index,
+        &transform_helper,
+    ) {
+        errors.report(error);
+        commands
+            .entity(entity)
+            .insert(RetryRegistration::<Group>(PhantomData));
+    }
+}
 
-    let (mut registry, shape) = hurtboxes.get_mut(entity).unwrap();
-    let shape = shape.expect(&hurtbox_registering_error!(
-        " without `HurtboxShape` present"
-    ));
+#[allow(clippy::type_complexity)]
+pub(super) fn retry_hurtbox_registration<Group: SpatialIndexColliderGroup>(
+    mut to_retry: Query<
+        (
+            Entity,
+            &mut SpatialIndexRegistry<Group>,
+            &HurtboxShape<Group>,
+            Has<StaticHurtbox<Group>>,
+        ),
+        With<RetryRegistration<Group>>,
+    >,
+    mut index: ResMut<SpatialIndex<Group>>,
+    transform_helper: TransformHelper,
+    errors: Res<BumpErrors>,
+    mut commands: Commands,
+) {
+    for (entity, mut registry, shape, is_static) in to_retry.iter_mut() {
+        match register::<Group>(
+            entity,
+            &mut registry,
+            shape,
+            is_static,
+            &mut index,
+            &transform_helper,
+        ) {
+            Ok(()) => {
+                commands.entity(entity).remove::<RetryRegistration<Group>>();
+            }
+            Err(error) => errors.report(error),
+        }
+    }
+}
 
-    let current_shape_bounding = shape.bounding();
-    let current_position = transform_helper
+fn register<Group: SpatialIndexColliderGroup>(
+    entity: Entity,
+    registry: &mut SpatialIndexRegistry<Group>,
+    shape: &HurtboxShape<Group>,
+    is_static: bool,
+    index: &mut SpatialIndex<Group>,
+    transform_helper: &TransformHelper,
+) -> Result<(), BumpError> {
+    let position = transform_helper
         .compute_global_transform(entity)
-        .expect(&hurtbox_registering_error!(
-            ", but there is a problem computing global position"
-        ))
-        .translation()
-        .xy();
+        .map_err(|cause| BumpError::HurtboxPosition { entity, cause })?;
+
+    let current_isometry = collider_isometry::<Group>(&position);
+    let current_shape_bounding = shape_bounding(shape, current_isometry.rotation);
+    let current_position = current_isometry.translation;
 
     *registry = SpatialIndexRegistry {
         current_shape_bounding,
         current_position,
+        current_rotation: current_isometry.rotation,
         last_shape_bounding: current_shape_bounding,
         last_position: current_position,
         marker: PhantomData,
     };
-    index.add_entity(entity, registry.global_aabb());
+    if is_static {
+        index.insert_static(entity, registry.global_aabb());
+    } else {
+        index.insert(entity, registry.global_aabb());
+    }
+    Ok(())
 }
 
-fn on_replace_spacial_index_registry<Group: SpatialIndexColliderGroup>(
-    trigger: Trigger<OnReplace, (SpatialIndexRegistry<Group>, HurtboxShape<Group>, Transform)>,
+/// Moves already registered hurtbox from the broad phase to the static storage.
+pub(super) fn on_add_static_hurtbox<Group: SpatialIndexColliderGroup>(
+    trigger: Trigger<OnAdd, StaticHurtbox<Group>>,
     mut index: ResMut<SpatialIndex<Group>>,
-    mut hurtboxes: Query<&SpatialIndexRegistry<Group>>,
+    hurtboxes: Query<&SpatialIndexRegistry<Group>>,
 ) {
     let entity = trigger.entity();
-    let Ok(registry) = hurtboxes.get_mut(entity) else {
+    let Ok(registry) = hurtboxes.get(entity) else {
         return;
     };
-    index.remove_entity(entity, registry.global_aabb());
+    if !registry.is_valid() || index.is_static(entity) {
+        return;
+    }
+    index.remove(entity, registry.global_aabb());
+    index.insert_static(entity, registry.global_aabb());
 }
 
-fn update_spatial_index_registry<Group: SpatialIndexColliderGroup>(
-    mut hurtboxes: Query<(
-        Entity,
-        &mut SpatialIndexRegistry<Group>,
-        Ref<HurtboxShape<Group>>,
-    )>,
+/// Moves registered hurtbox from the static storage back to the broad phase.
+/// On despawn the registry is removed from the index first, so nothing is moved.
+pub(super) fn on_remove_static_hurtbox<Group: SpatialIndexColliderGroup>(
+    trigger: Trigger<OnRemove, StaticHurtbox<Group>>,
+    mut index: ResMut<SpatialIndex<Group>>,
+    hurtboxes: Query<&SpatialIndexRegistry<Group>>,
+) {
+    let entity = trigger.entity();
+    let Ok(registry) = hurtboxes.get(entity) else {
+        return;
+    };
+    if !index.is_static(entity) {
+        return;
+    }
+    index.remove(entity, registry.global_aabb());
+    index.insert(entity, registry.global_aabb());
+}
+
+/// Runs when the registry is replaced, removed or its entity is despawned.
+/// Registry is invalidated after the removal, so later observers don't remove the entity again.
+pub(super) fn on_replace_spacial_index_registry<Group: SpatialIndexColliderGroup>(
+    trigger: Trigger<OnReplace, SpatialIndexRegistry<Group>>,
+    mut index: ResMut<SpatialIndex<Group>>,
+    mut hurtboxes: Query<&mut SpatialIndexRegistry<Group>>,
+) {
+    let entity = trigger.entity();
+    let Ok(mut registry) = hurtboxes.get_mut(entity) else {
+        return;
+    };
+    if registry.is_valid() {
+        index.remove(entity, registry.global_aabb());
+        *registry = SpatialIndexRegistry::not_valid();
+    }
+}
+
+/// Entity without [`HurtboxShape`] or [`Transform`] is no longer a hurtbox.
+/// It is removed from the index right away, and the registry is removed
+/// unless the components were inserted back before the command is applied.
+/// To make it a hurtbox again, insert the [`HurtboxShape`] again.
+pub(super) fn on_remove_hurtbox_component<Group: SpatialIndexColliderGroup>(
+    trigger: Trigger<OnRemove, (HurtboxShape<Group>, Transform)>,
+    mut index: ResMut<SpatialIndex<Group>>,
+    mut hurtboxes: Query<&mut SpatialIndexRegistry<Group>>,
+    mut commands: Commands,
+) {
+    let entity = trigger.entity();
+    let Ok(mut registry) = hurtboxes.get_mut(entity) else {
+        return;
+    };
+    if registry.is_valid() {
+        index.remove(entity, registry.global_aabb());
+        *registry = SpatialIndexRegistry::not_valid();
+    }
+
+    commands.queue(move |world: &mut World| {
+        let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+            return;
+        };
+        if !entity_mut.contains::<HurtboxShape<Group>>() || !entity_mut.contains::<Transform>() {
+            entity_mut.remove::<(SpatialIndexRegistry<Group>, RetryRegistration<Group>)>();
+        }
+    });
+}
+
+/// New positions of the hurtboxes are computed in parallel, then the moved ones are updated in the index at once.
+#[allow(clippy::type_complexity)]
+pub(super) fn update_spatial_index_registry<Group: SpatialIndexColliderGroup>(
+    mut hurtboxes: Query<
+        (
+            Entity,
+            &mut SpatialIndexRegistry<Group>,
+            Ref<HurtboxShape<Group>>,
+        ),
+        (
+            Without<StaticHurtbox<Group>>,
+            Without<RetryRegistration<Group>>,
+        ),
+    >,
     mut spacial_index: ResMut<SpatialIndex<Group>>,
     transform_helper: TransformHelper,
+    errors: Res<BumpErrors>,
+    mut moved: Local<Parallel<Vec<(Entity, Aabb2d, Aabb2d)>>>,
+    mut batch: Local<Vec<(Entity, Aabb2d, Aabb2d)>>,
 ) {
-    for (entity, mut registry, hurtbox) in hurtboxes.iter_mut() {
-        let Ok(new_position) = transform_helper.compute_global_transform(entity) else {
-            warn!("Unable to compute global position of registered hurtbox of {entity}. Skipping hurtbox update.");
-            continue;
-        };
-        let new_position = new_position.translation().xy();
+    hurtboxes
+        .par_iter_mut()
+        .for_each(|(entity, mut registry, hurtbox)| {
+            let new_position = match transform_helper.compute_global_transform(entity) {
+                Ok(new_position) => new_position,
+                Err(cause) => {
+                    errors.report(BumpError::ColliderPosition { entity, cause });
+                    return;
+                }
+            };
+            let new_isometry = collider_isometry::<Group>(&new_position);
+
+            if hurtbox.is_changed() || registry.current_isometry() != new_isometry {
+                registry.update(&hurtbox, new_isometry);
+                moved.borrow_local_mut().push((
+                    entity,
+                    registry.global_last_aabb(),
+                    registry.global_aabb(),
+                ));
+            }
+        });
+
+    moved.drain_into(&mut batch);
+    // Threads finish in any order, sorting keeps the index the same from run to run
+    batch.sort_unstable_by_key(|(entity, ..)| *entity);
+    for (entity, old_aabb, new_aabb) in batch.drain(..) {
+        spacial_index.update(entity, old_aabb, new_aabb);
+    }
+}
 
-        let position_change = registry.current_position - new_position;
-        if hurtbox.is_changed() || position_change != Vec2::ZERO {
-            registry.update(&hurtbox, new_position);
-            spacial_index.change_entity(entity, registry.global_last_aabb(), registry.global_aabb());
+pub(super) fn bake_static_hurtboxes<Group: SpatialIndexColliderGroup>(
+    mut spacial_index: ResMut<SpatialIndex<Group>>,
+) {
+    if spacial_index.needs_baking() {
+        spacial_index.bake();
+    }
+}
+
+/// Reports the hurtboxes that are stored in the [`SpatialIndex`] differently from what their registry says.
+/// Run by the [`SpatialIndexDebugPlugin`](super::SpatialIndexDebugPlugin) in debug builds.
+#[cfg(debug_assertions)]
+#[allow(clippy::type_complexity)]
+pub(super) fn check_spatial_index_consistency<Group: SpatialIndexColliderGroup>(
+    index: Res<SpatialIndex<Group>>,
+    hurtboxes: Query<
+        (Entity, &SpatialIndexRegistry<Group>),
+        (With<HurtboxShape<Group>>, With<Transform>),
+    >,
+    errors: Res<BumpErrors>,
+) {
+    let report =
+        |entity, problem| errors.report(BumpError::InconsistentSpatialIndex { entity, problem });
+
+    let mut indexed = bevy::ecs::entity::EntityHashSet::default();
+    for entity in index.iter_entities() {
+        if !indexed.insert(entity) {
+            report(entity, "is stored more than once");
+        }
+        if !hurtboxes.contains(entity) {
+            report(entity, "is stored, but is not a registered hurtbox");
+        }
+    }
+
+    for (entity, registry) in hurtboxes.iter() {
+        if !registry.is_valid() {
+            if indexed.contains(&entity) {
+                report(entity, "is stored, but its registration failed");
+            }
+            continue;
+        }
+        if !indexed.contains(&entity) {
+            report(entity, "is a registered hurtbox, but is not stored");
+        } else if !index
+            .query_aabb(registry.global_aabb())
+            .any(|found| found == entity)
+        {
+            report(entity, "is stored away from its hurtbox");
         }
     }
 }
@@ -1,42 +1,128 @@
 use bevy::{
-    app::{App, Plugin},
+    app::{App, Plugin, Update},
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
     math::bounding::Aabb2d,
+    prelude::{default, IntoSystemConfigs},
+};
+use broad_phase::ChunkGrid;
+#[cfg(debug_assertions)]
+use components::check_spatial_index_consistency;
+use components::{
+    bake_static_hurtboxes, on_add_static_hurtbox, on_insert_hurtbox_shape,
+    on_remove_hurtbox_component, on_remove_static_hurtbox, on_replace_spacial_index_registry,
+    retry_hurtbox_registration, update_spatial_index_registry,
 };
 use spatial_index::SpatialIndex;
+use std::marker::PhantomData;
 
-use crate::{bounded::Bounded, ColliderGroup};
+use crate::{
+    bounded::Bounded, configure_collision_detection_sets, error::BumpErrorPlugin, ColliderGroup,
+    CollisionDetectionSet,
+};
 
+pub mod broad_phase;
 pub mod components;
+pub mod pairs;
 pub mod query;
+#[allow(clippy::module_inception)]
 pub mod spatial_index;
 
 pub trait SpatialIndexColliderGroup: ColliderGroup<Hitbox: Bounded<Aabb2d>, Hurtbox: Bounded<Aabb2d>> {}
 
 impl<T: ColliderGroup<Hitbox: Bounded<Aabb2d>, Hurtbox: Bounded<Aabb2d>>> SpatialIndexColliderGroup for T {}
 
-pub struct SpatialIndexPlugin<Group> {
-    pub pixels_per_chunk: f32,
-    marker: std::marker::PhantomData<fn() -> Group>,
+pub struct SpatialIndexPlugin<Group: ColliderGroup> {
+    /// Broad phase the [`SpatialIndex`] of the group starts with.
+    pub broad_phase: Group::BroadPhase,
+    /// Schedule the index is updated in, [`Update`] by default.
+    /// Should be the same schedule the hitboxes of the group are moved in.
+    pub schedule: InternedScheduleLabel,
 }
 
-impl<Group> Default for SpatialIndexPlugin<Group> {
+impl<Group: ColliderGroup<BroadPhase: Default>> Default for SpatialIndexPlugin<Group> {
     fn default() -> Self {
-        Self::new(SpatialIndex::<Group>::PIXELS_PER_CHUNK_DEFAULT)
+        Self::with_broad_phase(default())
     }
 }
 
-impl<Group> SpatialIndexPlugin<Group> {
+impl<Group: ColliderGroup<BroadPhase = ChunkGrid>> SpatialIndexPlugin<Group> {
     pub fn new(pixels_per_chunk: f32) -> Self {
+        Self::with_broad_phase(ChunkGrid::new(pixels_per_chunk))
+    }
+}
+
+impl<Group: ColliderGroup> SpatialIndexPlugin<Group> {
+    pub fn with_broad_phase(broad_phase: Group::BroadPhase) -> Self {
         SpatialIndexPlugin {
-            pixels_per_chunk,
-            marker: std::marker::PhantomData,
+            broad_phase,
+            schedule: Update.intern(),
         }
     }
+
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = schedule.intern();
+        self
+    }
 }
 
-impl<Group: ColliderGroup> Plugin for SpatialIndexPlugin<Group> {
-    fn build(&self, _app: &mut App) {
-        // app.add_resource(SpatialIndex::<Group>::new(self.pixels_per_chunk));
-        // TODO
+impl<Group: SpatialIndexColliderGroup<BroadPhase: Clone>> Plugin for SpatialIndexPlugin<Group> {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<BumpErrorPlugin>() {
+            app.add_plugins(BumpErrorPlugin::default());
+        }
+        configure_collision_detection_sets(app, self.schedule);
+        app.insert_resource(SpatialIndex::<Group>::new(self.broad_phase.clone()));
+
+        app.add_systems(
+            self.schedule,
+            (
+                retry_hurtbox_registration::<Group>,
+                update_spatial_index_registry::<Group>,
+                bake_static_hurtboxes::<Group>,
+            )
+                .chain()
+                .in_set(CollisionDetectionSet::First),
+        );
+
+        app.add_observer(on_insert_hurtbox_shape::<Group>)
+            .add_observer(on_replace_spacial_index_registry::<Group>)
+            .add_observer(on_remove_hurtbox_component::<Group>)
+            .add_observer(on_add_static_hurtbox::<Group>)
+            .add_observer(on_remove_static_hurtbox::<Group>);
     }
 }
+
+/// Checks every frame that the [`SpatialIndex`] of the group matches the registered hurtboxes,
+/// reporting mismatches through the [`BumpErrors`](crate::error::BumpErrors).
+/// Costs a query per hurtbox, so it only runs in debug builds and does nothing in release ones.
+/// Runs in the schedule of the [`SpatialIndexPlugin`] of the group, which has to be added before.
+pub struct SpatialIndexDebugPlugin<Group: ColliderGroup>(PhantomData<Group>);
+
+impl<Group: ColliderGroup> Default for SpatialIndexDebugPlugin<Group> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<Group: SpatialIndexColliderGroup<BroadPhase: Clone>> Plugin
+    for SpatialIndexDebugPlugin<Group>
+{
+    #[cfg(debug_assertions)]
+    fn build(&self, app: &mut App) {
+        let Some(index_plugin) = app.get_added_plugins::<SpatialIndexPlugin<Group>>().pop() else {
+            panic!(
+                "SpatialIndexDebugPlugin has to be added after the SpatialIndexPlugin of the group"
+            );
+        };
+        let schedule = index_plugin.schedule;
+        app.add_systems(
+            schedule,
+            check_spatial_index_consistency::<Group>
+                .after(bake_static_hurtboxes::<Group>)
+                .in_set(CollisionDetectionSet::First),
+        );
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn build(&self, _app: &mut App) {}
+}
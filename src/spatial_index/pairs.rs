@@ -0,0 +1,144 @@
+use super::{components::SpatialIndexRegistry, SpatialIndexColliderGroup};
+use crate::{
+    bounded::Bounded,
+    collider::Collider,
+    components::{collider_isometry, HitboxShape, HurtboxShape},
+    error::{BumpError, BumpErrors},
+    spatial_query::filter::{
+        HitboxFilterSystemParam, HitboxParam, HurtboxFilterSystemParam, HurtboxParam,
+        SpatialQueryFilter, SystemSpatialQueryFilter,
+    },
+};
+use bevy::{
+    ecs::system::{StaticSystemParam, SystemParam},
+    math::bounding::Aabb2d,
+    prelude::*,
+};
+
+/// Finds all the intersecting pairs of hitboxes and hurtboxes of the group in one pass,
+/// sorting their [`Aabb2d`]s along the x axis and sweeping over them.
+/// Much cheaper than querying the spatial index for every hitbox, when there are lots of hitboxes.
+///
+/// Hurtboxes use the [`Aabb2d`]s cached in the [`SpatialIndexRegistry`],
+/// hitboxes are taken at their current global position.
+#[derive(SystemParam)]
+pub struct SpatialIndexPairs<'w, 's, Group: SpatialIndexColliderGroup> {
+    hitboxes: Query<'w, 's, (Entity, &'static HitboxShape<Group>)>,
+    hurtboxes: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static HurtboxShape<Group>,
+            &'static SpatialIndexRegistry<Group>,
+        ),
+    >,
+    hitbox_filter: StaticSystemParam<'w, 's, HitboxFilterSystemParam<Group>>,
+    hurtbox_filter: StaticSystemParam<'w, 's, HurtboxFilterSystemParam<Group>>,
+    transform_helper: TransformHelper<'w, 's>,
+    errors: Res<'w, BumpErrors>,
+}
+
+/// Collider that takes part in the sweep.
+struct SweepEntry<'a, T, P> {
+    entity: Entity,
+    aabb: Aabb2d,
+    collider: Collider<'a, T>,
+    filter_param: P,
+}
+
+impl<Group: SpatialIndexColliderGroup> SpatialIndexPairs<'_, '_, Group> {
+    /// Returns every `(hitbox, hurtbox)` pair that intersects and passes the [`ColliderGroup::Filter`](crate::ColliderGroup::Filter) once.
+    /// Entity is never paired with itself.
+    pub fn intersecting_pairs(&self) -> Vec<(Entity, Entity)> {
+        let mut hitboxes: Vec<SweepEntry<Group::Hitbox, HitboxParam<Group>>> = self
+            .hitboxes
+            .iter()
+            .filter_map(|(entity, shape)| {
+                let position = match self.transform_helper.compute_global_transform(entity) {
+                    Ok(position) => position,
+                    Err(cause) => {
+                        self.errors
+                            .report(BumpError::ColliderPosition { entity, cause });
+                        return None;
+                    }
+                };
+                let collider =
+                    Collider::from_isometry(&**shape, collider_isometry::<Group>(&position));
+                Some(SweepEntry {
+                    entity,
+                    aabb: collider.bounding(),
+                    collider,
+                    filter_param: Group::Filter::hitbox_filter_param(entity, &self.hitbox_filter),
+                })
+            })
+            .collect();
+        let mut hurtboxes: Vec<SweepEntry<Group::Hurtbox, HurtboxParam<Group>>> = self
+            .hurtboxes
+            .iter()
+            .filter(|(_, _, registry)| registry.is_valid())
+            .map(|(entity, shape, registry)| SweepEntry {
+                entity,
+                aabb: registry.global_aabb(),
+                collider: Collider::from_isometry(&**shape, registry.current_isometry()),
+                filter_param: Group::Filter::hurtbox_filter_param(entity, &self.hurtbox_filter),
+            })
+            .collect();
+
+        hitboxes.sort_by(|a, b| a.aabb.min.x.total_cmp(&b.aabb.min.x));
+        hurtboxes.sort_by(|a, b| a.aabb.min.x.total_cmp(&b.aabb.min.x));
+
+        // Every pair is found once, when the collider that starts further along the x axis is reached
+        let mut pairs = Vec::new();
+        let mut active_hitboxes: Vec<&SweepEntry<Group::Hitbox, HitboxParam<Group>>> = Vec::new();
+        let mut active_hurtboxes: Vec<&SweepEntry<Group::Hurtbox, HurtboxParam<Group>>> =
+            Vec::new();
+        let (mut hitboxes, mut hurtboxes) =
+            (hitboxes.iter().peekable(), hurtboxes.iter().peekable());
+
+        loop {
+            let next_is_hitbox = match (hitboxes.peek(), hurtboxes.peek()) {
+                (Some(hitbox), Some(hurtbox)) => hitbox.aabb.min.x <= hurtbox.aabb.min.x,
+                (hitbox, _) => hitbox.is_some(),
+            };
+
+            if next_is_hitbox {
+                let Some(hitbox) = hitboxes.next() else {
+                    break;
+                };
+                active_hurtboxes.retain(|hurtbox| hurtbox.aabb.max.x >= hitbox.aabb.min.x);
+                for hurtbox in &active_hurtboxes {
+                    if intersect::<Group>(hitbox, hurtbox) {
+                        pairs.push((hitbox.entity, hurtbox.entity));
+                    }
+                }
+                active_hitboxes.push(hitbox);
+            } else {
+                let Some(hurtbox) = hurtboxes.next() else {
+                    break;
+                };
+                active_hitboxes.retain(|hitbox| hitbox.aabb.max.x >= hurtbox.aabb.min.x);
+                for hitbox in &active_hitboxes {
+                    if intersect::<Group>(hitbox, hurtbox) {
+                        pairs.push((hitbox.entity, hurtbox.entity));
+                    }
+                }
+                active_hurtboxes.push(hurtbox);
+            }
+        }
+
+        pairs
+    }
+}
+
+/// Checks the pair, the [`Aabb2d`]s of which already overlap on the x axis.
+fn intersect<Group: SpatialIndexColliderGroup>(
+    hitbox: &SweepEntry<Group::Hitbox, HitboxParam<Group>>,
+    hurtbox: &SweepEntry<Group::Hurtbox, HurtboxParam<Group>>,
+) -> bool {
+    hitbox.entity != hurtbox.entity
+        && hitbox.aabb.min.y <= hurtbox.aabb.max.y
+        && hurtbox.aabb.min.y <= hitbox.aabb.max.y
+        && Group::Filter::filter(hitbox.filter_param, hurtbox.filter_param)
+        && hitbox.collider.intersect(hurtbox.collider)
+}
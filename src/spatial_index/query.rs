@@ -2,15 +2,17 @@ use super::{
     components::SpatialIndexRegistry, spatial_index::SpatialIndex, SpatialIndexColliderGroup,
 };
 use crate::{
-    bounded::Bounded,
-    collider::Collider,
+    bounded::{Bounded, Point},
+    collider::{
+        Collider, ColliderDistance, ColliderInteraction, ColliderParts, ColliderPenetration,
+        Distance, Penetration,
+    },
     components::HurtboxShape,
     spatial_query::{
         filter::{
-            HitboxFilterSystemParam, HurtboxFilterSystemParam, SpatialQueryFilter,
-            SystemSpatialQueryFilter,
+            HitboxParam, HurtboxFilterSystemParam, SpatialQueryFilter, SystemSpatialQueryFilter,
         },
-        SpatialQuery,
+        RayHit, SpatialQuery,
     },
 };
 use bevy::{
@@ -23,7 +25,7 @@ use bevy::{
 };
 
 #[derive(SystemParam)]
-struct GenericSpatialIndexQuery<
+pub struct GenericSpatialIndexQuery<
     'w,
     's,
     Group: SpatialIndexColliderGroup,
@@ -38,117 +40,540 @@ struct GenericSpatialIndexQuery<
             &'static SpatialIndexRegistry<Group>,
         ),
     >,
-    hitbox_filter: StaticSystemParam<'w, 's, HitboxFilterSystemParam<Group>>,
     hurtbox_filter: StaticSystemParam<'w, 's, HurtboxFilterSystemParam<Group>>,
     marker: std::marker::PhantomData<fn() -> I>,
 }
 
-trait IterHurtboxesOnAabb: Sized + Send + Sync + 'static {
+pub trait IterHurtboxesOnAabb: Sized + Send + Sync + 'static {
     fn iter_hurtboxes_on_aabb<'w, 'a, Group: SpatialIndexColliderGroup>(
-        index: &'a mut Res<'w, SpatialIndex<Group>>,
+        index: &'a Res<'w, SpatialIndex<Group>>,
         aabb: Aabb2d,
     ) -> impl Iterator<Item = Entity>;
+
+    /// Iterates over hurtboxes in the chunks that `aabb` passes through, in the order it enters them.
+    /// Each hurtbox comes with the distance at which `aabb` enters the chunk it is found in.
+    fn iter_hurtboxes_on_swept_aabb<'w, 'a, Group: SpatialIndexColliderGroup>(
+        index: &'a Res<'w, SpatialIndex<Group>>,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)>;
 }
 
-struct AllowDuplication;
+pub struct AllowDuplication;
 
 impl IterHurtboxesOnAabb for AllowDuplication {
     fn iter_hurtboxes_on_aabb<'w, 'a, Group: SpatialIndexColliderGroup>(
-        index: &'a mut Res<'w, SpatialIndex<Group>>,
+        index: &'a Res<'w, SpatialIndex<Group>>,
         aabb: Aabb2d,
     ) -> impl Iterator<Item = Entity> {
-        index
-            .iter_chunks_on_aabb(aabb)
-            .flat_map(|chunk| chunk.iter().copied())
+        index.query_aabb(aabb)
+    }
+
+    fn iter_hurtboxes_on_swept_aabb<'w, 'a, Group: SpatialIndexColliderGroup>(
+        index: &'a Res<'w, SpatialIndex<Group>>,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> {
+        index.query_swept_aabb(aabb, direction, distance)
     }
 }
 
-struct NoDuplication;
+pub struct NoDuplication;
 
 impl IterHurtboxesOnAabb for NoDuplication {
     fn iter_hurtboxes_on_aabb<'w, 'a, Group: SpatialIndexColliderGroup>(
-        index: &'a mut Res<'w, SpatialIndex<Group>>,
+        index: &'a Res<'w, SpatialIndex<Group>>,
         aabb: Aabb2d,
     ) -> impl Iterator<Item = Entity> {
         let mut deduplication_set = EntityHashSet::default();
 
         index
-            .iter_chunks_on_aabb(aabb)
-            .flat_map(|chunk| chunk.iter().copied())
+            .query_aabb(aabb)
             .filter(move |&entity| deduplication_set.insert(entity))
     }
+
+    fn iter_hurtboxes_on_swept_aabb<'w, 'a, Group: SpatialIndexColliderGroup>(
+        index: &'a Res<'w, SpatialIndex<Group>>,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> {
+        let mut deduplication_set = EntityHashSet::default();
+
+        index
+            .query_swept_aabb(aabb, direction, distance)
+            .filter(move |&(_, entity)| deduplication_set.insert(entity))
+    }
 }
 
-pub type SpatialIndexQuery<'w, 's, Group: SpatialIndexColliderGroup> =
+pub type SpatialIndexQuery<'w, 's, Group> =
     GenericSpatialIndexQuery<'w, 's, Group, NoDuplication>;
 
-pub type SpatialIndexQueryAllowDuplication<'w, 's, Group: SpatialIndexColliderGroup> =
+pub type SpatialIndexQueryAllowDuplication<'w, 's, Group> =
     GenericSpatialIndexQuery<'w, 's, Group, AllowDuplication>;
 
 impl<'w, 's, Group: SpatialIndexColliderGroup, I: IterHurtboxesOnAabb>
     GenericSpatialIndexQuery<'w, 's, Group, I>
 {
-    fn iter_hurtboxes_on_aabb<'a>(
-        &'a mut self,
+    fn iter_hurtboxes_on_aabb<'a, 'p: 'a>(
+        &'a self,
         aabb: Aabb2d,
-        hitbox_param: <Group::Filter as SpatialQueryFilter>::HitboxParam<'a>,
-    ) -> impl Iterator<Item = (Collider<'a, Group::Hurtbox>, Entity)> + use<'w, 's, 'a, I, Group>
+        hitbox_param: HitboxParam<'p, Group>,
+        excluded: Option<Entity>,
+    ) -> impl Iterator<Item = (Collider<'a, Group::Hurtbox>, Entity)> + use<'w, 's, 'a, 'p, I, Group>
     {
-        let hurtbox_filter = &mut self.hurtbox_filter;
+        let hurtbox_filter = &self.hurtbox_filter;
 
-        I::iter_hurtboxes_on_aabb(&mut self.index, aabb)
+        I::iter_hurtboxes_on_aabb(&self.index, aabb)
+            .filter(move |entity| Some(*entity) != excluded)
             .filter_map(|entity| {
                 let (shape, registry) = self.hurtboxes.get(entity).ok()?;
-                Some((Collider::new(&**shape, registry.current_position()), entity))
+                Some((
+                    Collider::from_isometry(&**shape, registry.current_isometry()),
+                    entity,
+                ))
             })
             .filter(move |(_, entity)| {
                 let hurtbox_param = Group::Filter::hurtbox_filter_param(*entity, hurtbox_filter);
                 Group::Filter::filter(hitbox_param, hurtbox_param)
             })
     }
+
+    fn iter_hurtboxes_on_swept_aabb<'a, 'p: 'a>(
+        &'a self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+        hitbox_param: HitboxParam<'p, Group>,
+        excluded: Option<Entity>,
+    ) -> impl Iterator<Item = (f32, Collider<'a, Group::Hurtbox>, Entity)> + use<'w, 's, 'a, 'p, I, Group>
+    {
+        let hurtbox_filter = &self.hurtbox_filter;
+
+        I::iter_hurtboxes_on_swept_aabb(&self.index, aabb, direction, distance)
+            .filter(move |(_, entity)| Some(*entity) != excluded)
+            .filter_map(|(distance, entity)| {
+                let (shape, registry) = self.hurtboxes.get(entity).ok()?;
+                Some((
+                    distance,
+                    Collider::from_isometry(&**shape, registry.current_isometry()),
+                    entity,
+                ))
+            })
+            .filter(move |(_, _, entity)| {
+                let hurtbox_param = Group::Filter::hurtbox_filter_param(*entity, hurtbox_filter);
+                Group::Filter::filter(hitbox_param, hurtbox_param)
+            })
+    }
+
+    /// Walks the broad phase along the `ray`, instead of iterating over the whole [`Aabb2d`] of it.
+    /// If `first_only` is true, stops as soon as no hurtbox further along the ray can be hit sooner,
+    /// and returns only the closest hit.
+    fn ray_hits(
+        &self,
+        ray: Ray2d,
+        max_distance: f32,
+        hitbox_param: HitboxParam<'_, Group>,
+        first_only: bool,
+        excluded: Option<Entity>,
+    ) -> Vec<RayHit<Entity>>
+    where
+        Point: ColliderInteraction<Group::Hurtbox>,
+    {
+        let mut visited = EntityHashSet::default();
+        let mut hits: Vec<RayHit<Entity>> = Vec::new();
+
+        for (entry_distance, entity) in
+            self.index
+                .query_ray(ray.origin, ray.direction, max_distance)
+        {
+            if first_only
+                && hits
+                    .first()
+                    .is_some_and(|hit| hit.distance < entry_distance)
+            {
+                break;
+            }
+            if Some(entity) == excluded || !visited.insert(entity) {
+                continue;
+            }
+            let Ok((shape, registry)) = self.hurtboxes.get(entity) else {
+                continue;
+            };
+            let hurtbox_param = Group::Filter::hurtbox_filter_param(entity, &self.hurtbox_filter);
+            if !Group::Filter::filter(hitbox_param, hurtbox_param) {
+                continue;
+            }
+
+            let hurtbox = Collider::from_isometry(&**shape, registry.current_isometry());
+            let Some((distance, normal, parts)) =
+                Collider::new(&Point, ray.origin).cast_parts(hurtbox, ray.direction, max_distance)
+            else {
+                continue;
+            };
+            let hit = RayHit {
+                distance,
+                point: ray.get_point(distance),
+                normal,
+                parts,
+                data: entity,
+            };
+
+            if !first_only {
+                hits.push(hit);
+            } else if hits
+                .first()
+                .is_none_or(|closest| distance < closest.distance)
+            {
+                hits.clear();
+                hits.push(hit);
+            }
+        }
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+}
+
+/// Same queries as the ones of [`SpatialQuery`], but through a shared reference,
+/// so one query can be used by many hitboxes at once, for example from a `par_iter`.
+impl<'w, 's, Group: SpatialIndexColliderGroup, I: IterHurtboxesOnAabb>
+    GenericSpatialIndexQuery<'w, 's, Group, I>
+{
+    pub fn intersect<'a, 'p: 'a>(
+        &'a self,
+        hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+        hitbox_param: HitboxParam<'p, Group>,
+    ) -> impl Iterator<Item = Entity> + use<'w, 's, 'a, 'p, I, Group> {
+        self.intersect_excluding(hitbox, hitbox_param, None)
+    }
+
+    pub fn cast<'a, 'p: 'a>(
+        &'a self,
+        hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+        offset_dir: Dir2,
+        offset_len: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl Iterator<Item = (f32, Dir2, ColliderParts, Entity)> + use<'w, 's, 'a, 'p, I, Group>
+    {
+        self.cast_excluding(hitbox, offset_dir, offset_len, hitbox_filter, None)
+    }
+
+    pub fn cast_first<'p>(
+        &self,
+        hitbox: Collider<'_, <Group as crate::ColliderGroup>::Hitbox>,
+        offset_dir: Dir2,
+        offset_len: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Option<(f32, Dir2, ColliderParts, Entity)> {
+        self.cast_first_excluding(hitbox, offset_dir, offset_len, hitbox_filter, None)
+    }
+
+    pub fn penetrate<'a, 'p: 'a>(
+        &'a self,
+        hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl Iterator<Item = (Penetration, Entity)> + use<'w, 's, 'a, 'p, I, Group>
+    where
+        Group::Hitbox: ColliderPenetration<Group::Hurtbox>,
+    {
+        self.penetrate_excluding(hitbox, hitbox_filter, None)
+    }
+
+    pub fn nearest<'a, 'p: 'a>(
+        &'a self,
+        hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+        radius: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Option<(Distance, Entity)>
+    where
+        Group::Hitbox: ColliderDistance<Group::Hurtbox>,
+    {
+        self.nearest_excluding(hitbox, radius, hitbox_filter, None)
+    }
+
+    pub fn ray_cast<'p>(
+        &self,
+        ray: Ray2d,
+        max_distance: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Option<RayHit<Entity>>
+    where
+        Point: ColliderInteraction<Group::Hurtbox>,
+    {
+        self.ray_cast_excluding(ray, max_distance, hitbox_filter, None)
+    }
+
+    pub fn ray_cast_all<'p>(
+        &self,
+        ray: Ray2d,
+        max_distance: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Vec<RayHit<Entity>>
+    where
+        Point: ColliderInteraction<Group::Hurtbox>,
+    {
+        self.ray_cast_all_excluding(ray, max_distance, hitbox_filter, None)
+    }
+
+    /// Same queries, but `entity` is never found by them.
+    /// Used by the hitbox that is a hurtbox of the same group too, so it doesn't collide with itself.
+    pub fn excluding(&self, entity: Entity) -> ExcludingSpatialIndexQuery<'_, 'w, 's, Group, I> {
+        ExcludingSpatialIndexQuery {
+            query: self,
+            entity,
+        }
+    }
 }
 
-impl<Group: SpatialIndexColliderGroup, I: IterHurtboxesOnAabb> SpatialQuery<Group>
-    for GenericSpatialIndexQuery<'_, '_, Group, I>
+impl<'w, 's, Group: SpatialIndexColliderGroup, I: IterHurtboxesOnAabb>
+    GenericSpatialIndexQuery<'w, 's, Group, I>
 {
-    type HurtboxData = Entity;
-    fn intersect<'a>(
-        &'a mut self,
+    fn intersect_excluding<'a, 'p: 'a>(
+        &'a self,
         hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
-        hitbox_param: <<Group as crate::ColliderGroup>::Filter as SpatialQueryFilter>::HitboxParam<
-            'a,
-        >,
-    ) -> impl Iterator<Item = Self::HurtboxData> + 'a {
+        hitbox_param: HitboxParam<'p, Group>,
+        excluded: Option<Entity>,
+    ) -> impl Iterator<Item = Entity> + use<'w, 's, 'a, 'p, I, Group> {
         let aabb = hitbox.bounding();
 
-        self.iter_hurtboxes_on_aabb(aabb, hitbox_param).filter_map(
+        self.iter_hurtboxes_on_aabb(aabb, hitbox_param, excluded).filter_map(
             move |(hurtbox, hurtbox_entity)| hitbox.intersect(hurtbox).then_some(hurtbox_entity),
         )
     }
 
-    fn cast<'a>(
-        &'a mut self,
+    fn cast_excluding<'a, 'p: 'a>(
+        &'a self,
         hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
         offset_dir: Dir2,
         offset_len: f32,
-        hitbox_filter: <<Group as crate::ColliderGroup>::Filter as SpatialQueryFilter>::HitboxParam<
-            'a,
-        >,
-    ) -> impl Iterator<Item = (f32, Dir2, Self::HurtboxData)> + 'a {
-        let offset = offset_dir * offset_len;
-
-        let aabb1 = hitbox.bounding();
-        let aabb2 = Aabb2d {
-            min: aabb1.min + offset,
-            max: aabb1.max + offset,
-        };
-        let aabb = aabb1.merge(&aabb2);
-
-        self.iter_hurtboxes_on_aabb(aabb, hitbox_filter)
+        hitbox_filter: HitboxParam<'p, Group>,
+        excluded: Option<Entity>,
+    ) -> impl Iterator<Item = (f32, Dir2, ColliderParts, Entity)> + use<'w, 's, 'a, 'p, I, Group>
+    {
+        let aabb = hitbox.bounding();
+
+        self.iter_hurtboxes_on_swept_aabb(aabb, offset_dir, offset_len, hitbox_filter, excluded)
+            .filter_map(move |(_, other, data)| {
+                hitbox
+                    .cast_parts(other, offset_dir, offset_len)
+                    .map(|(dist, norm, parts)| (dist, norm, parts, data))
+            })
+    }
+
+    fn cast_first_excluding<'p>(
+        &self,
+        hitbox: Collider<'_, <Group as crate::ColliderGroup>::Hitbox>,
+        offset_dir: Dir2,
+        offset_len: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+        excluded: Option<Entity>,
+    ) -> Option<(f32, Dir2, ColliderParts, Entity)> {
+        let aabb = hitbox.bounding();
+        let mut first: Option<(f32, Dir2, ColliderParts, Entity)> = None;
+
+        for (enter_distance, other, data) in
+            self.iter_hurtboxes_on_swept_aabb(aabb, offset_dir, offset_len, hitbox_filter, excluded)
+        {
+            // Hurtboxes entered further along can't be hit any sooner
+            if first.is_some_and(|(distance, ..)| distance < enter_distance) {
+                break;
+            }
+
+            let Some((distance, normal, parts)) = hitbox.cast_parts(other, offset_dir, offset_len)
+            else {
+                continue;
+            };
+            if first.is_none_or(|(min_distance, ..)| distance <= min_distance) {
+                first = Some((distance, normal, parts, data));
+            }
+        }
+
+        first
+    }
+
+    fn penetrate_excluding<'a, 'p: 'a>(
+        &'a self,
+        hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+        hitbox_filter: HitboxParam<'p, Group>,
+        excluded: Option<Entity>,
+    ) -> impl Iterator<Item = (Penetration, Entity)> + use<'w, 's, 'a, 'p, I, Group>
+    where
+        Group::Hitbox: ColliderPenetration<Group::Hurtbox>,
+    {
+        let aabb = hitbox.bounding();
+
+        self.iter_hurtboxes_on_aabb(aabb, hitbox_filter, excluded)
             .filter_map(move |(other, data)| {
                 hitbox
-                    .cast(other, offset_dir, offset_len)
-                    .map(|(dist, norm)| (dist, norm, data))
+                    .penetration(other)
+                    .map(|penetration| (penetration, data))
             })
     }
+
+    fn nearest_excluding<'a, 'p: 'a>(
+        &'a self,
+        hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+        radius: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+        excluded: Option<Entity>,
+    ) -> Option<(Distance, Entity)>
+    where
+        Group::Hitbox: ColliderDistance<Group::Hurtbox>,
+    {
+        let aabb = hitbox.bounding().grow(Vec2::splat(radius));
+
+        self.iter_hurtboxes_on_aabb(aabb, hitbox_filter, excluded)
+            .filter_map(|(other, data)| {
+                let distance = hitbox.distance(other)?;
+                (distance.distance <= radius).then_some((distance, data))
+            })
+            .min_by(|(a, _), (b, _)| a.distance.total_cmp(&b.distance))
+    }
+
+    fn ray_cast_excluding(
+        &self,
+        ray: Ray2d,
+        max_distance: f32,
+        hitbox_filter: HitboxParam<'_, Group>,
+        excluded: Option<Entity>,
+    ) -> Option<RayHit<Entity>>
+    where
+        Point: ColliderInteraction<Group::Hurtbox>,
+    {
+        self.ray_hits(ray, max_distance, hitbox_filter, true, excluded)
+            .pop()
+    }
+
+    fn ray_cast_all_excluding(
+        &self,
+        ray: Ray2d,
+        max_distance: f32,
+        hitbox_filter: HitboxParam<'_, Group>,
+        excluded: Option<Entity>,
+    ) -> Vec<RayHit<Entity>>
+    where
+        Point: ColliderInteraction<Group::Hurtbox>,
+    {
+        self.ray_hits(ray, max_distance, hitbox_filter, false, excluded)
+    }
+}
+
+/// Implements [`SpatialQuery`] for `$query` by forwarding to the queries
+/// of the [`GenericSpatialIndexQuery`] given by `$generic`, which never find the `$excluded` entity.
+macro_rules! impl_spatial_query {
+    ($query:ty, |$self:ident| $generic:expr, $excluded:expr) => {
+        impl<Group: SpatialIndexColliderGroup, I: IterHurtboxesOnAabb> SpatialQuery<Group>
+            for $query
+        {
+            type HurtboxData = Entity;
+
+            fn intersect<'a, 'p: 'a>(
+                &'a mut $self,
+                hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+                hitbox_filter: HitboxParam<'p, Group>,
+            ) -> impl Iterator<Item = Self::HurtboxData> + 'a {
+                $generic.intersect_excluding(hitbox, hitbox_filter, $excluded)
+            }
+
+            fn cast<'a, 'p: 'a>(
+                &'a mut $self,
+                hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+                offset_dir: Dir2,
+                offset_len: f32,
+                hitbox_filter: HitboxParam<'p, Group>,
+            ) -> impl Iterator<Item = (f32, Dir2, ColliderParts, Self::HurtboxData)> + 'a {
+                $generic.cast_excluding(hitbox, offset_dir, offset_len, hitbox_filter, $excluded)
+            }
+
+            fn cast_first<'p>(
+                &mut $self,
+                hitbox: Collider<'_, <Group as crate::ColliderGroup>::Hitbox>,
+                offset_dir: Dir2,
+                offset_len: f32,
+                hitbox_filter: HitboxParam<'p, Group>,
+            ) -> Option<(f32, Dir2, ColliderParts, Self::HurtboxData)> {
+                $generic.cast_first_excluding(
+                    hitbox,
+                    offset_dir,
+                    offset_len,
+                    hitbox_filter,
+                    $excluded,
+                )
+            }
+
+            fn penetrate<'a, 'p: 'a>(
+                &'a mut $self,
+                hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+                hitbox_filter: HitboxParam<'p, Group>,
+            ) -> impl Iterator<Item = (Penetration, Self::HurtboxData)> + 'a
+            where
+                Group::Hitbox: ColliderPenetration<Group::Hurtbox>,
+            {
+                $generic.penetrate_excluding(hitbox, hitbox_filter, $excluded)
+            }
+
+            fn nearest<'a, 'p: 'a>(
+                &'a mut $self,
+                hitbox: Collider<'a, <Group as crate::ColliderGroup>::Hitbox>,
+                radius: f32,
+                hitbox_filter: HitboxParam<'p, Group>,
+            ) -> Option<(Distance, Self::HurtboxData)>
+            where
+                Group::Hitbox: ColliderDistance<Group::Hurtbox>,
+            {
+                $generic.nearest_excluding(hitbox, radius, hitbox_filter, $excluded)
+            }
+
+            fn ray_cast<'p>(
+                &mut $self,
+                ray: Ray2d,
+                max_distance: f32,
+                hitbox_filter: HitboxParam<'p, Group>,
+            ) -> Option<RayHit<Self::HurtboxData>>
+            where
+                Point: ColliderInteraction<Group::Hurtbox>,
+            {
+                $generic.ray_cast_excluding(ray, max_distance, hitbox_filter, $excluded)
+            }
+
+            fn ray_cast_all<'p>(
+                &mut $self,
+                ray: Ray2d,
+                max_distance: f32,
+                hitbox_filter: HitboxParam<'p, Group>,
+            ) -> Vec<RayHit<Self::HurtboxData>>
+            where
+                Point: ColliderInteraction<Group::Hurtbox>,
+            {
+                $generic.ray_cast_all_excluding(ray, max_distance, hitbox_filter, $excluded)
+            }
+        }
+    };
+}
+
+impl_spatial_query!(
+    GenericSpatialIndexQuery<'_, '_, Group, I>,
+    |self| self,
+    None
+);
+
+/// Spatial index query that never finds one entity, returned by [`GenericSpatialIndexQuery::excluding`].
+pub struct ExcludingSpatialIndexQuery<
+    'q,
+    'w,
+    's,
+    Group: SpatialIndexColliderGroup,
+    I: IterHurtboxesOnAabb,
+> {
+    query: &'q GenericSpatialIndexQuery<'w, 's, Group, I>,
+    entity: Entity,
 }
+
+impl_spatial_query!(
+    ExcludingSpatialIndexQuery<'_, '_, '_, Group, I>,
+    |self| self.query,
+    Some(self.entity)
+);
@@ -1,121 +1,244 @@
-use bevy::ecs::entity::MapEntities;
-use bevy::math::bounding::Aabb2d;
-use bevy::prelude::*;
-use bevy::utils::HashMap;
-
-pub type Chunk = Vec<Entity>;
-
-#[derive(Resource, Reflect)]
-pub struct SpatialIndex<Group> {
-    chunks: HashMap<IVec2, Chunk>,
-    pixels_per_chunk: f32,
-    marker: std::marker::PhantomData<fn() -> Group>,
+use super::broad_phase::{BroadPhase, StaticBvh};
+use crate::ColliderGroup;
+use bevy::{
+    ecs::entity::{EntityHashMap, MapEntities},
+    math::bounding::{Aabb2d, AabbCast2d, BoundingVolume, IntersectsVolume, RayCast2d},
+    prelude::*,
+};
+
+pub use super::broad_phase::Chunk;
+
+/// Stores hurtboxes of the group.
+/// Moving hurtboxes are stored in the [`ColliderGroup::BroadPhase`],
+/// [`StaticHurtbox`](super::components::StaticHurtbox)es are baked into the [`StaticBvh`].
+///
+/// Dereferences to the broad phase. Inherent query methods go through both storages.
+/// Reflected if the broad phase is, static hurtboxes are not reflected.
+#[derive(Resource, Reflect, Deref, DerefMut)]
+pub struct SpatialIndex<Group: ColliderGroup> {
+    #[deref]
+    broad_phase: Group::BroadPhase,
+    #[reflect(ignore)]
+    static_hurtboxes: StaticHurtboxes,
 }
 
-impl<Group> Default for SpatialIndex<Group> {
+/// Static hurtboxes, baked once per frame if any of them were inserted or removed.
+#[derive(Debug, Clone, Default)]
+struct StaticHurtboxes {
+    baked: StaticBvh,
+    /// Inserted since the last bake
+    pending: Vec<(Entity, Aabb2d)>,
+    /// All static hurtboxes currently in the index.
+    /// Entities of `baked` that are not in it were removed since the last bake.
+    entities: EntityHashMap<Aabb2d>,
+    needs_baking: bool,
+}
+
+impl<Group: ColliderGroup<BroadPhase: Default>> Default for SpatialIndex<Group> {
     fn default() -> Self {
-        Self::new(Self::PIXELS_PER_CHUNK_DEFAULT)
+        Self::new(default())
     }
 }
 
-impl<Group> MapEntities for SpatialIndex<Group> {
+impl<Group: ColliderGroup> MapEntities for SpatialIndex<Group> {
     fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
-        for (_, chunk) in self.chunks.iter_mut() {
-            for entity in chunk.iter_mut() {
-                *entity = entity_mapper.map_entity(*entity);
-            }
+        self.broad_phase.map_entities(entity_mapper);
+
+        let static_hurtboxes = &mut self.static_hurtboxes;
+        static_hurtboxes.baked.map_entities(entity_mapper);
+        for (entity, _) in static_hurtboxes.pending.iter_mut() {
+            *entity = entity_mapper.map_entity(*entity);
         }
+        static_hurtboxes.entities = std::mem::take(&mut static_hurtboxes.entities)
+            .into_iter()
+            .map(|(entity, aabb)| (entity_mapper.map_entity(entity), aabb))
+            .collect();
     }
 }
 
-/// Coordinates of the chunk in which the `global` lies in
-fn global_to_chunk(pixels_per_chunk: f32, global: Vec2) -> IVec2 {
-    IVec2 {
-        x: (global.x / pixels_per_chunk).floor() as i32,
-        y: (global.y / pixels_per_chunk).floor() as i32,
+impl<Group: ColliderGroup> SpatialIndex<Group> {
+    pub fn new(broad_phase: Group::BroadPhase) -> Self {
+        SpatialIndex {
+            broad_phase,
+            static_hurtboxes: default(),
+        }
     }
-}
 
-/// Bottom left corner of the chunk
-fn chunk_to_global(pixels_per_chunk: f32, chunk: IVec2) -> Vec2 {
-    Vec2 {
-        x: chunk.x as f32 * pixels_per_chunk,
-        y: chunk.y as f32 * pixels_per_chunk,
+    #[inline]
+    pub fn broad_phase(&self) -> &Group::BroadPhase {
+        &self.broad_phase
     }
-}
 
-impl<Group> SpatialIndex<Group> {
-    pub const PIXELS_PER_CHUNK_DEFAULT: f32 = 100.;
-
-    /// Creates a new spacial index.
-    /// # Arguments
-    /// * `pixels_per_chunk` - Spacial index is divided into chunks of size `pixels_per_chunk`.
-    ///   Every hurtbox, Aabb2d of which is intersected with the chunk, is added to the chunk.
-    ///   This is done to reduce the number of collision checks, only neccessary chunks are iterated.
-    ///   Generally, this should match size of the colliders for best performance,
-    ///   but this really depends on lots of factors.   
-    pub fn new(pixels_per_chunk: f32) -> Self {
-        SpatialIndex {
-            chunks: HashMap::default(),
-            pixels_per_chunk,
-            marker: std::marker::PhantomData,
+    /// Adds a hurtbox that is never going to move.
+    /// It is queried right away, but is baked into the [`StaticBvh`] only by the [`bake`](Self::bake).
+    pub fn insert_static(&mut self, entity: Entity, aabb: Aabb2d) {
+        let static_hurtboxes = &mut self.static_hurtboxes;
+        static_hurtboxes.entities.insert(entity, aabb);
+        static_hurtboxes.pending.push((entity, aabb));
+        static_hurtboxes.needs_baking = true;
+    }
+
+    /// Removes the hurtbox from the static storage if it is there, from the broad phase otherwise.
+    pub fn remove(&mut self, entity: Entity, aabb: Aabb2d) {
+        let static_hurtboxes = &mut self.static_hurtboxes;
+        if static_hurtboxes.entities.remove(&entity).is_none() {
+            self.broad_phase.remove(entity, aabb);
+            return;
         }
+        static_hurtboxes
+            .pending
+            .retain(|(pending, _)| *pending != entity);
+        static_hurtboxes.needs_baking = true;
     }
 
-    /// Coordinates of the chunk in which the `global` lies in.
-    #[inline]
-    pub fn global_to_chunk(&self, global: Vec2) -> IVec2 {
-        global_to_chunk(self.pixels_per_chunk, global)
+    /// Iterates over all the hurtboxes in the index, both static and moving ones.
+    pub fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.broad_phase
+            .iter_entities()
+            .chain(self.static_hurtboxes.entities.keys().copied())
     }
 
-    /// Bottom left corner of the chunk.
     #[inline]
-    pub fn chunk_to_global(&self, chunk: IVec2) -> Vec2 {
-        chunk_to_global(self.pixels_per_chunk, chunk)
+    pub fn is_static(&self, entity: Entity) -> bool {
+        self.static_hurtboxes.entities.contains_key(&entity)
     }
 
-    /// The size of the chunk in pixels.
+    /// True if static hurtboxes were inserted or removed since the last [`bake`](Self::bake).
     #[inline]
-    pub fn pixels_per_chunk(&self) -> f32 {
-        self.pixels_per_chunk
+    pub fn needs_baking(&self) -> bool {
+        self.static_hurtboxes.needs_baking
     }
 
-    /// Iterates over all chunks that intersect with the given `aabb`.
-    pub fn iter_chunks_on_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = &Chunk> {
-        let min = self.global_to_chunk(aabb.min);
-        let max = self.global_to_chunk(aabb.max);
+    /// Rebuilds the [`StaticBvh`] from all the static hurtboxes.
+    pub fn bake(&mut self) {
+        let static_hurtboxes = &mut self.static_hurtboxes;
+        static_hurtboxes.baked = StaticBvh::build(
+            static_hurtboxes
+                .entities
+                .iter()
+                .map(|(&entity, &aabb)| (entity, aabb)),
+        );
+        static_hurtboxes.pending.clear();
+        static_hurtboxes.needs_baking = false;
+    }
 
-        (min.x..=max.x)
-            .flat_map(move |x| (min.y..=max.y).map(move |y| (x, y)))
-            .filter_map(|(x, y)| self.chunks.get(&IVec2::new(x, y)))
+    /// Baked static hurtboxes.
+    #[inline]
+    pub fn static_bvh(&self) -> &StaticBvh {
+        &self.static_hurtboxes.baked
     }
 
-    fn foreach_chunk_on_aabb_mut(&mut self, aabb: Aabb2d, mut f: impl FnMut(&mut Chunk)) {
-        let min = self.global_to_chunk(aabb.min);
-        let max = self.global_to_chunk(aabb.max);
+    /// Iterates over hurtboxes that may intersect with the `aabb`, see [`BroadPhase::query_aabb`].
+    pub fn query_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = Entity> + '_ {
+        self.broad_phase
+            .query_aabb(aabb)
+            .chain(self.static_hurtboxes.query_aabb(aabb))
+    }
 
-        for x in min.x..=max.x {
-            for y in min.y..=max.y {
-                let chunk = self.chunks.entry(IVec2::new(x, y)).or_default();
-                f(chunk);
-            }
-        }
+    /// Iterates over hurtboxes that may be hit by the ray, see [`BroadPhase::query_ray`].
+    pub fn query_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        let static_hurtboxes = &self.static_hurtboxes;
+        let ray = RayCast2d::new(origin, direction, max_distance);
+        merge_sorted(
+            self.broad_phase.query_ray(origin, direction, max_distance),
+            static_hurtboxes
+                .sorted_hits(
+                    static_hurtboxes
+                        .baked
+                        .query_ray(origin, direction, max_distance),
+                    |aabb| ray.aabb_intersection_at(aabb),
+                )
+                .into_iter(),
+        )
     }
 
-    pub(super) fn add_entity(&mut self, entity: Entity, aabb: Aabb2d) {
-        self.foreach_chunk_on_aabb_mut(aabb, |chunk| chunk.push(entity));
+    /// Iterates over hurtboxes that may be hit by the moving `aabb`, see [`BroadPhase::query_swept_aabb`].
+    pub fn query_swept_aabb(
+        &self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        let static_hurtboxes = &self.static_hurtboxes;
+        let center = aabb.center();
+        let local = Aabb2d {
+            min: aabb.min - center,
+            max: aabb.max - center,
+        };
+        let cast = AabbCast2d::new(local, center, direction, distance);
+        merge_sorted(
+            self.broad_phase.query_swept_aabb(aabb, direction, distance),
+            static_hurtboxes
+                .sorted_hits(
+                    static_hurtboxes
+                        .baked
+                        .query_swept_aabb(aabb, direction, distance),
+                    |aabb| cast.aabb_collision_at(*aabb),
+                )
+                .into_iter(),
+        )
     }
+}
 
-    pub(super) fn change_entity(&mut self, entity: Entity, old_aabb: Aabb2d, new_aabb: Aabb2d) {
-        self.remove_entity(entity, old_aabb);
-        self.add_entity(entity, new_aabb);
+impl StaticHurtboxes {
+    fn query_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = Entity> + '_ {
+        let pending = self
+            .pending
+            .iter()
+            .filter(move |(_, pending)| pending.intersects(&aabb))
+            .map(|(entity, _)| *entity);
+
+        self.baked
+            .query_aabb(aabb)
+            .filter(|entity| self.entities.contains_key(entity))
+            .chain(pending)
     }
 
-    pub(super) fn remove_entity(&mut self, entity: Entity, aabb: Aabb2d) {
-        self.foreach_chunk_on_aabb_mut(aabb, |chunk| {
-            if let Some(entity_index) = chunk.iter().position(|entry| *entry == entity) {
-                chunk.swap_remove(entity_index);
-            }
-        });
+    /// Merges `baked` hits, skipping the ones that were removed since the last bake,
+    /// with the pending hurtboxes `enter` returns distance for.
+    /// Pending hurtboxes are checked one by one, there are only a few of them between bakes.
+    fn sorted_hits(
+        &self,
+        baked: impl Iterator<Item = (f32, Entity)>,
+        enter: impl Fn(&Aabb2d) -> Option<f32>,
+    ) -> Vec<(f32, Entity)> {
+        let mut found: Vec<_> = baked
+            .filter(|(_, entity)| self.entities.contains_key(entity))
+            .collect();
+
+        if !self.pending.is_empty() {
+            found.extend(
+                self.pending
+                    .iter()
+                    .filter_map(|(entity, aabb)| Some((enter(aabb)?, *entity))),
+            );
+            found.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        }
+        found
     }
 }
+
+/// Merges two iterators sorted by distance into one, keeping the order.
+fn merge_sorted(
+    first: impl Iterator<Item = (f32, Entity)>,
+    second: impl Iterator<Item = (f32, Entity)>,
+) -> impl Iterator<Item = (f32, Entity)> {
+    let (mut first, mut second) = (first.peekable(), second.peekable());
+
+    std::iter::from_fn(move || {
+        let take_first = match (first.peek(), second.peek()) {
+            (Some((a, _)), Some((b, _))) => a <= b,
+            (first, _) => first.is_some(),
+        };
+        if take_first {
+            first.next()
+        } else {
+            second.next()
+        }
+    })
+}
@@ -1,5 +1,5 @@
 use super::{SpatialQueryFilter, SystemSpatialQueryFilter};
-use crate::{ColliderGroup, CollisionImplementation};
+use crate::{error::BumpErrors, ColliderGroup, CollisionImplementation};
 use bevy::{ecs::system::SystemParam, prelude::*};
 #[cfg(feature = "enumset_layer")]
 use enumset::{EnumSet, EnumSetType};
@@ -22,15 +22,20 @@ impl<Group: ColliderGroup<Implementation: LayeredImplementation<Group>>> Layered
 
 pub struct Layer<L: CollisionLayer + Send + Sync + 'static>(pub L);
 
+/// Colliders without the [`HitboxLayer`] or [`HurtboxLayer`] are reported to the [`BumpErrors`]
+/// once and are filtered out.
 impl<L: CollisionLayer + Send + Sync + 'static> SpatialQueryFilter for Layer<L> {
-    type HitboxParam<'a> = &'a L;
-    type HurtboxParam<'a> = &'a L;
+    type HitboxParam<'a> = Option<&'a L>;
+    type HurtboxParam<'a> = Option<&'a L>;
 
     fn filter(
         hitbox_data: Self::HitboxParam<'_>,
         hurtbox_data: Self::HurtboxParam<'_>,
     ) -> bool {
-        !hitbox_data.collides(hurtbox_data)
+        match (hitbox_data, hurtbox_data) {
+            (Some(hitbox_data), Some(hurtbox_data)) => !hitbox_data.collides(hurtbox_data),
+            _ => false,
+        }
     }
 }
 
@@ -42,28 +47,42 @@ pub struct HurtboxLayer<Group: LayeredColliderGroup>(pub Group::Layer);
 impl<Group: LayeredColliderGroup<Layer = L>, L: CollisionLayer + Send + Sync + 'static>
     SystemSpatialQueryFilter<Group> for Layer<L>
 {
-    type HitboxSystemParam = Query<'static, 'static, &'static HitboxLayer<Group>>;
-    type HurtboxSystemParam = Query<'static, 'static, &'static HurtboxLayer<Group>>;
+    type HitboxSystemParam = (
+        Query<'static, 'static, &'static HitboxLayer<Group>>,
+        Res<'static, BumpErrors>,
+    );
+    type HurtboxSystemParam = (
+        Query<'static, 'static, &'static HurtboxLayer<Group>>,
+        Res<'static, BumpErrors>,
+    );
 
     fn hitbox_filter_param<'a>(
         hitbox: Entity,
-        system_param: &'a mut <Self::HitboxSystemParam as SystemParam>::Item<'_, '_>,
+        (layers, errors): &'a <Self::HitboxSystemParam as SystemParam>::Item<'_, '_>,
     ) -> Self::HitboxParam<'a> {
-        system_param.get(hitbox).expect(
-            "Implementation must guaratee that `hitbox_filter_param` only called for entities that pass filter `SystemSpatialQueryFilter::HitboxQueryFilter`"
-        )
+        layer_or_report(hitbox, layers, errors)
     }
 
     fn hurtbox_filter_param<'a>(
         hurtbox: Entity,
-        system_param: &'a mut <Self::HurtboxSystemParam as SystemParam>::Item<'_, '_>,
+        (layers, errors): &'a <Self::HurtboxSystemParam as SystemParam>::Item<'_, '_>,
     ) -> Self::HurtboxParam<'a> {
-        system_param.get(hurtbox).expect(
-            "Implementation must guaratee that `hurtbox_filter_param` only called for entities that pass filter `SystemSpatialQueryFilter::HurtboxQueryFilter`"
-        )
+        layer_or_report(hurtbox, layers, errors)
     }
 }
 
+fn layer_or_report<'a, C: Component + std::ops::Deref>(
+    entity: Entity,
+    layers: &'a Query<&C>,
+    errors: &BumpErrors,
+) -> Option<&'a C::Target> {
+    let Ok(layer) = layers.get(entity) else {
+        errors.report_missing_filter_component::<C>(entity);
+        return None;
+    };
+    Some(&**layer)
+}
+
 pub trait CollisionLayer {
     fn collides(&self, other: &Self) -> bool;
 }
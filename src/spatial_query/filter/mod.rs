@@ -15,20 +15,27 @@ pub trait SpatialQueryFilter: Send + Sync + 'static {
     ) -> bool;
 }
 
+/// System params of the filter are read only and are only borrowed immutably,
+/// so queries that use the filter can run in parallel.
 pub trait SystemSpatialQueryFilter<Group>: SpatialQueryFilter {
     type HitboxSystemParam: ReadOnlySystemParam;
     type HurtboxSystemParam: ReadOnlySystemParam;
 
     fn hitbox_filter_param<'a>(
         hitbox: Entity,
-        system_param: &'a mut SystemParamItem<Self::HitboxSystemParam>
+        system_param: &'a SystemParamItem<Self::HitboxSystemParam>,
     ) -> Self::HitboxParam<'a>;
     fn hurtbox_filter_param<'a>(
         hurtbox: Entity,
-        system_param: &'a mut SystemParamItem<Self::HurtboxSystemParam>,
+        system_param: &'a SystemParamItem<Self::HurtboxSystemParam>,
     ) -> Self::HurtboxParam<'a>;
 }
 
+pub type HitboxParam<'a, Group> =
+    <<Group as ColliderGroup>::Filter as SpatialQueryFilter>::HitboxParam<'a>;
+pub type HurtboxParam<'a, Group> =
+    <<Group as ColliderGroup>::Filter as SpatialQueryFilter>::HurtboxParam<'a>;
+
 pub type HitboxFilterSystemParam<Group> =
     <<Group as ColliderGroup>::Filter as SystemSpatialQueryFilter<Group>>::HitboxSystemParam;
 pub type HurtboxFilterSystemParam<Group> =
@@ -19,11 +19,11 @@ impl<Group: ColliderGroup> SystemSpatialQueryFilter<Group> for Monitorable {
     type HitboxSystemParam = ();
     type HurtboxSystemParam = Query<'static, 'static, &'static HurtboxMonitorable<Group>>;
 
-    fn hitbox_filter_param<'a>(_hitbox: Entity, _system_param: &mut ()) -> () {}
+    fn hitbox_filter_param<'a>(_hitbox: Entity, _system_param: &()) {}
 
     fn hurtbox_filter_param(
         hurtbox: Entity,
-        system_param: &mut <Self::HurtboxSystemParam as SystemParam>::Item<'_, '_>,
+        system_param: &<Self::HurtboxSystemParam as SystemParam>::Item<'_, '_>,
     ) -> bool {
         system_param.get(hurtbox).copied().unwrap_or_default().0
     }
@@ -21,12 +21,12 @@ impl<Group: ColliderGroup> SystemSpatialQueryFilter<Group> for Monitoring {
 
     fn hitbox_filter_param(
         hitbox: Entity,
-        system_param: &mut <Self::HitboxSystemParam as SystemParam>::Item<'_, '_>,
+        system_param: &<Self::HitboxSystemParam as SystemParam>::Item<'_, '_>,
     ) -> bool {
         system_param.get(hitbox).copied().unwrap_or_default().0
     }
 
-    fn hurtbox_filter_param(_hurtbox: Entity, _system_param: &mut ()) -> () {}
+    fn hurtbox_filter_param(_hurtbox: Entity, _system_param: &()) {}
 }
 
 #[derive(Component, Deref)]
@@ -1,29 +1,119 @@
-use crate::{collider::Collider, ColliderGroup};
-use bevy::math::Dir2;
-use filter::SpatialQueryFilter;
+use crate::{
+    bounded::Point,
+    collider::{
+        Collider, ColliderDistance, ColliderInteraction, ColliderParts, ColliderPenetration,
+        Distance, Penetration,
+    },
+    ColliderGroup,
+};
+use bevy::math::{Dir2, Ray2d, Vec2};
+use filter::HitboxParam;
 
 pub mod filter;
 
+/// Hurtbox that was hit by a ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit<Data> {
+    /// Distance along the ray from its origin to the hit
+    pub distance: f32,
+    /// Point at which ray hit the hurtbox
+    pub point: Vec2,
+    /// Normal of the hurtbox surface at the `point`
+    pub normal: Dir2,
+    /// Part of the hurtbox that was hit, `hitbox` part is always 0
+    pub parts: ColliderParts,
+    /// [`SpatialQuery::HurtboxData`] of the hurtbox that was hit
+    pub data: Data,
+}
+
 pub trait SpatialQuery<Group: ColliderGroup> {
     type HurtboxData;
 
     /// Should return only colliders that are potentially colliding with actor,
     /// and only thing that could prevent collision is stored in collider itself (usually it`s only position)
-    fn intersect<'a>(
+    ///
+    /// `hitbox_filter` may outlive the borrow of the query,
+    /// so the same filter parameter can be reused across several queries.
+    fn intersect<'a, 'p: 'a>(
         &'a mut self,
         hitbox: Collider<'a, Group::Hitbox>,
-        hitbox_filter: <Group::Filter as SpatialQueryFilter>::HitboxParam<'a>,
+        hitbox_filter: HitboxParam<'p, Group>,
     ) -> impl Iterator<Item = Self::HurtboxData> + 'a;
 
     /// Returns iterator over all the collisions that happened.
     /// f32 is distance in the direction of offset_dir. It is always less than offset_len.
     /// Dir2 is normal of the collision.
-    fn cast<'a>(
+    /// [`ColliderParts`] are the parts of compound shapes that collided.
+    fn cast<'a, 'p: 'a>(
         &'a mut self,
         hitbox: Collider<'a, Group::Hitbox>,
         offset_dir: Dir2,
         offset_len: f32,
-        hitbox_filter: <Group::Filter as SpatialQueryFilter>::HitboxParam<'a>,
-    ) -> impl Iterator<Item = (f32, Dir2, Self::HurtboxData)> + 'a;
-}
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl Iterator<Item = (f32, Dir2, ColliderParts, Self::HurtboxData)> + 'a;
+
+    /// Returns the closest of the collisions returned by [`SpatialQuery::cast`].
+    /// Implementors are encouraged to stop searching as soon as no closer collision is possible.
+    fn cast_first<'p>(
+        &mut self,
+        hitbox: Collider<'_, Group::Hitbox>,
+        offset_dir: Dir2,
+        offset_len: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Option<(f32, Dir2, ColliderParts, Self::HurtboxData)> {
+        let mut first: Option<(f32, Dir2, ColliderParts, Self::HurtboxData)> = None;
+        for collision in self.cast(hitbox, offset_dir, offset_len, hitbox_filter) {
+            if first
+                .as_ref()
+                .is_none_or(|(min_distance, ..)| collision.0 <= *min_distance)
+            {
+                first = Some(collision);
+            }
+        }
+        first
+    }
+
+    /// Returns iterator over all the hurtboxes that `hitbox` overlaps,
+    /// together with the [`Penetration`] of `hitbox` into them.
+    fn penetrate<'a, 'p: 'a>(
+        &'a mut self,
+        hitbox: Collider<'a, Group::Hitbox>,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> impl Iterator<Item = (Penetration, Self::HurtboxData)> + 'a
+    where
+        Group::Hitbox: ColliderPenetration<Group::Hurtbox>;
 
+    /// Returns the hurtbox closest to the `hitbox`, that is no farther than `radius` from it.
+    /// Overlapping hurtboxes have negative distance, so the deepest of them is returned.
+    fn nearest<'a, 'p: 'a>(
+        &'a mut self,
+        hitbox: Collider<'a, Group::Hitbox>,
+        radius: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Option<(Distance, Self::HurtboxData)>
+    where
+        Group::Hitbox: ColliderDistance<Group::Hurtbox>;
+
+    /// Returns the closest hurtbox hit by the `ray`, no farther than `max_distance` from its origin.
+    /// `hitbox_filter` is used as if the ray was a hitbox.
+    fn ray_cast<'p>(
+        &mut self,
+        ray: Ray2d,
+        max_distance: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Option<RayHit<Self::HurtboxData>>
+    where
+        Point: ColliderInteraction<Group::Hurtbox>;
+
+    /// Returns all the hurtboxes hit by the `ray`, no farther than `max_distance` from its origin,
+    /// sorted by distance.
+    /// `hitbox_filter` is used as if the ray was a hitbox.
+    fn ray_cast_all<'p>(
+        &mut self,
+        ray: Ray2d,
+        max_distance: f32,
+        hitbox_filter: HitboxParam<'p, Group>,
+    ) -> Vec<RayHit<Self::HurtboxData>>
+    where
+        Point: ColliderInteraction<Group::Hurtbox>;
+}
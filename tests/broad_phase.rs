@@ -0,0 +1,280 @@
+use bevy::{
+    ecs::system::RunSystemOnce,
+    math::bounding::{Aabb2d, IntersectsVolume, RayCast2d},
+    prelude::*,
+};
+use bevy_bump::{
+    collider::Collider,
+    prelude::*,
+    spatial_index::{
+        broad_phase::{BroadPhase, StaticBvh},
+        spatial_index::SpatialIndex,
+    },
+};
+
+/// Deterministic pseudo random numbers in `0..1`.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Aabb somewhere in the -500..500 square, sizes vary from tiny to huge.
+    fn aabb(&mut self) -> Aabb2d {
+        let center = Vec2::new(self.next(), self.next()) * 1000. - 500.;
+        let half_size = Vec2::new(self.next(), self.next()) * self.next().powi(4) * 200. + 0.5;
+        Aabb2d::new(center, half_size)
+    }
+}
+
+/// Inserts, moves and removes entities, then checks that queries find everything they should.
+fn check_broad_phase(mut broad_phase: impl BroadPhase) {
+    let mut random = Random(7);
+    let mut stored: Vec<(Entity, Aabb2d)> = (0..300)
+        .map(|i| (Entity::from_raw(i), random.aabb()))
+        .collect();
+    for &(entity, aabb) in &stored {
+        broad_phase.insert(entity, aabb);
+    }
+
+    for (entity, aabb) in stored.iter_mut().step_by(2) {
+        let moved = random.aabb();
+        broad_phase.update(*entity, *aabb, moved);
+        *aabb = moved;
+    }
+    for (entity, aabb) in stored.drain(..100) {
+        broad_phase.remove(entity, aabb);
+    }
+
+    for _ in 0..50 {
+        let query = random.aabb();
+        let found: Vec<Entity> = broad_phase.query_aabb(query).collect();
+        for (entity, aabb) in &stored {
+            if aabb.intersects(&query) {
+                assert!(found.contains(entity), "{entity} not found on aabb");
+            }
+        }
+        for removed in 0..100 {
+            assert!(!found.contains(&Entity::from_raw(removed)));
+        }
+
+        let origin = query.min;
+        let direction = Dir2::from_xy(random.next() - 0.5, random.next() - 0.5).unwrap();
+        let found: Vec<(f32, Entity)> = broad_phase.query_ray(origin, direction, 700.).collect();
+        assert!(found.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+        let ray = RayCast2d::new(origin, direction, 700.);
+        for (entity, aabb) in &stored {
+            let Some(distance) = ray.aabb_intersection_at(aabb) else {
+                continue;
+            };
+            assert!(
+                found
+                    .iter()
+                    .any(|(entry, found)| found == entity && *entry <= distance + 1e-3),
+                "{entity} not found on ray"
+            );
+        }
+    }
+}
+
+#[test]
+fn chunk_grid_finds_all_entities() {
+    check_broad_phase(ChunkGrid::new(50.));
+}
+
+#[test]
+fn chunk_grid_only_keeps_occupied_chunks() {
+    let mut grid = ChunkGrid::new(10.);
+    let (first, second) = (Entity::from_raw(0), Entity::from_raw(1));
+    let first_aabb = Aabb2d::new(Vec2::new(5., 5.), Vec2::splat(8.));
+    let second_aabb = Aabb2d::new(Vec2::new(5., 5.), Vec2::splat(2.));
+    grid.insert(first, first_aabb);
+    grid.insert(second, second_aabb);
+    assert_eq!(grid.chunk_count(), 9);
+
+    // Moving within the same chunks changes nothing
+    let nudged = Aabb2d::new(Vec2::new(6., 6.), Vec2::splat(8.));
+    grid.update(first, first_aabb, nudged);
+    assert_eq!(grid.chunk_count(), 9);
+
+    // Chunks that were left are dropped, the shared chunk still has both entities
+    let moved = Aabb2d::new(Vec2::new(10., 5.), Vec2::splat(2.));
+    grid.update(first, nudged, moved);
+    assert_eq!(grid.chunk_count(), 2);
+    let mut found: Vec<Entity> = grid.query_aabb(second_aabb).collect();
+    found.sort();
+    assert_eq!(found, [first, second]);
+    let right_chunk = Aabb2d::new(Vec2::new(15., 5.), Vec2::splat(1.));
+    assert_eq!(grid.query_aabb(right_chunk).collect::<Vec<_>>(), [first]);
+
+    grid.remove(first, moved);
+    grid.remove(second, second_aabb);
+    assert_eq!(grid.chunk_count(), 0);
+}
+
+#[test]
+fn aabb_tree_finds_all_entities() {
+    check_broad_phase(AabbTree::default());
+}
+
+#[test]
+fn aabb_tree_stays_balanced() {
+    let mut tree = AabbTree::new(0.);
+    // Sorted insertion degrades unbalanced trees into lists
+    for i in 0..1024 {
+        let aabb = Aabb2d::new(Vec2::new(i as f32 * 10., 0.), Vec2::splat(1.));
+        tree.insert(Entity::from_raw(i), aabb);
+    }
+    assert!(tree.height() <= 20, "height is {}", tree.height());
+}
+
+#[test]
+fn hierarchical_grid_finds_all_entities() {
+    check_broad_phase(HierarchicalGrid::new(10., 4));
+}
+
+#[test]
+fn hierarchical_grid_stores_large_entity_in_few_chunks() {
+    let mut grid = HierarchicalGrid::new(10., 4);
+    let wall = Entity::from_raw(0);
+    let wall_aabb = Aabb2d::new(Vec2::ZERO, Vec2::splat(150.));
+    grid.insert(wall, wall_aabb);
+
+    let level = grid.level_of(wall_aabb);
+    assert_eq!(level, 3);
+    let chunks = grid.levels()[level]
+        .iter_chunks_on_aabb(wall_aabb)
+        .filter(|chunk| chunk.contains(&wall))
+        .count();
+    assert!(chunks <= 4, "wall is in {chunks} chunks");
+
+    // Small entity next to the wall stays in the smallest chunks
+    let crate_entity = Entity::from_raw(1);
+    let mut crate_aabb = Aabb2d::new(Vec2::new(-155., 0.), Vec2::splat(4.));
+    grid.insert(crate_entity, crate_aabb);
+    let moved = Aabb2d::new(Vec2::new(-155., 40.), Vec2::splat(4.));
+    grid.update(crate_entity, crate_aabb, moved);
+    crate_aabb = moved;
+    assert_eq!(grid.level_of(crate_aabb), 0);
+
+    let mut found: Vec<Entity> = grid.query_aabb(crate_aabb).collect();
+    found.sort();
+    found.dedup();
+    assert_eq!(found, [wall, crate_entity]);
+
+    grid.remove(wall, wall_aabb);
+    let mut found: Vec<Entity> = grid.query_aabb(crate_aabb).collect();
+    found.dedup();
+    assert_eq!(found, [crate_entity]);
+}
+
+#[test]
+fn loose_quadtree_finds_all_entities() {
+    check_broad_phase(LooseQuadtree::new(Vec2::ZERO, 400., 8));
+}
+
+#[test]
+fn static_bvh_finds_all_entities() {
+    let mut random = Random(11);
+    let stored: Vec<(Entity, Aabb2d)> = (0..300)
+        .map(|i| (Entity::from_raw(i), random.aabb()))
+        .collect();
+    let bvh = StaticBvh::build(stored.iter().copied());
+    assert_eq!(bvh.len(), stored.len());
+
+    for _ in 0..50 {
+        let query = random.aabb();
+        let mut found: Vec<Entity> = bvh.query_aabb(query).collect();
+        found.sort();
+        let mut expected: Vec<Entity> = stored
+            .iter()
+            .filter(|(_, aabb)| aabb.intersects(&query))
+            .map(|(entity, _)| *entity)
+            .collect();
+        expected.sort();
+        assert_eq!(found, expected);
+
+        let direction = Dir2::from_xy(random.next() - 0.5, random.next() - 0.5).unwrap();
+        let found: Vec<(f32, Entity)> = bvh.query_ray(query.min, direction, 700.).collect();
+        let ray = RayCast2d::new(query.min, direction, 700.);
+        let mut expected: Vec<(f32, Entity)> = stored
+            .iter()
+            .filter_map(|(entity, aabb)| Some((ray.aabb_intersection_at(aabb)?, *entity)))
+            .collect();
+        expected.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        assert_eq!(
+            found
+                .iter()
+                .map(|(distance, _)| *distance)
+                .collect::<Vec<_>>(),
+            expected
+                .iter()
+                .map(|(distance, _)| *distance)
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+struct TreeGroup;
+
+struct TreeImplementation;
+
+impl CollisionImplementation<TreeGroup> for TreeImplementation {}
+
+impl ColliderGroup for TreeGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = TreeImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = AabbTree;
+}
+
+#[test]
+fn broad_phase_is_selected_by_group() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        SpatialIndexPlugin::<TreeGroup>::with_broad_phase(AabbTree::new(5.)),
+    ));
+    let boss = app
+        .world_mut()
+        .spawn((
+            HurtboxShape::<TreeGroup>(Rectangle::new(2000., 2000.)),
+            Transform::from_xyz(1000., 0., 0.),
+        ))
+        .id();
+    let bullet = app
+        .world_mut()
+        .spawn((
+            HurtboxShape::<TreeGroup>(Rectangle::new(1., 1.)),
+            Transform::from_xyz(-3., 0., 0.),
+        ))
+        .id();
+    app.update();
+
+    assert_eq!(
+        app.world()
+            .resource::<SpatialIndex<TreeGroup>>()
+            .broad_phase()
+            .margin(),
+        5.
+    );
+
+    let mut found = app
+        .world_mut()
+        .run_system_once(|query: SpatialIndexQuery<TreeGroup>| {
+            let hitbox = Rectangle::new(2100., 2100.);
+            query
+                .intersect(Collider::new(&hitbox, Vec2::new(1000., 0.)), ())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+    found.sort();
+    assert_eq!(found, [boss, bullet]);
+}
@@ -0,0 +1,974 @@
+use bevy::{
+    prelude::*,
+    reflect::{DynamicStruct, ReflectRef},
+};
+use bevy_bump::prelude::*;
+
+const EPSILON: f32 = 1e-4;
+
+fn assert_cast(result: Option<(f32, Dir2)>, distance: f32, normal: Vec2) {
+    let (result_distance, result_normal) = result.expect("expected collision");
+    assert!(
+        (result_distance - distance).abs() < EPSILON,
+        "distance {result_distance} != {distance}"
+    );
+    assert!(
+        result_normal.abs_diff_eq(normal.normalize(), EPSILON),
+        "normal {result_normal:?} != {normal}"
+    );
+}
+
+#[test]
+fn point_rounded_rectangle_intersect_ignores_cut_corners() {
+    let rounded = RoundedRectangle {
+        rect: Rectangle::new(4., 4.),
+        radius: 1.,
+    };
+
+    assert!(Point.intersect(Vec2::new(1.9, 0.), &rounded, Vec2::ZERO));
+    assert!(Point.intersect(Vec2::new(1.5, 1.5), &rounded, Vec2::ZERO));
+    assert!(!Point.intersect(Vec2::new(1.9, 1.9), &rounded, Vec2::ZERO));
+    assert!(!Point.intersect(Vec2::new(12.5, 11.5), &rounded, Vec2::splat(10.)));
+}
+
+#[test]
+fn point_rounded_rectangle_cast_hits_corner() {
+    let rounded = RoundedRectangle {
+        rect: Rectangle::new(4., 4.),
+        radius: 1.,
+    };
+
+    // Passes by the rectangle bounds, but hits the rounded corner
+    let start = Vec2::new(-5., 1. + 0.5_f32.sqrt());
+    assert_cast(
+        Point.cast(start, &rounded, Vec2::ZERO, Dir2::X, 10.),
+        5. - 1. - 0.5_f32.sqrt(),
+        Vec2::new(-1., 1.),
+    );
+
+    // Crosses the rectangle bounds near the corner, but misses the rounded corner
+    let start = Vec2::new(-3., 0.8);
+    let direction = Dir2::new(Vec2::ONE).unwrap();
+    assert!(Point
+        .cast(start, &rounded, Vec2::ZERO, direction, 10.)
+        .is_none());
+}
+
+#[test]
+fn partially_overlapping_rectangles_intersect() {
+    let small = Rectangle::new(4., 4.);
+    let large = Rectangle::new(10., 10.);
+
+    assert!(small.intersect(Vec2::new(6., 0.), &large, Vec2::ZERO));
+    assert!(large.intersect(Vec2::ZERO, &small, Vec2::new(6., 6.)));
+    assert!(small.intersect(Vec2::ZERO, &large, Vec2::ZERO));
+    assert!(!small.intersect(Vec2::new(8., 0.), &large, Vec2::ZERO));
+}
+
+#[test]
+fn point_capsule_intersect() {
+    let capsule = Capsule2d::new(1., 4.);
+    let position = Vec2::new(10., 10.);
+
+    assert!(Point.intersect(Vec2::new(10., 12.9), &capsule, position));
+    assert!(Point.intersect(Vec2::new(10.9, 10.), &capsule, position));
+    assert!(!Point.intersect(Vec2::new(10.9, 12.9), &capsule, position));
+    assert!(capsule.intersect(position, &Point, Vec2::new(10., 7.1)));
+}
+
+#[test]
+fn capsule_falls_on_rectangle() {
+    let capsule = Capsule2d::new(1., 2.);
+    let rect = Rectangle::new(10., 2.);
+
+    // Bottom of the capsule is at 7, top of the rectangle is at 1
+    assert_cast(
+        capsule.cast(Vec2::new(0., 9.), &rect, Vec2::ZERO, Dir2::NEG_Y, 10.),
+        6.,
+        Vec2::Y,
+    );
+    assert_cast(
+        rect.cast(Vec2::ZERO, &capsule, Vec2::new(0., 9.), Dir2::Y, 10.),
+        6.,
+        Vec2::NEG_Y,
+    );
+    assert!(capsule
+        .cast(Vec2::new(0., 9.), &rect, Vec2::ZERO, Dir2::NEG_Y, 5.)
+        .is_none());
+}
+
+#[test]
+fn capsule_cap_hits_rectangle_edge() {
+    let capsule = Capsule2d::new(1., 2.);
+    let rect = Rectangle::new(2., 2.);
+
+    // Capsule falls next to the rectangle, so its cap touches the corner at (1, 1)
+    let x = 1. + 0.5_f32.sqrt();
+    let bottom_cap_y = 1. + 0.5_f32.sqrt();
+    assert_cast(
+        capsule.cast(Vec2::new(x, 10.), &rect, Vec2::ZERO, Dir2::NEG_Y, 20.),
+        10. - (bottom_cap_y + 1.),
+        Vec2::new(1., 1.),
+    );
+}
+
+#[test]
+fn capsule_hits_circle_side() {
+    let capsule = Capsule2d::new(1., 4.);
+    let circle = Circle::new(2.);
+
+    assert_cast(
+        capsule.cast(Vec2::new(-10., 0.), &circle, Vec2::ZERO, Dir2::X, 20.),
+        7.,
+        Vec2::NEG_X,
+    );
+    assert_cast(
+        circle.cast(Vec2::ZERO, &capsule, Vec2::new(-10., 0.), Dir2::NEG_X, 20.),
+        7.,
+        Vec2::X,
+    );
+    assert!(capsule.intersect(Vec2::new(-2.5, 2.9), &circle, Vec2::ZERO));
+    assert!(!circle.intersect(Vec2::ZERO, &capsule, Vec2::new(-2.5, 4.5)));
+}
+
+#[test]
+fn capsule_caps_collide() {
+    let capsule = Capsule2d::new(1., 2.);
+    let other = Capsule2d::new(0.5, 4.);
+
+    // Top of the capsule is at 2, bottom of the other is at 7
+    assert_cast(
+        capsule.cast(Vec2::ZERO, &other, Vec2::new(0., 9.5), Dir2::Y, 10.),
+        5.,
+        Vec2::NEG_Y,
+    );
+
+    // Caps meet at an angle
+    let offset = Vec2::splat(1.5) / 2.0_f32.sqrt();
+    let other_position = Vec2::new(offset.x, 1. + offset.y + 2. + 10.);
+    assert_cast(
+        capsule.cast(Vec2::ZERO, &other, other_position, Dir2::Y, 20.),
+        10.,
+        Vec2::new(-1., -1.),
+    );
+}
+
+#[test]
+fn capsule_rounded_rectangle() {
+    let capsule = Capsule2d::new(1., 2.);
+    let rounded = RoundedRectangle {
+        rect: Rectangle::new(4., 4.),
+        radius: 1.,
+    };
+
+    assert_cast(
+        capsule.cast(Vec2::new(10., 0.), &rounded, Vec2::ZERO, Dir2::NEG_X, 20.),
+        7.,
+        Vec2::X,
+    );
+    assert_cast(
+        rounded.cast(Vec2::ZERO, &capsule, Vec2::new(10., 0.), Dir2::X, 20.),
+        7.,
+        Vec2::NEG_X,
+    );
+    assert!(capsule.intersect(Vec2::new(0., 3.9), &rounded, Vec2::ZERO));
+    assert!(!rounded.intersect(Vec2::ZERO, &capsule, Vec2::new(0., 4.1)));
+}
+
+#[test]
+fn touching_capsule_slides_and_moves_away() {
+    let capsule = Capsule2d::new(1., 2.);
+    let rect = Rectangle::new(10., 2.);
+    let position = Vec2::new(0., 3.);
+
+    assert!(capsule
+        .cast(position, &rect, Vec2::ZERO, Dir2::X, 1.)
+        .is_none());
+    assert!(capsule
+        .cast(position, &rect, Vec2::ZERO, Dir2::Y, 1.)
+        .is_none());
+    assert_cast(
+        capsule.cast(position, &rect, Vec2::ZERO, Dir2::NEG_Y, 1.),
+        0.,
+        Vec2::Y,
+    );
+}
+
+fn slope() -> ConvexShape {
+    // Slope going up to the right, its surface lies on the line -x + 2y - 10 = 0
+    ConvexShape::new([Vec2::new(-10., 0.), Vec2::new(10., 0.), Vec2::new(10., 10.)]).unwrap()
+}
+
+#[test]
+fn convex_shape_validation() {
+    let clockwise = ConvexShape::new([
+        Vec2::new(0., 0.),
+        Vec2::new(0., 1.),
+        Vec2::new(1., 1.),
+        Vec2::new(1., 0.),
+    ])
+    .unwrap();
+    assert_eq!(
+        clockwise.vertices(),
+        [
+            Vec2::new(1., 0.),
+            Vec2::new(1., 1.),
+            Vec2::new(0., 1.),
+            Vec2::new(0., 0.),
+        ]
+    );
+    assert_eq!(clockwise.normals()[0], Dir2::X);
+
+    let with_collinear = ConvexShape::new([
+        Vec2::new(0., 0.),
+        Vec2::new(1., 0.),
+        Vec2::new(2., 0.),
+        Vec2::new(2., 2.),
+        Vec2::new(0., 0.),
+    ])
+    .unwrap();
+    assert_eq!(with_collinear.vertices().len(), 3);
+
+    assert_eq!(
+        ConvexShape::new([Vec2::ZERO, Vec2::X, Vec2::X]),
+        Err(ConvexShapeError::TooFewVertices)
+    );
+    assert_eq!(
+        ConvexShape::new([Vec2::ZERO, Vec2::X, Vec2::X * 2.]),
+        Err(ConvexShapeError::Degenerate)
+    );
+    assert_eq!(
+        ConvexShape::new([
+            Vec2::new(0., 0.),
+            Vec2::new(2., 0.),
+            Vec2::new(1., 0.5),
+            Vec2::new(1., 2.),
+        ]),
+        Err(ConvexShapeError::NotConvex)
+    );
+
+    let pentagram = (0..5).map(|i| {
+        let angle = i as f32 * std::f32::consts::TAU * 2. / 5.;
+        Vec2::from_angle(angle)
+    });
+    assert_eq!(
+        ConvexShape::new(pentagram),
+        Err(ConvexShapeError::NotConvex)
+    );
+
+    assert!(ConvexShape::try_from(RegularPolygon::new(1., 6)).is_ok());
+    assert!(ConvexShape::try_from(Rhombus::new(2., 1.)).is_ok());
+}
+
+#[test]
+fn edge_chain_needs_two_vertices() {
+    assert_eq!(
+        EdgeChain::try_new([Vec2::ZERO]),
+        Err(EdgeChainError::TooFewVertices)
+    );
+    assert_eq!(
+        EdgeChain::try_new([Vec2::ZERO, Vec2::X])
+            .unwrap()
+            .segments_count(),
+        1
+    );
+}
+
+#[test]
+fn validated_shapes_are_reflected_as_opaque_values() {
+    let convex = ConvexShape::try_from(Rectangle::new(2., 2.)).unwrap();
+    let chain = EdgeChain::new([Vec2::ZERO, Vec2::X]);
+    assert!(matches!(convex.reflect_ref(), ReflectRef::Opaque(_)));
+    assert!(matches!(chain.reflect_ref(), ReflectRef::Opaque(_)));
+    assert_eq!(ConvexShape::from_reflect(&convex), Some(convex.clone()));
+    assert_eq!(EdgeChain::from_reflect(&chain), Some(chain.clone()));
+
+    // Vertices can't be swapped without validating them
+    let mut vertices = DynamicStruct::default();
+    vertices.insert("vertices", vec![Vec2::ZERO]);
+    assert!(EdgeChain::from_reflect(&vertices).is_none());
+    assert!(ConvexShape::from_reflect(&vertices).is_none());
+    assert!(chain.clone().try_apply(&vertices).is_err());
+}
+
+#[test]
+fn convex_polygons_intersect_by_separating_axis() {
+    let triangle = ConvexShape::new([Vec2::ZERO, Vec2::new(4., 0.), Vec2::new(0., 4.)]).unwrap();
+    let other = ConvexShape::new([Vec2::ZERO, Vec2::new(2., 0.), Vec2::new(2., 2.)]).unwrap();
+
+    // Bounding boxes overlap, but the hypotenuse separates the shapes
+    assert!(!triangle.intersect(Vec2::ZERO, &other, Vec2::new(3., 3.)));
+    assert!(triangle.intersect(Vec2::ZERO, &other, Vec2::new(1.5, 1.5)));
+    assert!(!other.intersect(Vec2::new(3., 3.), &triangle, Vec2::ZERO));
+
+    assert!(!triangle.intersect(Vec2::ZERO, &Rectangle::new(2., 2.), Vec2::new(3.2, 3.2)));
+    assert!(Rectangle::new(2., 2.).intersect(Vec2::new(2.5, 2.5), &triangle, Vec2::ZERO));
+}
+
+#[test]
+fn shapes_fall_on_slope() {
+    let slope = slope();
+    let normal = Vec2::new(-1., 2.);
+
+    assert_cast(
+        Point.cast(Vec2::new(0., 20.), &slope, Vec2::ZERO, Dir2::NEG_Y, 20.),
+        15.,
+        normal,
+    );
+    assert_cast(
+        Circle::new(1.).cast(Vec2::new(0., 20.), &slope, Vec2::ZERO, Dir2::NEG_Y, 20.),
+        15. - 5.0_f32.sqrt() / 2.,
+        normal,
+    );
+    assert_cast(
+        Rectangle::new(2., 2.).cast(Vec2::new(0., 20.), &slope, Vec2::ZERO, Dir2::NEG_Y, 20.),
+        13.5,
+        normal,
+    );
+    assert_cast(
+        slope.cast(
+            Vec2::ZERO,
+            &Rectangle::new(2., 2.),
+            Vec2::new(0., 20.),
+            Dir2::Y,
+            20.,
+        ),
+        13.5,
+        -normal,
+    );
+}
+
+#[test]
+fn circle_hits_polygon_vertex() {
+    let slope = slope();
+    let circle = Circle::new(1.);
+
+    assert_cast(
+        circle.cast(Vec2::new(20., 5.), &slope, Vec2::ZERO, Dir2::NEG_X, 20.),
+        9.,
+        Vec2::X,
+    );
+    assert_cast(
+        circle.cast(Vec2::new(20., 10.5), &slope, Vec2::ZERO, Dir2::NEG_X, 20.),
+        10. - 0.75_f32.sqrt(),
+        Vec2::new(0.75_f32.sqrt(), 0.5),
+    );
+    assert!(circle
+        .cast(Vec2::new(20., 11.5), &slope, Vec2::ZERO, Dir2::NEG_X, 20.)
+        .is_none());
+}
+
+#[test]
+fn zero_height_rectangle_and_polygon() {
+    let platform = Rectangle::new(10., 0.);
+    let triangle =
+        ConvexShape::new([Vec2::new(-1., -1.), Vec2::new(1., -1.), Vec2::new(0., 1.)]).unwrap();
+
+    assert!(ConvexShape::try_from(platform).is_err());
+    assert!(platform.intersect(Vec2::ZERO, &triangle, Vec2::new(4., 0.5)));
+    assert!(triangle.intersect(Vec2::new(4., 0.5), &platform, Vec2::ZERO));
+    assert!(!platform.intersect(Vec2::ZERO, &triangle, Vec2::new(4., 1.5)));
+    assert!(!triangle.intersect(Vec2::new(7., 0.5), &platform, Vec2::ZERO));
+    assert_cast(
+        triangle.cast(Vec2::new(0., 5.), &platform, Vec2::ZERO, Dir2::NEG_Y, 10.),
+        4.,
+        Vec2::Y,
+    );
+}
+
+#[test]
+fn capsule_and_rounded_rectangle_fall_on_polygon() {
+    let platform = ConvexShape::try_from(Rectangle::new(10., 2.)).unwrap();
+
+    assert_cast(
+        Capsule2d::new(1., 2.).cast(Vec2::new(0., 10.), &platform, Vec2::ZERO, Dir2::NEG_Y, 20.),
+        7.,
+        Vec2::Y,
+    );
+    assert_cast(
+        platform.cast(
+            Vec2::ZERO,
+            &Capsule2d::new(1., 2.),
+            Vec2::new(0., 10.),
+            Dir2::Y,
+            20.,
+        ),
+        7.,
+        Vec2::NEG_Y,
+    );
+
+    let rounded = RoundedRectangle {
+        rect: Rectangle::new(4., 4.),
+        radius: 1.,
+    };
+    assert_cast(
+        rounded.cast(Vec2::new(0., 10.), &platform, Vec2::ZERO, Dir2::NEG_Y, 20.),
+        7.,
+        Vec2::Y,
+    );
+    assert!(rounded.intersect(Vec2::new(0., 2.9), &platform, Vec2::ZERO));
+    assert!(!platform.intersect(Vec2::ZERO, &rounded, Vec2::new(6.8, 2.8)));
+}
+
+#[test]
+fn point_slides_along_slope() {
+    let slope = slope();
+    let on_surface = Vec2::new(0., 5.);
+    let along = Dir2::new(Vec2::new(2., 1.)).unwrap();
+
+    assert!(Point
+        .cast(on_surface, &slope, Vec2::ZERO, along, 5.)
+        .is_none());
+    assert!(Point
+        .cast(on_surface, &slope, Vec2::ZERO, Dir2::Y, 5.)
+        .is_none());
+    assert_cast(
+        Point.cast(on_surface, &slope, Vec2::ZERO, Dir2::NEG_Y, 5.),
+        0.,
+        Vec2::new(-1., 2.),
+    );
+}
+
+#[test]
+fn circle_hits_rectangle_corner() {
+    let circle = Circle::new(1.);
+    let rect = Rectangle::new(4., 4.);
+    let diagonal = 0.5_f32.sqrt();
+
+    assert_cast(
+        circle.cast(
+            Vec2::new(-5., 2. + diagonal),
+            &rect,
+            Vec2::ZERO,
+            Dir2::X,
+            10.,
+        ),
+        3. - diagonal,
+        Vec2::new(-1., 1.),
+    );
+    assert_cast(
+        rect.cast(
+            Vec2::ZERO,
+            &circle,
+            Vec2::new(-5., 2. + diagonal),
+            Dir2::NEG_X,
+            10.,
+        ),
+        3. - diagonal,
+        Vec2::new(1., -1.),
+    );
+    assert!(circle
+        .cast(Vec2::new(-5., 3.05), &rect, Vec2::ZERO, Dir2::X, 10.)
+        .is_none());
+    assert!(!circle.intersect(Vec2::new(2.8, 2.8), &rect, Vec2::ZERO));
+    assert!(circle.intersect(Vec2::new(2.6, 2.6), &rect, Vec2::ZERO));
+}
+
+#[test]
+fn rounded_rectangle_against_point_and_rectangle() {
+    let rounded = RoundedRectangle::new(4., 4., 1.);
+    let rect = Rectangle::new(2., 2.);
+
+    assert_cast(
+        rounded.cast(Vec2::new(-10., 0.), &Point, Vec2::ZERO, Dir2::X, 20.),
+        8.,
+        Vec2::NEG_X,
+    );
+    assert!(rounded.intersect(Vec2::ZERO, &Point, Vec2::new(1.5, 1.5)));
+    assert!(!rounded.intersect(Vec2::ZERO, &Point, Vec2::new(1.9, 1.9)));
+
+    assert_cast(
+        rect.cast(Vec2::new(0., 10.), &rounded, Vec2::ZERO, Dir2::NEG_Y, 20.),
+        7.,
+        Vec2::Y,
+    );
+    assert_cast(
+        rounded.cast(Vec2::ZERO, &rect, Vec2::new(0., 10.), Dir2::Y, 20.),
+        7.,
+        Vec2::NEG_Y,
+    );
+    // Corners of the rectangle don't reach rounded corner
+    assert!(!rect.intersect(Vec2::new(2.9, 2.9), &rounded, Vec2::ZERO));
+    assert!(rounded.intersect(Vec2::ZERO, &rect, Vec2::new(2.6, 2.6)));
+}
+
+#[test]
+fn rounded_rectangle_corners_meet() {
+    let rounded = RoundedRectangle::new(4., 4., 1.);
+    let diagonal = 2. * 0.5_f32.sqrt();
+
+    // Minkowski sum has corners of radius 2 centered at (±2, ±2)
+    assert_cast(
+        rounded.cast(
+            Vec2::new(-10., 2. + diagonal),
+            &rounded,
+            Vec2::ZERO,
+            Dir2::X,
+            20.,
+        ),
+        8. - diagonal,
+        Vec2::new(-1., 1.),
+    );
+
+    let circle = Circle::new(1.);
+    let direction = Dir2::new(Vec2::ONE).unwrap();
+    // Minkowski sum has corners of radius 2 centered at (±1, ±1)
+    assert_cast(
+        rounded.cast(Vec2::splat(-10.), &circle, Vec2::ZERO, direction, 20.),
+        9. * 2.0_f32.sqrt() - 2.,
+        Vec2::new(-1., -1.),
+    );
+    assert_cast(
+        circle.cast(Vec2::ZERO, &rounded, Vec2::splat(-10.), -direction, 20.),
+        9. * 2.0_f32.sqrt() - 2.,
+        Vec2::new(1., 1.),
+    );
+    assert!(!circle.intersect(Vec2::splat(2.5), &rounded, Vec2::ZERO));
+    assert!(rounded.intersect(Vec2::ZERO, &circle, Vec2::splat(2.3)));
+}
+
+#[test]
+fn rounded_rectangle_bounding() {
+    let rounded = RoundedRectangle::new(6., 4., 1.);
+
+    let aabb: bevy::math::bounding::Aabb2d = rounded.bounding();
+    assert_eq!(aabb.max, Vec2::new(3., 2.));
+
+    let circle: bevy::math::bounding::BoundingCircle = rounded.bounding();
+    assert!((circle.radius() - (5.0_f32.sqrt() + 1.)).abs() < EPSILON);
+}
+
+#[test]
+fn shapes_cast_against_segment() {
+    let segment = Segment2d::new(Dir2::X, 10.);
+
+    assert_cast(
+        Point.cast(Vec2::new(0., 5.), &segment, Vec2::ZERO, Dir2::NEG_Y, 10.),
+        5.,
+        Vec2::Y,
+    );
+    assert_cast(
+        Circle::new(1.).cast(Vec2::new(8., 0.), &segment, Vec2::ZERO, Dir2::NEG_X, 10.),
+        2.,
+        Vec2::X,
+    );
+    assert_cast(
+        Rectangle::new(2., 2.).cast(Vec2::new(0., -5.), &segment, Vec2::ZERO, Dir2::Y, 10.),
+        4.,
+        Vec2::NEG_Y,
+    );
+    assert_cast(
+        Capsule2d::new(1., 2.).cast(Vec2::new(0., 5.), &segment, Vec2::ZERO, Dir2::NEG_Y, 10.),
+        3.,
+        Vec2::Y,
+    );
+    assert!(Circle::new(1.).intersect(Vec2::new(5.5, 0.5), &segment, Vec2::ZERO));
+    assert!(!Point.intersect(Vec2::new(0., 0.5), &segment, Vec2::ZERO));
+}
+
+#[test]
+fn body_slides_across_chain_joint() {
+    let body = Rectangle::new(2., 2.);
+    // Slightly below the surface, as it usually happens due to float imprecision
+    let start = Vec2::new(-5., 1. - 1e-5);
+
+    // Separate segments catch the body on the joint
+    let segment = Segment2d::new(Dir2::X, 10.);
+    assert_cast(
+        body.cast(start, &segment, Vec2::new(5., 0.), Dir2::X, 10.),
+        4.,
+        Vec2::NEG_X,
+    );
+
+    let chain = EdgeChain::new([Vec2::new(-10., 0.), Vec2::ZERO, Vec2::new(10., 0.)]);
+    assert!(body.cast(start, &chain, Vec2::ZERO, Dir2::X, 10.).is_none());
+    // Circle may touch the rounded joint, but it doesn't stop it
+    let circle_cast = Circle::new(1.).cast(start, &chain, Vec2::ZERO, Dir2::X, 10.);
+    assert!(circle_cast.is_none_or(|(_, normal)| normal.y > 1. - EPSILON));
+
+    assert_cast(
+        body.cast(Vec2::new(-5., 5.), &chain, Vec2::ZERO, Dir2::NEG_Y, 10.),
+        4.,
+        Vec2::Y,
+    );
+}
+
+#[test]
+fn chain_convex_joint_collides() {
+    let chain = EdgeChain::new([Vec2::new(-10., 0.), Vec2::ZERO, Vec2::new(10., -10.)]);
+    let normal = Vec2::from_angle(67.5_f32.to_radians());
+
+    assert_cast(
+        Circle::new(1.).cast(
+            normal * 5.,
+            &chain,
+            Vec2::ZERO,
+            Dir2::new(-normal).unwrap(),
+            10.,
+        ),
+        4.,
+        normal,
+    );
+}
+
+#[test]
+fn one_sided_chain() {
+    let two_sided = EdgeChain::new([Vec2::new(-10., 0.), Vec2::new(10., 0.)]);
+    let one_sided = two_sided.clone().with_one_sided(true);
+
+    assert_cast(
+        Point.cast(Vec2::new(0., -5.), &two_sided, Vec2::ZERO, Dir2::Y, 10.),
+        5.,
+        Vec2::NEG_Y,
+    );
+    assert!(Point
+        .cast(Vec2::new(0., -5.), &one_sided, Vec2::ZERO, Dir2::Y, 10.)
+        .is_none());
+    assert_cast(
+        Point.cast(Vec2::new(0., 5.), &one_sided, Vec2::ZERO, Dir2::NEG_Y, 10.),
+        5.,
+        Vec2::Y,
+    );
+}
+
+#[test]
+fn chain_ghost_vertices() {
+    let body = Rectangle::new(2., 2.);
+    let start = Vec2::new(-5., 1. - 1e-5);
+    let chain = EdgeChain::new([Vec2::ZERO, Vec2::new(10., 0.)]);
+
+    assert_cast(
+        body.cast(start, &chain, Vec2::ZERO, Dir2::X, 10.),
+        4.,
+        Vec2::NEG_X,
+    );
+
+    let chain = chain.with_ghost_vertices(Some(Vec2::new(-10., 0.)), None);
+    assert!(body.cast(start, &chain, Vec2::ZERO, Dir2::X, 10.).is_none());
+}
+
+#[test]
+fn chain_from_polyline() {
+    let chain = EdgeChain::from(Polyline2d {
+        vertices: [Vec2::new(-10., 0.), Vec2::ZERO, Vec2::new(10., 5.)],
+    });
+    assert_eq!(chain.segments_count(), 2);
+
+    let aabb: bevy::math::bounding::Aabb2d = chain.bounding();
+    assert_eq!(aabb.min, Vec2::new(-10., 0.));
+    assert_eq!(aabb.max, Vec2::new(10., 5.));
+
+    let closed = EdgeChain::closed(chain.vertices().iter().copied());
+    assert_eq!(closed.segments_count(), 3);
+    assert_eq!(closed.previous_vertex(0), Some(Vec2::new(10., 5.)));
+}
+
+#[test]
+fn any_shape_dispatches_to_both_shapes() {
+    let circle = AnyShape::from(Circle::new(1.));
+    let rect = AnyShape::from(Rectangle::new(4., 2.));
+    let chain = AnyShape::from(EdgeChain::new([Vec2::new(-10., 0.), Vec2::new(10., 0.)]));
+
+    assert_cast(
+        circle.cast(Vec2::new(-10., 0.), &rect, Vec2::ZERO, Dir2::X, 20.),
+        7.,
+        Vec2::NEG_X,
+    );
+    assert_cast(
+        rect.cast(Vec2::ZERO, &circle, Vec2::new(-10., 0.), Dir2::NEG_X, 20.),
+        7.,
+        Vec2::X,
+    );
+    assert_cast(
+        rect.cast(Vec2::new(0., 5.), &chain, Vec2::ZERO, Dir2::NEG_Y, 10.),
+        4.,
+        Vec2::Y,
+    );
+    assert!(circle.intersect(Vec2::new(2.5, 0.), &rect, Vec2::ZERO));
+    assert!(!circle.intersect(Vec2::new(3.5, 0.), &rect, Vec2::ZERO));
+
+    let aabb: bevy::math::bounding::Aabb2d = rect.bounding();
+    assert_eq!(aabb.max, Vec2::new(2., 1.));
+}
+
+#[test]
+fn compound_reports_collided_parts() {
+    // L-shaped building
+    let building = Compound::new([
+        (Vec2::new(0., 0.), Rectangle::new(10., 2.)),
+        (Vec2::new(-4., 5.), Rectangle::new(2., 8.)),
+    ]);
+
+    let (distance, normal, parts) = Point
+        .cast_parts(Vec2::new(2., 10.), &building, Vec2::ZERO, Dir2::NEG_Y, 20.)
+        .unwrap();
+    assert!((distance - 9.).abs() < EPSILON);
+    assert_eq!(normal, Dir2::Y);
+    assert_eq!(parts.hurtbox, 0);
+
+    let (distance, _, parts) = Point
+        .cast_parts(Vec2::new(-4., 10.), &building, Vec2::ZERO, Dir2::NEG_Y, 20.)
+        .unwrap();
+    assert!((distance - 1.).abs() < EPSILON);
+    assert_eq!(parts.hurtbox, 1);
+
+    // Sword with a hilt hits the building with it's blade
+    let sword = Compound::new([
+        (Vec2::ZERO, Rectangle::new(1., 1.)),
+        (Vec2::new(3., 0.), Rectangle::new(5., 0.2)),
+    ]);
+    let (distance, normal, parts) = sword
+        .cast_parts(Vec2::new(-20., 3.), &building, Vec2::ZERO, Dir2::X, 30.)
+        .unwrap();
+    assert!((distance - 9.5).abs() < EPSILON);
+    assert_eq!(normal, Dir2::NEG_X);
+    assert_eq!(
+        parts,
+        ColliderParts {
+            hitbox: 1,
+            hurtbox: 1
+        }
+    );
+
+    assert!(building.intersect(Vec2::ZERO, &Circle::new(1.), Vec2::new(-4., 8.)));
+    assert!(!Circle::new(1.).intersect(Vec2::new(2., 5.), &building, Vec2::ZERO));
+
+    let aabb: bevy::math::bounding::Aabb2d = building.bounding();
+    assert_eq!(aabb.min, Vec2::new(-5., -1.));
+    assert_eq!(aabb.max, Vec2::new(5., 9.));
+}
+
+#[test]
+fn rotated_colliders() {
+    let square = Rectangle::new(2., 2.);
+    let diamond = Collider::from_isometry(&square, Isometry2d::from_rotation(Rot2::degrees(45.)));
+
+    let point = Collider::new(&Point, Vec2::new(0.5, 10.));
+    let (distance, normal) = point.cast(diamond, Dir2::NEG_Y, 20.).unwrap();
+    assert!((distance - (10.5 - 2.0_f32.sqrt())).abs() < EPSILON);
+    assert!(normal.abs_diff_eq(Vec2::ONE.normalize(), EPSILON));
+
+    let body = Collider::from_isometry(
+        &square,
+        Isometry2d::new(Vec2::new(-0.5, 10.), Rot2::degrees(45.)),
+    );
+    let (distance, normal) = body.cast(diamond, Dir2::NEG_Y, 20.).unwrap();
+    assert!((distance - (10.5 - 2. * 2.0_f32.sqrt())).abs() < EPSILON);
+    assert!(normal.abs_diff_eq(Vec2::new(-1., 1.).normalize(), EPSILON));
+
+    let wall = Rectangle::new(10., 1.);
+    let vertical_wall =
+        Collider::from_isometry(&wall, Isometry2d::from_rotation(Rot2::degrees(90.)));
+    assert!(Collider::new(&Point, Vec2::new(0., 4.)).intersect(vertical_wall));
+    assert!(!Collider::new(&Point, Vec2::new(4., 0.)).intersect(vertical_wall));
+
+    let aabb: bevy::math::bounding::Aabb2d = vertical_wall.bounding();
+    assert!(aabb.max.abs_diff_eq(Vec2::new(0.5, 5.), EPSILON));
+}
+
+#[test]
+fn rotated_asymmetric_polygon() {
+    let triangle = ConvexShape::new([Vec2::ZERO, Vec2::new(10., 0.), Vec2::new(0., 2.)]).unwrap();
+    let quarter = Isometry2d::from_rotation(Rot2::degrees(90.));
+
+    // Rotated triangle spans from 0 to 10 on the y axis
+    let point = Isometry2d::from_translation(Vec2::new(-0.2, 8.));
+    assert!(Point.intersect_isometry(point, &triangle, quarter));
+    assert!(!Point.intersect_isometry(point, &triangle, Isometry2d::IDENTITY));
+
+    // Turned upside down, the triangle reaches 2 below its origin
+    let ground = Rectangle::new(40., 2.);
+    let (distance, normal, _) = triangle
+        .cast_isometry(
+            Isometry2d::new(Vec2::new(0., 20.), Rot2::degrees(180.)),
+            &ground,
+            Isometry2d::IDENTITY,
+            Dir2::NEG_Y,
+            30.,
+        )
+        .unwrap();
+    assert!((distance - 17.).abs() < EPSILON);
+    assert!(normal.abs_diff_eq(Vec2::Y, EPSILON));
+}
+
+/// Shape of a downstream crate that only implements the translation methods.
+struct Dot;
+
+impl ColliderInteraction<Circle> for Dot {
+    fn intersect(&self, self_position: Vec2, other: &Circle, other_position: Vec2) -> bool {
+        Point.intersect(self_position, other, other_position)
+    }
+
+    fn cast(
+        &self,
+        self_position: Vec2,
+        other: &Circle,
+        other_position: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2)> {
+        Point.cast(self_position, other, other_position, offset_dir, offset_len)
+    }
+}
+
+#[test]
+fn default_isometry_methods_use_translation() {
+    let circle = Circle::new(1.);
+    let quarter = Rot2::degrees(90.);
+
+    assert!(Dot.intersect_isometry(
+        Isometry2d::new(Vec2::new(10.5, 0.), quarter),
+        &circle,
+        Isometry2d::new(Vec2::new(10., 0.), quarter),
+    ));
+    let (distance, normal, _) = Dot
+        .cast_isometry(
+            Isometry2d::new(Vec2::new(0., 10.), quarter),
+            &circle,
+            Isometry2d::IDENTITY,
+            Dir2::NEG_Y,
+            20.,
+        )
+        .unwrap();
+    assert!((distance - 9.).abs() < EPSILON);
+    assert!(normal.abs_diff_eq(Vec2::Y, EPSILON));
+}
+
+#[test]
+fn rotated_compound_moves_its_parts() {
+    let compound = Compound::new([(Vec2::new(5., 0.), Circle::new(1.))]);
+    let rotated = Collider::from_isometry(&compound, Isometry2d::from_rotation(Rot2::degrees(90.)));
+
+    let (distance, _, parts) = Collider::new(&Point, Vec2::new(0., 10.))
+        .cast_parts(rotated, Dir2::NEG_Y, 20.)
+        .unwrap();
+    assert!((distance - 4.).abs() < EPSILON);
+    assert_eq!(parts.hurtbox, 0);
+
+    let aabb: bevy::math::bounding::Aabb2d = rotated.bounding();
+    assert!(aabb.min.abs_diff_eq(Vec2::new(-1., 4.), EPSILON));
+}
+
+fn assert_contacts(penetration: &Penetration, contacts: &[Vec2]) {
+    assert_eq!(penetration.contacts().len(), contacts.len());
+    for contact in contacts {
+        assert!(
+            penetration
+                .contacts()
+                .iter()
+                .any(|c| c.abs_diff_eq(*contact, EPSILON)),
+            "{contact} not in {:?}",
+            penetration.contacts()
+        );
+    }
+}
+
+#[test]
+fn rectangles_penetration_has_two_contacts() {
+    let rect = Rectangle::new(4., 4.);
+    let penetration = Collider::new(&rect, Vec2::new(3., 0.5))
+        .penetration(Collider::new(&rect, Vec2::ZERO))
+        .expect("expected penetration");
+
+    assert!((penetration.depth - 1.).abs() < EPSILON);
+    assert_eq!(penetration.normal, Dir2::X);
+    assert!(penetration.translation().abs_diff_eq(Vec2::X, EPSILON));
+    // Halfway between the sides, clipped to the overlapping part
+    assert_contacts(&penetration, &[Vec2::new(1.5, -1.5), Vec2::new(1.5, 2.)]);
+
+    assert!(Collider::new(&rect, Vec2::new(4., 0.))
+        .penetration(Collider::new(&rect, Vec2::ZERO))
+        .is_some_and(|p| p.depth.abs() < EPSILON));
+    assert!(Collider::new(&rect, Vec2::new(5., 0.))
+        .penetration(Collider::new(&rect, Vec2::ZERO))
+        .is_none());
+}
+
+#[test]
+fn circles_penetration_has_one_contact() {
+    let circle = Circle::new(1.);
+    let penetration = circle
+        .penetration(
+            Isometry2d::from_translation(Vec2::new(1.5, 0.)),
+            &circle,
+            Isometry2d::IDENTITY,
+        )
+        .expect("expected penetration");
+
+    assert!((penetration.depth - 0.5).abs() < EPSILON);
+    assert_eq!(penetration.normal, Dir2::X);
+    assert_contacts(&penetration, &[Vec2::new(0.75, 0.)]);
+}
+
+#[test]
+fn chain_penetration_in_both_directions() {
+    let chain = EdgeChain::new(vec![Vec2::new(-5., 0.), Vec2::new(5., 0.)]);
+    let circle = Circle::new(1.);
+    let circle_isometry = Isometry2d::from_translation(Vec2::new(0., 0.5));
+
+    let penetration = circle
+        .penetration(circle_isometry, &chain, Isometry2d::IDENTITY)
+        .expect("expected penetration");
+    assert!((penetration.depth - 0.5).abs() < EPSILON);
+    assert_eq!(penetration.normal, Dir2::Y);
+
+    let reversed = chain
+        .penetration(Isometry2d::IDENTITY, &circle, circle_isometry)
+        .expect("expected penetration");
+    assert_eq!(reversed.normal, Dir2::NEG_Y);
+    assert!((reversed.depth - 0.5).abs() < EPSILON);
+}
+
+#[test]
+fn distance_between_separated_shapes() {
+    let rect = Rectangle::new(4., 4.);
+    let circle = Circle::new(1.);
+
+    let distance = Collider::new(&circle, Vec2::new(6., 1.))
+        .distance(Collider::new(&rect, Vec2::ZERO))
+        .expect("expected distance");
+    assert!((distance.distance - 3.).abs() < EPSILON);
+    assert_eq!(distance.normal, Dir2::X);
+    assert!(distance.point.abs_diff_eq(Vec2::new(5., 1.), EPSILON));
+    assert!(distance.other_point.abs_diff_eq(Vec2::new(2., 1.), EPSILON));
+
+    // Closest to the corner of the rectangle
+    let distance = Collider::new(&Point, Vec2::new(5., 6.))
+        .distance(Collider::new(&rect, Vec2::ZERO))
+        .expect("expected distance");
+    assert!((distance.distance - 5.).abs() < EPSILON);
+    assert!(distance.other_point.abs_diff_eq(Vec2::splat(2.), EPSILON));
+
+    let reversed = rect
+        .distance(
+            Isometry2d::IDENTITY,
+            &Point,
+            Isometry2d::from_translation(Vec2::new(5., 6.)),
+        )
+        .expect("expected distance");
+    assert!((reversed.distance - 5.).abs() < EPSILON);
+    assert!(reversed.point.abs_diff_eq(Vec2::splat(2.), EPSILON));
+}
+
+#[test]
+fn distance_between_overlapping_shapes_is_negative() {
+    let rect = Rectangle::new(4., 4.);
+    let long = Rectangle::new(10., 1.);
+
+    // Crossing rectangles, none of the corners is inside of the other rectangle
+    let distance = Collider::new(&long, Vec2::ZERO)
+        .distance(Collider::new(&rect, Vec2::new(0., 0.5)))
+        .expect("expected distance");
+    assert!((distance.distance + 2.).abs() < EPSILON);
+    assert_eq!(distance.normal, Dir2::NEG_Y);
+
+    let rounded = RoundedRectangle {
+        rect: Rectangle::new(4., 4.),
+        radius: 1.,
+    };
+    let distance = Collider::new(&Point, Vec2::new(1.5, 0.))
+        .distance(Collider::new(&rounded, Vec2::ZERO))
+        .expect("expected distance");
+    assert!((distance.distance + 0.5).abs() < EPSILON);
+    assert_eq!(distance.normal, Dir2::X);
+}
@@ -0,0 +1,33 @@
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use bevy_bump::{
+    spatial_index::broad_phase::ChunkGrid, spatial_query::filter::monitorable::Monitorable,
+    ColliderGroup, CollisionImplementation,
+};
+
+#[derive(TypePath)]
+pub struct TestGroup;
+
+pub struct TestImplementation;
+
+impl CollisionImplementation<TestGroup> for TestImplementation {}
+
+impl ColliderGroup for TestGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = TestImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+/// Sorted entities stored in the chunks intersecting with `aabb`, duplicates removed.
+pub fn entities_on_aabb(index: &ChunkGrid, min: Vec2, max: Vec2) -> Vec<Entity> {
+    let mut entities: Vec<Entity> = index
+        .iter_chunks_on_aabb(bevy::math::bounding::Aabb2d { min, max })
+        .flat_map(|chunk| chunk.iter().copied())
+        .collect();
+    entities.sort();
+    entities.dedup();
+    entities
+}
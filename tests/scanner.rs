@@ -0,0 +1,120 @@
+#![cfg(feature = "implementations")]
+
+use bevy::prelude::*;
+use bevy_bump::prelude::*;
+
+struct ScannerTestGroup;
+
+struct ScannerTestImplementation;
+
+impl CollisionImplementation<ScannerTestGroup> for ScannerTestImplementation {}
+
+impl ScannerImplementation<ScannerTestGroup> for ScannerTestImplementation {
+    type ReportStrategy = ExtendHitboxComponent;
+}
+
+impl ColliderGroup for ScannerTestGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = ScannerTestImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        SpatialIndexPlugin::<ScannerTestGroup>::new(10.),
+    ))
+    .add_scanner_group::<ScannerTestGroup>();
+    app
+}
+
+fn spawn_hurtbox(app: &mut App, position: Vec2) -> Entity {
+    app.world_mut()
+        .spawn((
+            HurtboxShape::<ScannerTestGroup>(Rectangle::new(10., 10.)),
+            Transform::from_translation(position.extend(0.)),
+        ))
+        .id()
+}
+
+fn spawn_scanner(app: &mut App, position: Vec2) -> Entity {
+    app.world_mut()
+        .spawn((
+            HitboxShape::<ScannerTestGroup>(Rectangle::new(2., 2.)),
+            Transform::from_translation(position.extend(0.)),
+        ))
+        .id()
+}
+
+fn collisions(app: &App, scanner: Entity) -> Vec<Entity> {
+    app.world()
+        .get::<HitboxCollisions<ScannerTestGroup>>(scanner)
+        .unwrap()
+        .0
+        .iter()
+        .map(|collision| collision.hurtbox)
+        .collect()
+}
+
+#[test]
+fn scanner_reports_hurtboxes_on_the_way() {
+    let mut app = app();
+    let hurtbox = spawn_hurtbox(&mut app, Vec2::new(50., 0.));
+    let scanner = spawn_scanner(&mut app, Vec2::ZERO);
+    app.update();
+    app.update();
+    assert!(collisions(&app, scanner).is_empty());
+
+    app.world_mut()
+        .get_mut::<Transform>(scanner)
+        .unwrap()
+        .translation = Vec3::new(100., 0., 0.);
+    app.update();
+
+    assert_eq!(collisions(&app, scanner), [hurtbox]);
+
+    app.update();
+    assert!(collisions(&app, scanner).is_empty());
+}
+
+#[test]
+fn scanner_ignores_unmonitorable_hurtboxes() {
+    let mut app = app();
+    let hurtbox = spawn_hurtbox(&mut app, Vec2::new(50., 0.));
+    app.world_mut()
+        .entity_mut(hurtbox)
+        .insert(HurtboxMonitorable::<ScannerTestGroup>::new(false));
+    let scanner = spawn_scanner(&mut app, Vec2::ZERO);
+    app.update();
+
+    app.world_mut()
+        .get_mut::<Transform>(scanner)
+        .unwrap()
+        .translation = Vec3::new(100., 0., 0.);
+    app.update();
+
+    assert!(collisions(&app, scanner).is_empty());
+}
+
+#[test]
+fn scanner_that_is_also_a_hurtbox_does_not_find_itself() {
+    let mut app = app();
+    let hurtbox = spawn_hurtbox(&mut app, Vec2::new(50., 0.));
+    let scanner = spawn_scanner(&mut app, Vec2::ZERO);
+    app.world_mut()
+        .entity_mut(scanner)
+        .insert(HurtboxShape::<ScannerTestGroup>(Rectangle::new(2., 2.)));
+    app.update();
+    assert!(collisions(&app, scanner).is_empty());
+
+    app.world_mut()
+        .get_mut::<Transform>(scanner)
+        .unwrap()
+        .translation = Vec3::new(100., 0., 0.);
+    app.update();
+
+    assert_eq!(collisions(&app, scanner), [hurtbox]);
+}
@@ -0,0 +1,277 @@
+#![cfg(feature = "implementations")]
+
+use std::time::Duration;
+
+use bevy::{
+    ecs::schedule::ScheduleLabel, prelude::*, time::TimeUpdateStrategy, transform::TransformSystem,
+};
+use bevy_bump::prelude::*;
+
+struct MoveGroup;
+
+struct MoveImplementation;
+
+impl CollisionImplementation<MoveGroup> for MoveImplementation {}
+
+impl VelocityImplementation<MoveGroup> for MoveImplementation {
+    type ReportStrategy = ExtendHitboxComponent;
+    type Response = Slide;
+    type Depenetration = Depenetrate;
+}
+
+impl ColliderGroup for MoveGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = MoveImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+fn app(schedule: impl ScheduleLabel) -> App {
+    let schedule = schedule.intern();
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        CollisionDetectionPlugin::new(schedule),
+        SpatialIndexPlugin::<MoveGroup>::new(10.).in_schedule(schedule),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )));
+    app
+}
+
+#[derive(Resource, Default)]
+struct Order(Vec<&'static str>);
+
+fn record(label: &'static str) -> impl Fn(ResMut<Order>) {
+    move |mut order: ResMut<Order>| order.0.push(label)
+}
+
+#[test]
+fn sets_run_in_order_before_transform_propagation() {
+    let mut app = app(PostUpdate);
+    app.init_resource::<Order>().add_systems(
+        PostUpdate,
+        (
+            record("last").in_set(CollisionDetectionSet::Last),
+            record("propagated").after(TransformSystem::TransformPropagate),
+            record("colliding").in_set(CollisionDetectionSet::Colliding),
+            record("first").in_set(CollisionDetectionSet::First),
+        ),
+    );
+    app.update();
+
+    assert_eq!(
+        app.world().resource::<Order>().0,
+        ["first", "colliding", "last", "propagated"]
+    );
+}
+
+#[test]
+fn moved_hitbox_is_propagated_in_the_same_frame() {
+    for schedule in [Update.intern(), PostUpdate.intern()] {
+        let mut app = app(schedule);
+        app.add_velocity_group_in_schedule::<MoveGroup>(schedule);
+        let body = app
+            .world_mut()
+            .spawn((
+                HitboxShape::<MoveGroup>(Rectangle::new(10., 10.)),
+                Transform::default(),
+                Velocity(Vec2::new(100., 0.)),
+            ))
+            .id();
+
+        // First update has zero delta time
+        app.update();
+        app.update();
+
+        let global_transform = app.world().get::<GlobalTransform>(body).unwrap();
+        assert!(
+            global_transform
+                .translation()
+                .xy()
+                .abs_diff_eq(Vec2::new(10., 0.), 1e-3),
+            "{schedule:?}"
+        );
+    }
+}
+
+#[test]
+fn interpolated_transform_is_smoothed_between_fixed_steps() {
+    let mut app = app(FixedUpdate);
+    app.add_plugins(TransformInterpolationPlugin)
+        .add_velocity_group_in_schedule::<MoveGroup>(FixedUpdate)
+        .insert_resource(Time::<Fixed>::from_seconds(0.1))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            50,
+        )));
+    let body = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<MoveGroup>(Rectangle::new(10., 10.)),
+            Velocity(Vec2::new(100., 0.)),
+            TransformInterpolation::default(),
+        ))
+        .id();
+    let position = |app: &App| app.world().get::<Transform>(body).unwrap().translation.x;
+
+    // First update has zero delta time, the second one doesn't reach the fixed step
+    app.update();
+    app.update();
+    assert_eq!(position(&app), 0.);
+
+    // Step from 0 to 10, no time left over
+    app.update();
+    assert!(position(&app).abs() < 1e-3);
+
+    // Half way to the next step
+    app.update();
+    assert!((position(&app) - 5.).abs() < 1e-3);
+
+    // Step from 10 to 20 starts from the real position
+    app.update();
+    assert!((position(&app) - 10.).abs() < 1e-3);
+    app.update();
+    assert!((position(&app) - 15.).abs() < 1e-3);
+}
+
+struct PluginGroup;
+
+impl ColliderGroup for PluginGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = VelocityPlugin<ExtendHitboxComponent, Slide>;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+#[test]
+fn velocity_plugin_runs_in_schedule_of_collider_group() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        SpatialIndexPlugin::<PluginGroup>::new(10.).in_schedule(FixedUpdate),
+        WithColliderGroup::<PluginGroup> {
+            implementation: VelocityPlugin::default(),
+            schedule: FixedUpdate.intern(),
+        },
+    ))
+    .insert_resource(Time::<Fixed>::from_seconds(0.1))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        50,
+    )));
+    app.world_mut().spawn((
+        HurtboxShape::<PluginGroup>(Rectangle::new(10., 10.)),
+        Transform::from_xyz(25., 0., 0.),
+    ));
+    let body = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<PluginGroup>(Rectangle::new(10., 10.)),
+            Transform::default(),
+            Velocity(Vec2::new(100., 0.)),
+        ))
+        .id();
+    let position = |app: &App| app.world().get::<Transform>(body).unwrap().translation.x;
+
+    // First update has zero delta time, the second one doesn't reach the fixed step
+    app.update();
+    app.update();
+    assert_eq!(position(&app), 0.);
+
+    app.update();
+    assert!((position(&app) - 10.).abs() < 1e-3);
+
+    // Stopped by the hurtbox
+    app.update();
+    app.update();
+    assert!((position(&app) - 15.).abs() < 1e-3);
+    assert_eq!(
+        app.world()
+            .get::<HitboxCollisions<PluginGroup>>(body)
+            .unwrap()
+            .0
+            .len(),
+        1
+    );
+}
+
+struct ScannerPluginGroup;
+
+impl ColliderGroup for ScannerPluginGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = ScannerPlugin<ExtendHitboxComponent>;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+#[test]
+fn scanner_plugin_is_added_with_collider_group() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        SpatialIndexPlugin::<ScannerPluginGroup>::new(10.),
+        WithColliderGroup::<ScannerPluginGroup>::new(ScannerPlugin::default()),
+    ));
+    let hurtbox = app
+        .world_mut()
+        .spawn((
+            HurtboxShape::<ScannerPluginGroup>(Rectangle::new(10., 10.)),
+            Transform::from_xyz(50., 0., 0.),
+        ))
+        .id();
+    let scanner = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<ScannerPluginGroup>(Rectangle::new(2., 2.)),
+            Transform::default(),
+        ))
+        .id();
+    app.update();
+
+    app.world_mut()
+        .get_mut::<Transform>(scanner)
+        .unwrap()
+        .translation
+        .x = 100.;
+    app.update();
+
+    let collisions = &app
+        .world()
+        .get::<HitboxCollisions<ScannerPluginGroup>>(scanner)
+        .unwrap()
+        .0;
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].hurtbox, hurtbox);
+}
+
+struct OwnGroup;
+
+/// Implementation written as a [`Plugin`], like before the schedule could be chosen.
+struct OwnImplementation;
+
+impl CollisionImplementation<OwnGroup> for OwnImplementation {}
+
+impl Plugin for OwnImplementation {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Order>();
+    }
+}
+
+impl ColliderGroup for OwnGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = OwnImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+#[test]
+fn plugin_implementation_is_built() {
+    let mut app = App::new();
+    app.add_plugins(WithColliderGroup::<OwnGroup>::new(OwnImplementation));
+    assert!(app.world().contains_resource::<Order>());
+}
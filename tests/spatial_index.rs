@@ -0,0 +1,1107 @@
+mod common;
+
+use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        system::RunSystemOnce,
+    },
+    math::bounding::Aabb2d,
+    prelude::*,
+};
+use bevy_bump::{
+    collider::Collider,
+    components::{HitboxShape, HurtboxShape},
+    error::{BumpError, BumpErrorPlugin, ErrorPolicy},
+    spatial_index::{
+        broad_phase::{BroadPhase, ChunkGrid},
+        components::{SpatialIndexRegistry, StaticHurtbox},
+        pairs::SpatialIndexPairs,
+        query::SpatialIndexQuery,
+        spatial_index::SpatialIndex,
+        SpatialIndexDebugPlugin, SpatialIndexPlugin,
+    },
+    spatial_query::filter::{
+        layer::{HitboxLayer, HurtboxLayer, Layer, LayeredImplementation},
+        monitorable::{HurtboxMonitorable, Monitorable},
+    },
+    ColliderGroup, CollisionImplementation,
+};
+use common::{entities_on_aabb, TestGroup};
+
+/// Any error fails the test, including inconsistencies found by the debug plugin.
+fn app() -> App {
+    app_with_policy(ErrorPolicy::Panic)
+}
+
+fn app_with_policy(policy: ErrorPolicy) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        BumpErrorPlugin { policy },
+        SpatialIndexPlugin::<TestGroup>::new(10.),
+        SpatialIndexDebugPlugin::<TestGroup>::default(),
+    ));
+    app
+}
+
+fn spawn_hurtbox(app: &mut App, position: Vec2, half_size: Vec2) -> Entity {
+    app.world_mut()
+        .spawn((
+            HurtboxShape::<TestGroup>(Rectangle { half_size }),
+            Transform::from_translation(position.extend(0.)),
+        ))
+        .id()
+}
+
+fn spatial_index(app: &App) -> &SpatialIndex<TestGroup> {
+    app.world().resource::<SpatialIndex<TestGroup>>()
+}
+
+#[test]
+fn plugin_inserts_configured_index() {
+    let app = app();
+    assert_eq!(spatial_index(&app).pixels_per_chunk(), 10.);
+}
+
+#[test]
+fn index_is_reflected_with_its_broad_phase() {
+    let app = app();
+    let index: &dyn Reflect = spatial_index(&app);
+    assert_eq!(index.path::<f32>("broad_phase.pixels_per_chunk"), Ok(&10.));
+}
+
+#[test]
+fn spawned_hurtbox_is_indexed() {
+    let mut app = app();
+    let entity = spawn_hurtbox(&mut app, Vec2::new(15., 15.), Vec2::splat(2.));
+    // Indexed as soon as it's spawned
+    assert_eq!(
+        entities_on_aabb(spatial_index(&app), Vec2::splat(10.), Vec2::splat(19.)),
+        [entity]
+    );
+    app.update();
+
+    assert!(app
+        .world()
+        .get::<SpatialIndexRegistry<TestGroup>>(entity)
+        .is_some());
+
+    let index = spatial_index(&app);
+    assert_eq!(
+        entities_on_aabb(index, Vec2::splat(10.), Vec2::splat(19.)),
+        [entity]
+    );
+    assert!(entities_on_aabb(index, Vec2::ZERO, Vec2::splat(9.)).is_empty());
+    assert!(entities_on_aabb(index, Vec2::splat(20.), Vec2::splat(29.)).is_empty());
+}
+
+#[test]
+fn hurtbox_on_chunk_border_is_in_every_chunk() {
+    let mut app = app();
+    let entity = spawn_hurtbox(&mut app, Vec2::new(10., 10.), Vec2::splat(2.));
+    app.update();
+
+    let index = spatial_index(&app);
+    for chunk in [
+        IVec2::new(0, 0),
+        IVec2::new(0, 1),
+        IVec2::new(1, 0),
+        IVec2::new(1, 1),
+    ] {
+        let min = index.chunk_to_global(chunk) + Vec2::splat(1.);
+        assert_eq!(entities_on_aabb(index, min, min), [entity], "chunk {chunk}");
+    }
+}
+
+#[test]
+fn moved_hurtbox_changes_chunks() {
+    let mut app = app();
+    let entity = spawn_hurtbox(&mut app, Vec2::new(15., 15.), Vec2::splat(2.));
+    app.update();
+
+    app.world_mut()
+        .get_mut::<Transform>(entity)
+        .unwrap()
+        .translation = Vec3::new(-25., 35., 0.);
+    app.update();
+
+    let index = spatial_index(&app);
+    assert!(entities_on_aabb(index, Vec2::splat(10.), Vec2::splat(19.)).is_empty());
+    assert_eq!(
+        entities_on_aabb(index, Vec2::new(-30., 30.), Vec2::new(-21., 39.)),
+        [entity]
+    );
+
+    let registry = app
+        .world()
+        .get::<SpatialIndexRegistry<TestGroup>>(entity)
+        .unwrap();
+    assert_eq!(registry.current_position(), Vec2::new(-25., 35.));
+}
+
+#[derive(Component, Default)]
+struct Probe(Vec<Entity>);
+
+#[test]
+fn many_moved_hurtboxes_are_queried_in_parallel() {
+    let mut app = app();
+    let hurtboxes: Vec<Entity> = (0..100)
+        .map(|i| spawn_hurtbox(&mut app, Vec2::new(i as f32 * 20., 0.), Vec2::splat(2.)))
+        .collect();
+    app.update();
+
+    for &entity in &hurtboxes {
+        app.world_mut()
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation
+            .y = 100.;
+    }
+    let probes: Vec<Entity> = (0..100)
+        .map(|i| {
+            app.world_mut()
+                .spawn((
+                    Probe::default(),
+                    Transform::from_xyz(i as f32 * 20., 100., 0.),
+                ))
+                .id()
+        })
+        .collect();
+    app.update();
+
+    app.world_mut()
+        .run_system_once(
+            |mut probes: Query<(&Transform, &mut Probe)>, query: SpatialIndexQuery<TestGroup>| {
+                probes.par_iter_mut().for_each(|(transform, mut probe)| {
+                    let hitbox = Rectangle::new(10., 10.);
+                    let position = transform.translation.truncate();
+                    probe.0 = query
+                        .intersect(Collider::new(&hitbox, position), ())
+                        .collect();
+                });
+            },
+        )
+        .unwrap();
+
+    for (probe, hurtbox) in probes.into_iter().zip(hurtboxes) {
+        assert_eq!(app.world().get::<Probe>(probe).unwrap().0, [hurtbox]);
+    }
+}
+
+#[test]
+fn sweep_finds_same_pairs_as_queries() {
+    let mut app = app();
+    let mut random = 7u32;
+    let mut next = move || {
+        random = random.wrapping_mul(1664525).wrapping_add(1013904223);
+        (random >> 8) as f32 / (1 << 24) as f32
+    };
+    for _ in 0..60 {
+        let position = Vec2::new(next(), next()) * 200.;
+        let hurtbox = spawn_hurtbox(&mut app, position, Vec2::new(next(), next()) * 10. + 1.);
+        if next() < 0.2 {
+            app.world_mut()
+                .entity_mut(hurtbox)
+                .insert(HurtboxMonitorable::<TestGroup>::new(false));
+        }
+        // Some entities are both hitboxes and hurtboxes
+        if next() < 0.3 {
+            app.world_mut()
+                .entity_mut(hurtbox)
+                .insert(HitboxShape::<TestGroup>(Rectangle::new(30., 30.)));
+        }
+    }
+    for _ in 0..60 {
+        let size = Vec2::new(next(), next()) * 40. + 2.;
+        app.world_mut().spawn((
+            HitboxShape::<TestGroup>(Rectangle::from_size(size)),
+            Transform::from_translation((Vec2::new(next(), next()) * 200.).extend(0.)),
+        ));
+    }
+    app.update();
+
+    let (mut swept, mut queried) = app
+        .world_mut()
+        .run_system_once(
+            |pairs: SpatialIndexPairs<TestGroup>,
+             query: SpatialIndexQuery<TestGroup>,
+             hitboxes: Query<(Entity, &HitboxShape<TestGroup>, &Transform)>| {
+                let queried: Vec<(Entity, Entity)> = hitboxes
+                    .iter()
+                    .flat_map(|(entity, shape, transform)| {
+                        let hitbox = Collider::new(&**shape, transform.translation.truncate());
+                        query
+                            .intersect(hitbox, ())
+                            .filter(move |hurtbox| *hurtbox != entity)
+                            .map(move |hurtbox| (entity, hurtbox))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect();
+                (pairs.intersecting_pairs(), queried)
+            },
+        )
+        .unwrap();
+    swept.sort();
+    queried.sort();
+    assert!(!swept.is_empty());
+    assert_eq!(swept, queried);
+}
+
+#[test]
+fn sweep_finds_partially_overlapping_rectangles() {
+    let mut app = app();
+    let hitbox = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<TestGroup>(Rectangle::new(10., 10.)),
+            Transform::default(),
+        ))
+        .id();
+    // Overlaps the right edge of the hitbox
+    let overlapping = spawn_hurtbox(&mut app, Vec2::new(8., 3.), Vec2::splat(4.));
+    // Larger than the hitbox, covers it
+    let covering = spawn_hurtbox(&mut app, Vec2::new(-2., 0.), Vec2::splat(20.));
+    // Beside the hitbox
+    spawn_hurtbox(&mut app, Vec2::new(0., 12.), Vec2::splat(4.));
+    app.update();
+
+    let mut pairs = app
+        .world_mut()
+        .run_system_once(|pairs: SpatialIndexPairs<TestGroup>| pairs.intersecting_pairs())
+        .unwrap();
+    pairs.sort();
+    assert_eq!(pairs, [(hitbox, overlapping), (hitbox, covering)]);
+}
+
+#[test]
+fn moved_parent_moves_child_hurtbox() {
+    let mut app = app();
+    let parent = app.world_mut().spawn(Transform::default()).id();
+    let child = spawn_hurtbox(&mut app, Vec2::new(5., 5.), Vec2::splat(2.));
+    app.world_mut().entity_mut(parent).add_child(child);
+    app.update();
+
+    app.world_mut()
+        .get_mut::<Transform>(parent)
+        .unwrap()
+        .translation = Vec3::new(20., 0., 0.);
+    app.update();
+
+    let index = spatial_index(&app);
+    assert!(entities_on_aabb(index, Vec2::ZERO, Vec2::splat(9.)).is_empty());
+    assert_eq!(
+        entities_on_aabb(index, Vec2::new(20., 0.), Vec2::new(29., 9.)),
+        [child]
+    );
+}
+
+fn drain_errors(app: &mut App) -> Vec<String> {
+    app.world_mut()
+        .resource_mut::<Events<BumpError>>()
+        .drain()
+        .map(|error| error.to_string())
+        .collect()
+}
+
+/// Spawns hurtbox as a child of an entity without transform, so its global position is unknown.
+fn spawn_orphaned_hurtbox(app: &mut App) -> (Entity, Entity) {
+    let parent = app.world_mut().spawn_empty().id();
+    let child = app.world_mut().spawn(Transform::from_xyz(5., 5., 0.)).id();
+    app.world_mut().entity_mut(parent).add_child(child);
+    app.world_mut()
+        .entity_mut(child)
+        .insert(HurtboxShape::<TestGroup>(Rectangle::new(4., 4.)));
+    (parent, child)
+}
+
+#[test]
+fn hurtbox_registration_is_retried_until_position_is_known() {
+    let mut app = app_with_policy(ErrorPolicy::Event);
+    let (parent, child) = spawn_orphaned_hurtbox(&mut app);
+    app.update();
+
+    // Reported when the shape is inserted and when it's retried
+    assert_eq!(drain_errors(&mut app).len(), 2);
+    assert!(entities_on_aabb(spatial_index(&app), Vec2::ZERO, Vec2::splat(9.)).is_empty());
+    assert!(!app
+        .world()
+        .get::<SpatialIndexRegistry<TestGroup>>(child)
+        .unwrap()
+        .is_valid());
+
+    app.world_mut()
+        .entity_mut(parent)
+        .insert(Transform::from_xyz(20., 0., 0.));
+    app.update();
+
+    assert_eq!(drain_errors(&mut app), Vec::<String>::new());
+    assert_eq!(
+        entities_on_aabb(spatial_index(&app), Vec2::new(20., 0.), Vec2::new(29., 9.)),
+        [child]
+    );
+}
+
+#[test]
+fn hurtbox_that_lost_its_position_is_reported_and_kept() {
+    let mut app = app_with_policy(ErrorPolicy::Event);
+    let parent = app.world_mut().spawn(Transform::default()).id();
+    let child = app
+        .world_mut()
+        .spawn((
+            HurtboxShape::<TestGroup>(Rectangle::new(4., 4.)),
+            Transform::from_xyz(5., 5., 0.),
+        ))
+        .set_parent(parent)
+        .id();
+    app.update();
+    assert_eq!(drain_errors(&mut app), Vec::<String>::new());
+
+    app.world_mut().entity_mut(parent).remove::<Transform>();
+    app.update();
+
+    let errors = drain_errors(&mut app);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with(&format!(
+        "unable to compute global position of collider {child}, skipping it"
+    )));
+    assert_eq!(
+        entities_on_aabb(spatial_index(&app), Vec2::ZERO, Vec2::splat(9.)),
+        [child]
+    );
+}
+
+struct LayerGroup;
+
+struct LayerImplementation;
+
+impl CollisionImplementation<LayerGroup> for LayerImplementation {}
+
+impl LayeredImplementation<LayerGroup> for LayerImplementation {
+    type Layer = u8;
+}
+
+impl ColliderGroup for LayerGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = LayerImplementation;
+    type Filter = Layer<u8>;
+    type BroadPhase = ChunkGrid;
+}
+
+#[test]
+fn missing_layer_is_reported_once() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        BumpErrorPlugin {
+            policy: ErrorPolicy::Event,
+        },
+        SpatialIndexPlugin::<LayerGroup>::new(10.),
+    ));
+    let hitbox = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<LayerGroup>(Rectangle::new(4., 4.)),
+            Transform::default(),
+        ))
+        .id();
+    app.world_mut().spawn((
+        HurtboxShape::<LayerGroup>(Rectangle::new(4., 4.)),
+        HurtboxLayer::<LayerGroup>(1),
+    ));
+
+    for _ in 0..3 {
+        let pairs = app
+            .world_mut()
+            .run_system_once(|pairs: SpatialIndexPairs<LayerGroup>| pairs.intersecting_pairs())
+            .unwrap();
+        assert!(pairs.is_empty());
+        app.update();
+    }
+
+    assert_eq!(
+        drain_errors(&mut app),
+        [format!(
+            "{hitbox} is missing `{}` required by the collision filter",
+            std::any::type_name::<HitboxLayer<LayerGroup>>()
+        )]
+    );
+}
+
+#[test]
+fn missing_layer_is_reported_again_after_being_removed() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        BumpErrorPlugin {
+            policy: ErrorPolicy::Event,
+        },
+        SpatialIndexPlugin::<LayerGroup>::new(10.),
+    ));
+    let hitbox = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<LayerGroup>(Rectangle::new(4., 4.)),
+            Transform::default(),
+        ))
+        .id();
+    app.world_mut().spawn((
+        HurtboxShape::<LayerGroup>(Rectangle::new(4., 4.)),
+        HurtboxLayer::<LayerGroup>(1),
+    ));
+    let query_pairs = |app: &mut App| {
+        app.world_mut()
+            .run_system_once(|pairs: SpatialIndexPairs<LayerGroup>| pairs.intersecting_pairs())
+            .unwrap()
+    };
+
+    query_pairs(&mut app);
+    app.update();
+    app.world_mut()
+        .entity_mut(hitbox)
+        .insert(HitboxLayer::<LayerGroup>(1));
+    query_pairs(&mut app);
+    app.update();
+    app.world_mut()
+        .entity_mut(hitbox)
+        .remove::<HitboxLayer<LayerGroup>>();
+    query_pairs(&mut app);
+    app.update();
+
+    let error = format!(
+        "{hitbox} is missing `{}` required by the collision filter",
+        std::any::type_name::<HitboxLayer<LayerGroup>>()
+    );
+    assert_eq!(drain_errors(&mut app), [error.clone(), error]);
+}
+
+#[test]
+#[should_panic(expected = "unable to compute global position of hurtbox")]
+fn panic_policy_panics_on_error() {
+    let mut app = app_with_policy(ErrorPolicy::Panic);
+    spawn_orphaned_hurtbox(&mut app);
+}
+
+#[test]
+fn reshaped_hurtbox_changes_chunks() {
+    let mut app = app();
+    let entity = spawn_hurtbox(&mut app, Vec2::new(15., 15.), Vec2::splat(2.));
+    app.update();
+
+    app.world_mut()
+        .get_mut::<HurtboxShape<TestGroup>>(entity)
+        .unwrap()
+        .0 = Rectangle {
+        half_size: Vec2::new(12., 2.),
+    };
+    app.update();
+
+    let index = spatial_index(&app);
+    assert_eq!(
+        entities_on_aabb(index, Vec2::new(0., 10.), Vec2::new(9., 19.)),
+        [entity]
+    );
+    assert_eq!(
+        entities_on_aabb(index, Vec2::new(20., 10.), Vec2::new(29., 19.)),
+        [entity]
+    );
+    assert!(entities_on_aabb(index, Vec2::new(0., 0.), Vec2::new(29., 9.)).is_empty());
+
+    app.world_mut()
+        .get_mut::<HurtboxShape<TestGroup>>(entity)
+        .unwrap()
+        .0 = Rectangle {
+        half_size: Vec2::splat(1.),
+    };
+    app.update();
+
+    let index = spatial_index(&app);
+    assert!(entities_on_aabb(index, Vec2::new(0., 10.), Vec2::new(9., 19.)).is_empty());
+    assert_eq!(
+        entities_on_aabb(index, Vec2::new(10., 10.), Vec2::new(19., 19.)),
+        [entity]
+    );
+}
+
+#[test]
+fn despawned_hurtbox_is_removed() {
+    let mut app = app();
+    let despawned = spawn_hurtbox(&mut app, Vec2::new(15., 15.), Vec2::splat(2.));
+    let kept = spawn_hurtbox(&mut app, Vec2::new(16., 16.), Vec2::splat(2.));
+    app.update();
+
+    assert_eq!(
+        entities_on_aabb(spatial_index(&app), Vec2::splat(10.), Vec2::splat(19.)).len(),
+        2
+    );
+
+    app.world_mut().despawn(despawned);
+    app.update();
+
+    assert_eq!(
+        entities_on_aabb(spatial_index(&app), Vec2::splat(10.), Vec2::splat(19.)),
+        [kept]
+    );
+}
+
+#[test]
+fn static_hurtbox_is_baked_and_not_updated() {
+    let mut app = app();
+    let wall = app
+        .world_mut()
+        .spawn((
+            HurtboxShape::<TestGroup>(Rectangle::new(40., 4.)),
+            Transform::from_xyz(0., -10., 0.),
+            StaticHurtbox::<TestGroup>::new(),
+        ))
+        .id();
+    let moving = spawn_hurtbox(&mut app, Vec2::new(0., 10.), Vec2::splat(2.));
+    app.update();
+
+    let index = spatial_index(&app);
+    assert!(index.is_static(wall));
+    assert!(!index.needs_baking());
+    assert_eq!(index.static_bvh().len(), 1);
+    // Grid only holds the moving hurtbox
+    assert_eq!(
+        entities_on_aabb(index, Vec2::splat(-100.), Vec2::splat(100.)),
+        [moving]
+    );
+
+    let intersect = |query: SpatialIndexQuery<TestGroup>| {
+        let hitbox = Rectangle::new(100., 100.);
+        let mut found: Vec<Entity> = query
+            .intersect(Collider::new(&hitbox, Vec2::ZERO), ())
+            .collect();
+        found.sort();
+        found
+    };
+    assert_eq!(
+        app.world_mut().run_system_once(intersect).unwrap(),
+        [wall, moving]
+    );
+
+    // Moving static hurtbox doesn't change the index
+    app.world_mut()
+        .get_mut::<Transform>(wall)
+        .unwrap()
+        .translation = Vec3::new(500., 0., 0.);
+    app.update();
+    let registry = app
+        .world()
+        .get::<SpatialIndexRegistry<TestGroup>>(wall)
+        .unwrap();
+    assert_eq!(registry.current_position(), Vec2::new(0., -10.));
+
+    let hit = app
+        .world_mut()
+        .run_system_once(|query: SpatialIndexQuery<TestGroup>| {
+            query
+                .ray_cast(Ray2d::new(Vec2::new(5., 30.), Dir2::NEG_Y), 100., ())
+                .map(|hit| (hit.distance, hit.data))
+        })
+        .unwrap();
+    assert_eq!(hit, Some((38., wall)));
+
+    app.world_mut().despawn(wall);
+    app.update();
+    assert!(!spatial_index(&app).is_static(wall));
+    assert!(spatial_index(&app).static_bvh().is_empty());
+}
+
+#[test]
+fn queries_find_pending_and_skip_removed_static_hurtboxes() {
+    let mut index = SpatialIndex::<TestGroup>::new(ChunkGrid::new(10.));
+    let [baked, removed, pending, moving] = [0, 1, 2, 3].map(Entity::from_raw);
+    let aabb_at = |y: f32| Aabb2d::new(Vec2::new(0., y), Vec2::splat(2.));
+    index.insert_static(baked, aabb_at(-40.));
+    index.insert_static(removed, aabb_at(-10.));
+    index.bake();
+    index.remove(removed, aabb_at(-10.));
+    index.insert_static(pending, aabb_at(-20.));
+    index.insert(moving, aabb_at(-30.));
+    assert!(index.needs_baking());
+
+    // Grid hits are conservative, only the static ones are exact
+    let static_hits = |hits: Vec<(f32, Entity)>| {
+        assert!(hits.is_sorted_by(|(a, _), (b, _)| a <= b));
+        assert!(hits.iter().any(|(_, entity)| *entity == moving));
+        hits.into_iter()
+            .filter(|(_, entity)| *entity != moving)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        static_hits(index.query_ray(Vec2::ZERO, Dir2::NEG_Y, 100.).collect()),
+        [(18., pending), (38., baked)]
+    );
+    assert_eq!(
+        static_hits(
+            index
+                .query_swept_aabb(aabb_at(0.), Dir2::NEG_Y, 100.)
+                .collect()
+        ),
+        [(16., pending), (36., baked)]
+    );
+}
+
+#[test]
+fn static_marker_moves_registered_hurtbox_between_storages() {
+    let mut app = app();
+    let hurtbox = spawn_hurtbox(&mut app, Vec2::new(15., 15.), Vec2::splat(2.));
+    app.update();
+
+    app.world_mut()
+        .entity_mut(hurtbox)
+        .insert(StaticHurtbox::<TestGroup>::new());
+    app.update();
+    let index = spatial_index(&app);
+    assert!(index.is_static(hurtbox));
+    assert_eq!(index.iter_entities().collect::<Vec<_>>(), [hurtbox]);
+    assert!(entities_on_aabb(index, Vec2::splat(-100.), Vec2::splat(100.)).is_empty());
+
+    // Static hurtbox is not updated
+    app.world_mut()
+        .get_mut::<Transform>(hurtbox)
+        .unwrap()
+        .translation = Vec3::new(35., 15., 0.);
+    app.update();
+    assert_eq!(
+        spatial_index(&app)
+            .query_aabb(Aabb2d::new(Vec2::new(15., 15.), Vec2::ONE))
+            .collect::<Vec<_>>(),
+        [hurtbox]
+    );
+
+    app.world_mut()
+        .entity_mut(hurtbox)
+        .remove::<StaticHurtbox<TestGroup>>();
+    app.update();
+    let index = spatial_index(&app);
+    assert!(!index.is_static(hurtbox));
+    assert_eq!(index.iter_entities().collect::<Vec<_>>(), [hurtbox]);
+    assert_eq!(
+        entities_on_aabb(index, Vec2::splat(-100.), Vec2::splat(100.)),
+        [hurtbox]
+    );
+    assert_eq!(
+        entities_on_aabb(index, Vec2::new(30., 10.), Vec2::new(39., 19.)),
+        [hurtbox]
+    );
+
+    // Despawned static hurtbox is not moved back to the broad phase
+    app.world_mut()
+        .entity_mut(hurtbox)
+        .insert(StaticHurtbox::<TestGroup>::new());
+    app.world_mut().despawn(hurtbox);
+    app.update();
+    assert_eq!(spatial_index(&app).iter_entities().count(), 0);
+}
+
+#[test]
+fn removed_shape_or_transform_unregisters_hurtbox() {
+    let mut app = app();
+    let without_shape = spawn_hurtbox(&mut app, Vec2::new(15., 15.), Vec2::splat(2.));
+    let without_transform = spawn_hurtbox(&mut app, Vec2::new(16., 16.), Vec2::splat(2.));
+    let reinserted = spawn_hurtbox(&mut app, Vec2::new(14., 14.), Vec2::splat(2.));
+    app.update();
+
+    app.world_mut()
+        .entity_mut(without_shape)
+        .remove::<HurtboxShape<TestGroup>>();
+    app.world_mut()
+        .entity_mut(without_transform)
+        .remove::<Transform>();
+    // Shape is replaced, entity stays a hurtbox
+    app.world_mut()
+        .entity_mut(reinserted)
+        .remove::<HurtboxShape<TestGroup>>()
+        .insert(HurtboxShape::<TestGroup>(Rectangle::new(4., 4.)));
+    app.update();
+
+    assert_eq!(
+        entities_on_aabb(spatial_index(&app), Vec2::splat(10.), Vec2::splat(19.)),
+        [reinserted]
+    );
+    for entity in [without_shape, without_transform] {
+        assert!(app
+            .world()
+            .get::<SpatialIndexRegistry<TestGroup>>(entity)
+            .is_none());
+    }
+    assert!(app
+        .world()
+        .get::<SpatialIndexRegistry<TestGroup>>(reinserted)
+        .is_some());
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn consistency_checker_reports_stray_entity() {
+    let mut app = app_with_policy(ErrorPolicy::Event);
+    spawn_hurtbox(&mut app, Vec2::new(15., 15.), Vec2::splat(2.));
+    app.update();
+    assert!(drain_errors(&mut app).is_empty());
+
+    let stray = app.world_mut().spawn_empty().id();
+    app.world_mut()
+        .resource_mut::<SpatialIndex<TestGroup>>()
+        .insert(stray, Aabb2d::new(Vec2::ZERO, Vec2::ONE));
+    app.update();
+
+    assert_eq!(
+        drain_errors(&mut app),
+        [format!(
+            "spatial index is inconsistent, {stray} is stored, but is not a registered hurtbox"
+        )]
+    );
+}
+
+#[cfg(debug_assertions)]
+#[derive(bevy::ecs::schedule::ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct Manual;
+
+#[cfg(debug_assertions)]
+#[test]
+fn consistency_checker_runs_in_schedule_of_index() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        BumpErrorPlugin {
+            policy: ErrorPolicy::Event,
+        },
+        SpatialIndexPlugin::<TestGroup>::new(10.).in_schedule(Manual),
+        SpatialIndexDebugPlugin::<TestGroup>::default(),
+    ));
+    let stray = app.world_mut().spawn_empty().id();
+    app.world_mut()
+        .resource_mut::<SpatialIndex<TestGroup>>()
+        .insert(stray, Aabb2d::new(Vec2::ZERO, Vec2::ONE));
+    app.update();
+    assert!(drain_errors(&mut app).is_empty());
+
+    app.world_mut().run_schedule(Manual);
+    app.update();
+    assert_eq!(drain_errors(&mut app).len(), 1);
+}
+
+/// Chunk grid that panics when an entity it doesn't store is removed.
+#[derive(Clone)]
+struct StrictGrid(ChunkGrid);
+
+impl MapEntities for StrictGrid {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.0.map_entities(entity_mapper);
+    }
+}
+
+impl BroadPhase for StrictGrid {
+    fn insert(&mut self, entity: Entity, aabb: Aabb2d) {
+        self.0.insert(entity, aabb);
+    }
+
+    fn remove(&mut self, entity: Entity, aabb: Aabb2d) {
+        assert!(
+            self.0.iter_entities().any(|stored| stored == entity),
+            "{entity} is removed, but is not stored"
+        );
+        self.0.remove(entity, aabb);
+    }
+
+    fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter_entities()
+    }
+
+    fn query_aabb(&self, aabb: Aabb2d) -> impl Iterator<Item = Entity> + '_ {
+        self.0.query_aabb(aabb)
+    }
+
+    fn query_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        self.0.query_ray(origin, direction, max_distance)
+    }
+
+    fn query_swept_aabb(
+        &self,
+        aabb: Aabb2d,
+        direction: Dir2,
+        distance: f32,
+    ) -> impl Iterator<Item = (f32, Entity)> + '_ {
+        self.0.query_swept_aabb(aabb, direction, distance)
+    }
+}
+
+struct StrictGroup;
+
+impl CollisionImplementation<StrictGroup> for common::TestImplementation {}
+
+impl ColliderGroup for StrictGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = common::TestImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = StrictGrid;
+}
+
+#[test]
+fn removed_hurtbox_is_removed_from_index_once() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        SpatialIndexPlugin::<StrictGroup>::with_broad_phase(StrictGrid(ChunkGrid::new(10.))),
+    ));
+    let spawn = |app: &mut App, is_static: bool| {
+        let mut entity = app.world_mut().spawn((
+            HurtboxShape::<StrictGroup>(Rectangle::new(4., 4.)),
+            Transform::default(),
+        ));
+        if is_static {
+            entity.insert(StaticHurtbox::<StrictGroup>::new());
+        }
+        entity.id()
+    };
+
+    for is_static in [false, true] {
+        let reshaped = spawn(&mut app, is_static);
+        let despawned = spawn(&mut app, is_static);
+        let unshaped = spawn(&mut app, is_static);
+        app.update();
+
+        app.world_mut()
+            .entity_mut(reshaped)
+            .insert(HurtboxShape::<StrictGroup>(Rectangle::new(2., 2.)));
+        app.world_mut().entity_mut(despawned).despawn();
+        app.world_mut()
+            .entity_mut(unshaped)
+            .remove::<HurtboxShape<StrictGroup>>();
+        app.update();
+
+        let index = app.world().resource::<SpatialIndex<StrictGroup>>();
+        let mut stored: Vec<Entity> = index.iter_entities().collect();
+        stored.sort();
+        assert!(stored.contains(&reshaped));
+        assert!(!stored.contains(&despawned));
+        assert!(!stored.contains(&unshaped));
+        app.world_mut().entity_mut(reshaped).despawn();
+    }
+}
+
+#[test]
+fn entity_without_shape_is_not_indexed() {
+    let mut app = app();
+    app.world_mut().spawn((
+        Transform::from_xyz(15., 15., 0.),
+        SpatialIndexRegistry::<TestGroup>::default(),
+    ));
+    app.update();
+
+    assert!(
+        entities_on_aabb(spatial_index(&app), Vec2::splat(-100.), Vec2::splat(100.)).is_empty()
+    );
+}
+
+#[test]
+fn nearest_hurtbox_within_radius() {
+    let mut app = app();
+    spawn_hurtbox(&mut app, Vec2::new(30., 0.), Vec2::splat(2.));
+    let near = spawn_hurtbox(&mut app, Vec2::new(-20., 0.), Vec2::splat(2.));
+    app.update();
+
+    let nearest = |radius: f32| {
+        move |query: SpatialIndexQuery<TestGroup>| {
+            let hitbox = Rectangle::new(2., 2.);
+            query
+                .nearest(Collider::new(&hitbox, Vec2::ZERO), radius, ())
+                .map(|(distance, entity)| (distance.distance, entity))
+        }
+    };
+
+    let (distance, entity) = app
+        .world_mut()
+        .run_system_once(nearest(100.))
+        .unwrap()
+        .expect("expected hurtbox");
+    assert_eq!(entity, near);
+    assert!((distance - 17.).abs() < 1e-4);
+
+    assert!(app
+        .world_mut()
+        .run_system_once(nearest(10.))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn ray_cast_returns_hits_sorted_by_distance() {
+    let mut app = app();
+    let far = spawn_hurtbox(&mut app, Vec2::new(55., 2.), Vec2::splat(2.));
+    let near = spawn_hurtbox(&mut app, Vec2::new(25., 0.), Vec2::splat(2.));
+    // Not on the ray
+    spawn_hurtbox(&mut app, Vec2::new(40., -20.), Vec2::splat(2.));
+    // Behind the origin of the ray
+    spawn_hurtbox(&mut app, Vec2::new(-30., 0.), Vec2::splat(2.));
+    app.update();
+
+    let ray = Ray2d::new(Vec2::new(0., 1.), Dir2::X);
+    let hits = app
+        .world_mut()
+        .run_system_once(move |query: SpatialIndexQuery<TestGroup>| {
+            query.ray_cast_all(ray, 100., ())
+        })
+        .unwrap();
+    assert_eq!(
+        hits.iter().map(|hit| hit.data).collect::<Vec<_>>(),
+        [near, far]
+    );
+    assert!((hits[0].distance - 23.).abs() < 1e-4);
+    assert!(hits[0].point.abs_diff_eq(Vec2::new(23., 1.), 1e-4));
+    assert_eq!(hits[0].normal, Dir2::NEG_X);
+    assert!((hits[1].distance - 53.).abs() < 1e-4);
+
+    let first = app
+        .world_mut()
+        .run_system_once(move |query: SpatialIndexQuery<TestGroup>| query.ray_cast(ray, 100., ()))
+        .unwrap()
+        .expect("expected hit");
+    assert_eq!(first.data, near);
+
+    let short = app
+        .world_mut()
+        .run_system_once(move |query: SpatialIndexQuery<TestGroup>| query.ray_cast(ray, 20., ()))
+        .unwrap();
+    assert!(short.is_none());
+}
+
+#[test]
+fn ray_passes_chunks_in_order() {
+    let mut app = app();
+    let hurtboxes = [
+        spawn_hurtbox(&mut app, Vec2::new(-5., -5.), Vec2::splat(1.)),
+        spawn_hurtbox(&mut app, Vec2::new(-25., -15.), Vec2::splat(1.)),
+        spawn_hurtbox(&mut app, Vec2::new(-45., -25.), Vec2::splat(1.)),
+    ];
+    app.update();
+
+    let direction = Dir2::new(Vec2::new(-2., -1.)).unwrap();
+    let chunks: Vec<(f32, Entity)> = spatial_index(&app)
+        .iter_chunks_on_ray(Vec2::ZERO, direction, 60.)
+        .flat_map(|(distance, chunk)| chunk.iter().map(move |entity| (distance, *entity)))
+        .collect();
+
+    assert_eq!(
+        chunks.iter().map(|(_, entity)| *entity).collect::<Vec<_>>(),
+        hurtboxes
+    );
+    assert!(chunks.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+}
+
+#[test]
+fn swept_aabb_visits_only_crossed_chunks() {
+    let mut app = app();
+    // One hurtbox in each chunk of 20 by 20 chunks
+    for x in 0..20 {
+        for y in 0..20 {
+            spawn_hurtbox(
+                &mut app,
+                Vec2::new(x as f32 * 10. + 5., y as f32 * 10. + 5.),
+                Vec2::splat(1.),
+            );
+        }
+    }
+    app.update();
+
+    let aabb = Aabb2d {
+        min: Vec2::splat(1.),
+        max: Vec2::splat(3.),
+    };
+    let direction = Dir2::new(Vec2::ONE).unwrap();
+    let distance = 190. * std::f32::consts::SQRT_2;
+    let index = spatial_index(&app);
+
+    let merged = index
+        .iter_chunks_on_aabb(Aabb2d {
+            min: aabb.min,
+            max: aabb.max + direction * distance,
+        })
+        .count();
+    let swept: Vec<(f32, &Vec<Entity>)> = index
+        .iter_chunks_on_swept_aabb(aabb, direction, distance)
+        .collect();
+
+    assert_eq!(merged, 400);
+    // Diagonal and the chunks next to it
+    assert_eq!(swept.len(), 58);
+    assert!(swept.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    assert_eq!(swept[0].0, 0.);
+}
+
+/// Setup the chunk counts of the swept cast were measured on:
+/// 100 by 100 chunks of 10 px with a hurtbox in each, 4 by 4 box cast ~1400 px along the diagonal.
+#[test]
+fn long_diagonal_sweep_visits_few_chunks() {
+    let mut app = app();
+    for x in 0..100 {
+        for y in 0..100 {
+            spawn_hurtbox(
+                &mut app,
+                Vec2::new(x as f32 * 10. + 5., y as f32 * 10. + 5.),
+                Vec2::splat(1.),
+            );
+        }
+    }
+
+    let aabb = Aabb2d {
+        min: Vec2::splat(1.),
+        max: Vec2::splat(5.),
+    };
+    let direction = Dir2::new(Vec2::ONE).unwrap();
+    let distance = 990. * std::f32::consts::SQRT_2;
+    let index = spatial_index(&app);
+
+    let merged = index
+        .iter_chunks_on_aabb(Aabb2d {
+            min: aabb.min,
+            max: aabb.max + direction * distance,
+        })
+        .count();
+    let swept = index
+        .iter_chunks_on_swept_aabb(aabb, direction, distance)
+        .count();
+
+    assert_eq!(merged, 10000);
+    // Diagonal and the chunks next to it
+    assert_eq!(swept, 298);
+}
+
+#[test]
+fn cast_finds_first_hurtbox_on_long_diagonal() {
+    let mut app = app();
+    let first = spawn_hurtbox(&mut app, Vec2::new(105., 105.), Vec2::splat(2.));
+    spawn_hurtbox(&mut app, Vec2::new(205., 205.), Vec2::splat(2.));
+    // Inside of the merged aabb, but far from the path
+    spawn_hurtbox(&mut app, Vec2::new(5., 250.), Vec2::splat(2.));
+    app.update();
+
+    let direction = Dir2::new(Vec2::ONE).unwrap();
+    let (all, closest) = app
+        .world_mut()
+        .run_system_once(move |query: SpatialIndexQuery<TestGroup>| {
+            let hitbox = Rectangle::new(2., 2.);
+            let collider = Collider::new(&hitbox, Vec2::ZERO);
+            let all = query.cast(collider, direction, 400., ()).count();
+            let closest = query.cast_first(collider, direction, 400., ());
+            (
+                all,
+                closest.map(|(distance, _, _, entity)| (distance, entity)),
+            )
+        })
+        .unwrap();
+
+    assert_eq!(all, 2);
+    let (distance, entity) = closest.expect("expected collision");
+    assert_eq!(entity, first);
+    assert!((distance - 102. * std::f32::consts::SQRT_2).abs() < 1e-3);
+}
@@ -0,0 +1,469 @@
+#![cfg(feature = "implementations")]
+
+use std::{f32::consts::FRAC_PI_2, time::Duration};
+
+use bevy::{math::bounding::Aabb2d, prelude::*, time::TimeUpdateStrategy};
+use bevy_bump::prelude::*;
+
+struct SlideGroup;
+
+struct SlideImplementation;
+
+impl CollisionImplementation<SlideGroup> for SlideImplementation {}
+
+impl VelocityImplementation<SlideGroup> for SlideImplementation {
+    type ReportStrategy = ExtendHitboxComponent;
+    type Response = Slide;
+    type Depenetration = Depenetrate;
+}
+
+impl ColliderGroup for SlideGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = SlideImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+fn app(time_step: Duration) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SpatialIndexPlugin::<SlideGroup>::new(10.)))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(time_step));
+    app
+}
+
+/// Wall spanning from 15 to 25 on the x axis.
+fn spawn_wall(app: &mut App) -> Entity {
+    app.world_mut()
+        .spawn((
+            HurtboxShape::<SlideGroup>(Rectangle::new(10., 200.)),
+            Transform::from_xyz(20., 0., 0.),
+        ))
+        .id()
+}
+
+fn spawn_body(app: &mut App, velocity: Vec2) -> Entity {
+    app.world_mut()
+        .spawn((
+            HitboxShape::<SlideGroup>(Rectangle::new(10., 10.)),
+            Transform::default(),
+            Velocity(velocity),
+        ))
+        .id()
+}
+
+fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world()
+        .get::<Transform>(entity)
+        .unwrap()
+        .translation
+        .xy()
+}
+
+#[test]
+fn body_slides_along_wall() {
+    let mut app = app(Duration::from_millis(100));
+    app.add_velocity_group::<SlideGroup>();
+    spawn_wall(&mut app);
+    let body = spawn_body(&mut app, Vec2::new(200., 100.));
+
+    // First update has zero delta time
+    app.update();
+    assert_eq!(position(&app, body), Vec2::ZERO);
+
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 10.), 1e-3));
+
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 20.), 1e-3));
+}
+
+#[test]
+fn body_moves_away_from_wall() {
+    let mut app = app(Duration::from_millis(100));
+    app.add_velocity_group::<SlideGroup>();
+    spawn_wall(&mut app);
+    let body = spawn_body(&mut app, Vec2::new(200., 0.));
+    app.update();
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 0.), 1e-3));
+
+    app.world_mut().get_mut::<Velocity>(body).unwrap().0 = Vec2::new(-100., 0.);
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(0., 0.), 1e-3));
+}
+
+#[test]
+fn collisions_are_reported() {
+    let mut app = app(Duration::from_millis(100));
+    app.add_velocity_group::<SlideGroup>();
+    let wall = spawn_wall(&mut app);
+    let body = spawn_body(&mut app, Vec2::new(200., 100.));
+    app.update();
+    app.update();
+
+    let collisions = &app
+        .world()
+        .get::<HitboxCollisions<SlideGroup>>(body)
+        .unwrap()
+        .0;
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].hurtbox, wall);
+    assert_eq!(collisions[0].normal, Some(Dir2::NEG_X));
+    assert!(collisions[0]
+        .global_position
+        .abs_diff_eq(Vec2::new(10., 5.), 1e-3));
+}
+
+#[test]
+fn body_slides_along_wall_in_fixed_update() {
+    let time_step = Time::<Fixed>::default().timestep();
+    let mut app = app(time_step);
+    app.add_velocity_group_in_schedule::<SlideGroup>(FixedUpdate);
+    spawn_wall(&mut app);
+    let body = spawn_body(&mut app, Vec2::new(10., 5.) / time_step.as_secs_f32());
+
+    // Fixed update doesn't run on the first update
+    app.update();
+    assert_eq!(position(&app, body), Vec2::ZERO);
+
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 5.), 1e-3));
+
+    app.update();
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 15.), 1e-3));
+}
+
+struct AnyShapeGroup;
+
+impl CollisionImplementation<AnyShapeGroup> for SlideImplementation {}
+
+impl VelocityImplementation<AnyShapeGroup> for SlideImplementation {
+    type ReportStrategy = ExtendHitboxComponent;
+    type Response = Slide;
+    type Depenetration = Depenetrate;
+}
+
+impl ColliderGroup for AnyShapeGroup {
+    type Hitbox = AnyShape;
+    type Hurtbox = AnyShape;
+    type Implementation = SlideImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+#[test]
+fn circle_slides_along_any_shape_wall() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        SpatialIndexPlugin::<AnyShapeGroup>::new(10.),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .add_velocity_group::<AnyShapeGroup>();
+    app.world_mut().spawn((
+        HurtboxShape::<AnyShapeGroup>(Rectangle::new(10., 200.).into()),
+        Transform::from_xyz(20., 0., 0.),
+    ));
+    let body = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<AnyShapeGroup>(Circle::new(5.).into()),
+            Transform::default(),
+            Velocity(Vec2::new(200., 100.)),
+        ))
+        .id();
+
+    app.update();
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 10.), 1e-3));
+}
+
+struct CompoundGroup;
+
+impl CollisionImplementation<CompoundGroup> for SlideImplementation {}
+
+impl VelocityImplementation<CompoundGroup> for SlideImplementation {
+    type ReportStrategy = ExtendHitboxComponent;
+    type Response = Slide;
+    type Depenetration = Depenetrate;
+}
+
+impl ColliderGroup for CompoundGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Compound<Rectangle>;
+    type Implementation = SlideImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+#[test]
+fn collided_compound_part_is_reported() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        SpatialIndexPlugin::<CompoundGroup>::new(10.),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .add_velocity_group::<CompoundGroup>();
+    app.world_mut().spawn((
+        HurtboxShape::<CompoundGroup>(Compound::new([
+            (Vec2::new(0., -50.), Rectangle::new(10., 90.)),
+            (Vec2::new(0., 50.), Rectangle::new(10., 90.)),
+        ])),
+        Transform::from_xyz(20., 0., 0.),
+    ));
+    let body = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<CompoundGroup>(Rectangle::new(10., 10.)),
+            Transform::from_xyz(0., 30., 0.),
+            Velocity(Vec2::new(200., 0.)),
+        ))
+        .id();
+
+    app.update();
+    app.update();
+
+    let collisions = &app
+        .world()
+        .get::<HitboxCollisions<CompoundGroup>>(body)
+        .unwrap()
+        .0;
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(
+        collisions[0].parts,
+        Some(ColliderParts {
+            hitbox: 0,
+            hurtbox: 1
+        })
+    );
+}
+
+struct RotatedGroup;
+
+impl CollisionImplementation<RotatedGroup> for SlideImplementation {}
+
+impl VelocityImplementation<RotatedGroup> for SlideImplementation {
+    type ReportStrategy = ExtendHitboxComponent;
+    type Response = Slide;
+    type Depenetration = Depenetrate;
+}
+
+impl ColliderGroup for RotatedGroup {
+    type Hitbox = Rectangle;
+    type Hurtbox = Rectangle;
+    type Implementation = SlideImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+
+    const USE_ROTATION: bool = true;
+}
+
+#[test]
+fn body_slides_along_rotated_wall() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SpatialIndexPlugin::<RotatedGroup>::new(10.)))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )))
+        .add_velocity_group::<RotatedGroup>();
+    // Same wall as in other tests, but lying on the side and rotated upright
+    app.world_mut().spawn((
+        HurtboxShape::<RotatedGroup>(Rectangle::new(200., 10.)),
+        Transform::from_xyz(20., 0., 0.).with_rotation(Quat::from_rotation_z(FRAC_PI_2)),
+    ));
+    let body = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<RotatedGroup>(Rectangle::new(10., 10.)),
+            Transform::default(),
+            Velocity(Vec2::new(200., 100.)),
+        ))
+        .id();
+
+    app.update();
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 10.), 1e-3));
+}
+
+#[test]
+fn body_inside_wall_is_pushed_out() {
+    let mut app = app(Duration::from_millis(100));
+    app.add_velocity_group::<SlideGroup>();
+    spawn_wall(&mut app);
+    let body = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<SlideGroup>(Rectangle::new(10., 10.)),
+            Transform::from_xyz(12., 0., 0.),
+            Velocity(Vec2::ZERO),
+        ))
+        .id();
+
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 0.), 1e-3));
+
+    // Body that is pushed out still moves, sliding along the wall
+    app.world_mut().entity_mut(body).insert((
+        Transform::from_xyz(13., 0., 0.),
+        Velocity(Vec2::new(100., 100.)),
+    ));
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 10.), 1e-3));
+}
+
+#[test]
+fn body_that_is_also_a_hurtbox_does_not_collide_with_itself() {
+    let mut app = app(Duration::from_millis(100));
+    app.add_velocity_group::<SlideGroup>();
+    let body = spawn_body(&mut app, Vec2::new(100., 0.));
+    app.world_mut()
+        .entity_mut(body)
+        .insert(HurtboxShape::<SlideGroup>(Rectangle::new(10., 10.)));
+    app.update();
+    app.update();
+
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(10., 0.), 1e-3));
+    assert!(app
+        .world()
+        .get::<HitboxCollisions<SlideGroup>>(body)
+        .unwrap()
+        .0
+        .is_empty());
+}
+
+#[test]
+fn child_of_rotated_parent_moves_in_global_space() {
+    let mut app = app(Duration::from_millis(100));
+    app.add_plugins(TransformPlugin)
+        .add_velocity_group::<SlideGroup>();
+    spawn_wall(&mut app);
+    let parent = app
+        .world_mut()
+        .spawn(
+            Transform::from_xyz(-20., 0., 0.)
+                .with_rotation(Quat::from_rotation_z(FRAC_PI_2))
+                .with_scale(Vec3::splat(2.)),
+        )
+        .id();
+    let body = spawn_body(&mut app, Vec2::new(200., 0.));
+    app.world_mut().entity_mut(body).set_parent(parent);
+
+    app.update();
+    app.update();
+    let global_position = |app: &App| {
+        app.world()
+            .get::<GlobalTransform>(body)
+            .unwrap()
+            .translation()
+            .xy()
+    };
+    assert!(global_position(&app).abs_diff_eq(Vec2::new(0., 0.), 1e-3));
+
+    // Stops at the wall, even though it moves along the y axis of the parent
+    app.update();
+    assert!(global_position(&app).abs_diff_eq(Vec2::new(10., 0.), 1e-3));
+}
+
+/// Rectangle that can't find out how deep it overlaps other shapes.
+struct Sensor(Rectangle);
+
+impl Bounded<Aabb2d> for Sensor {
+    fn bounding(&self) -> Aabb2d {
+        self.0.bounding()
+    }
+}
+
+impl ColliderInteraction<Rectangle> for Sensor {
+    fn intersect(&self, self_position: Vec2, other: &Rectangle, other_position: Vec2) -> bool {
+        self.0.intersect(self_position, other, other_position)
+    }
+
+    fn cast(
+        &self,
+        self_position: Vec2,
+        other: &Rectangle,
+        other_position: Vec2,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2)> {
+        self.0
+            .cast(self_position, other, other_position, offset_dir, offset_len)
+    }
+
+    fn intersect_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &Rectangle,
+        other_isometry: Isometry2d,
+    ) -> bool {
+        self.0
+            .intersect_isometry(self_isometry, other, other_isometry)
+    }
+
+    fn cast_isometry(
+        &self,
+        self_isometry: Isometry2d,
+        other: &Rectangle,
+        other_isometry: Isometry2d,
+        offset_dir: Dir2,
+        offset_len: f32,
+    ) -> Option<(f32, Dir2, ColliderParts)> {
+        self.0
+            .cast_isometry(self_isometry, other, other_isometry, offset_dir, offset_len)
+    }
+}
+
+struct SensorGroup;
+
+impl CollisionImplementation<SensorGroup> for SlideImplementation {}
+
+impl VelocityImplementation<SensorGroup> for SlideImplementation {
+    type ReportStrategy = ExtendHitboxComponent;
+    type Response = Slide;
+    type Depenetration = NoDepenetration;
+}
+
+impl ColliderGroup for SensorGroup {
+    type Hitbox = Sensor;
+    type Hurtbox = Rectangle;
+    type Implementation = SlideImplementation;
+    type Filter = Monitorable;
+    type BroadPhase = ChunkGrid;
+}
+
+#[test]
+fn hitbox_without_penetration_is_not_pushed_out() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SpatialIndexPlugin::<SensorGroup>::new(10.)))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )))
+        .add_velocity_group::<SensorGroup>();
+    app.world_mut().spawn((
+        HurtboxShape::<SensorGroup>(Rectangle::new(10., 200.)),
+        Transform::from_xyz(20., 0., 0.),
+    ));
+    let body = app
+        .world_mut()
+        .spawn((
+            HitboxShape::<SensorGroup>(Sensor(Rectangle::new(10., 10.))),
+            Transform::from_xyz(12., 0., 0.),
+            Velocity(Vec2::ZERO),
+        ))
+        .id();
+
+    app.update();
+    assert_eq!(position(&app, body), Vec2::new(12., 0.));
+
+    // Still moves out of the wall
+    app.world_mut().get_mut::<Velocity>(body).unwrap().0 = Vec2::new(-100., 0.);
+    app.update();
+    assert!(position(&app, body).abs_diff_eq(Vec2::new(2., 0.), 1e-3));
+}